@@ -3,6 +3,7 @@
 use super::error::Sdi12Error;
 use core::convert::TryFrom;
 use core::fmt;
+use core::str::FromStr;
 
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub struct Sdi12Addr(char);
@@ -11,6 +12,11 @@ impl Sdi12Addr {
     pub const DEFAULT_ADDRESS: Sdi12Addr = Sdi12Addr('0');
     pub const QUERY_ADDRESS: Sdi12Addr = Sdi12Addr('?');
 
+    /// Every valid sensor address character, in scan order: `0-9`, `a-z`, `A-Z`.
+    /// The first ten entries are the standard (digit) addresses.
+    pub const VALID_ADDRESS_CHARS: &'static [u8; 62] =
+        b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
     /// Creates a new `Sdi12Addr` if the given character is a valid address.
     /// Returns `Result<Self, Sdi12Error<()>>` because validation itself
     /// cannot cause an I/O error.
@@ -23,7 +29,12 @@ impl Sdi12Addr {
         }
     }
 
-    // Unsafe constructor remains the same
+    /// Creates a new `Sdi12Addr` without validating the character.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure `address_char` is a valid SDI-12 address
+    /// character (`0-9`, `a-z`, `A-Z`) or the query address `?`.
     pub const unsafe fn new_unchecked(address_char: char) -> Self {
         Sdi12Addr(address_char)
     }
@@ -40,14 +51,12 @@ impl Sdi12Addr {
 
     #[inline]
     pub const fn is_standard(&self) -> bool {
-        // This one was okay because '0'..='9' is a single range pattern
-        matches!(self.0, '0'..='9')
+        self.0.is_ascii_digit()
     }
 
     #[inline]
     pub const fn is_extended(&self) -> bool {
-        // CORRECTED: Use '|' directly as a pattern separator
-        matches!(self.0, 'a'..='z' | 'A'..='Z')
+        self.0.is_ascii_alphabetic()
     }
 
     #[inline]
@@ -55,6 +64,35 @@ impl Sdi12Addr {
         // CORRECTED: Use '|' directly as a pattern separator
         matches!(c, '0'..='9' | 'a'..='z' | 'A'..='Z')
     }
+
+    /// Iterates over the standard addresses `0`-`9`.
+    pub fn iter_standard() -> impl Iterator<Item = Sdi12Addr> + Clone {
+        Self::VALID_ADDRESS_CHARS[..10].iter().map(|&b| Sdi12Addr(b as char))
+    }
+
+    /// Iterates over all 62 sensor addresses (`0-9`, `a-z`, `A-Z`), excluding `?`.
+    pub fn iter_all() -> impl Iterator<Item = Sdi12Addr> + Clone {
+        Self::VALID_ADDRESS_CHARS.iter().map(|&b| Sdi12Addr(b as char))
+    }
+
+    /// The address after this one in `iter_all` order (`9` is followed by `a`,
+    /// `z` by `A`), or `None` after `Z` and for the query address.
+    pub fn next(&self) -> Option<Sdi12Addr> {
+        let position = self.scan_position()?;
+        Self::VALID_ADDRESS_CHARS.get(position + 1).map(|&b| Sdi12Addr(b as char))
+    }
+
+    /// The address before this one in `iter_all` order, or `None` before `0`
+    /// and for the query address.
+    pub fn prev(&self) -> Option<Sdi12Addr> {
+        let position = self.scan_position()?.checked_sub(1)?;
+        Some(Sdi12Addr(Self::VALID_ADDRESS_CHARS[position] as char))
+    }
+
+    /// Index of this address in `VALID_ADDRESS_CHARS`.
+    fn scan_position(&self) -> Option<usize> {
+        Self::VALID_ADDRESS_CHARS.iter().position(|&b| b as char == self.0)
+    }
 }
 
 impl Default for Sdi12Addr {
@@ -82,6 +120,35 @@ impl TryFrom<char> for Sdi12Addr {
     }
 }
 
+impl FromStr for Sdi12Addr {
+    type Err = Sdi12Error<()>;
+
+    /// Parses a string holding exactly one address character (e.g. from a config file).
+    /// Empty or multi-character strings are `InvalidFormat`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Self::new(c),
+            _ => Err(Sdi12Error::InvalidFormat),
+        }
+    }
+}
+
+// Serialized as the bare address character, e.g. `"0"` in JSON.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Sdi12Addr {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_char(self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Sdi12Addr {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let c = char::deserialize(deserializer)?;
+        Sdi12Addr::new(c).map_err(serde::de::Error::custom)
+    }
+}
 
 impl From<Sdi12Addr> for char {
     fn from(value: Sdi12Addr) -> Self {
@@ -89,6 +156,20 @@ impl From<Sdi12Addr> for char {
     }
 }
 
+/// Compares with the address character, so `addr == '0'` works. A `char` that
+/// isn't a valid address never equals any `Sdi12Addr`.
+impl PartialEq<char> for Sdi12Addr {
+    fn eq(&self, other: &char) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<Sdi12Addr> for char {
+    fn eq(&self, other: &Sdi12Addr) -> bool {
+        *self == other.0
+    }
+}
+
 impl fmt::Display for Sdi12Addr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -100,15 +181,6 @@ impl fmt::Display for Sdi12Addr {
 mod tests {
     use super::*;
 
-    // Mock error type is no longer strictly needed inside the tests for `new` or `try_from`
-    // because they now return `Sdi12Error<()>` which doesn't involve a generic `E`.
-    // We might still need it later for testing functions that *do* take an E.
-    #[derive(Debug, Clone, PartialEq, Eq)]
-    struct MockIoError;
-    impl fmt::Display for MockIoError {
-        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "Mock I/O Error") }
-    }
-
     #[test]
     fn test_valid_addresses() {
         assert!(Sdi12Addr::new('0').is_ok());
@@ -140,5 +212,64 @@ mod tests {
         assert!(matches!(Sdi12Addr::try_from('*'), Err(Sdi12Error::InvalidAddress('*'))));
     }
 
+    #[test]
+    fn test_address_iterators() {
+        assert_eq!(Sdi12Addr::iter_standard().count(), 10);
+        assert!(Sdi12Addr::iter_standard().all(|a| a.is_standard()));
+        assert_eq!(Sdi12Addr::iter_standard().next(), Some(Sdi12Addr('0')));
+
+        assert_eq!(Sdi12Addr::iter_all().count(), 62);
+        assert!(Sdi12Addr::iter_all().all(|a| Sdi12Addr::is_valid_address_char(a.as_char())));
+        assert!(Sdi12Addr::iter_all().all(|a| !a.is_query()));
+        assert_eq!(Sdi12Addr::iter_all().last(), Some(Sdi12Addr('Z')));
+        assert!(Sdi12Addr::iter_all().take(10).eq(Sdi12Addr::iter_standard()));
+    }
+
+    #[test]
+    fn test_next_prev_follow_scan_order() {
+        let walked = core::iter::successors(Some(Sdi12Addr('0')), Sdi12Addr::next);
+        assert!(walked.eq(Sdi12Addr::iter_all()));
+        let walked_back = core::iter::successors(Some(Sdi12Addr('Z')), Sdi12Addr::prev);
+        assert!(walked_back.eq(Sdi12Addr::VALID_ADDRESS_CHARS.iter().rev().map(|&b| Sdi12Addr(b as char))));
+
+        assert_eq!(Sdi12Addr('9').next(), Some(Sdi12Addr('a')));
+        assert_eq!(Sdi12Addr('A').prev(), Some(Sdi12Addr('z')));
+        assert_eq!(Sdi12Addr('Z').next(), None);
+        assert_eq!(Sdi12Addr('0').prev(), None);
+        assert_eq!(Sdi12Addr::QUERY_ADDRESS.next(), None);
+        assert_eq!(Sdi12Addr::QUERY_ADDRESS.prev(), None);
+    }
+
+    #[test]
+    fn test_compare_with_char() {
+        let addr = Sdi12Addr::new('0').unwrap();
+        assert!(addr == '0');
+        assert!('0' == addr);
+        assert!(addr != '1');
+        assert!('Z' != addr);
+        // Query address compares with '?' both ways
+        assert!(Sdi12Addr::QUERY_ADDRESS == '?');
+        assert!('?' == Sdi12Addr::QUERY_ADDRESS);
+        // Invalid characters never match
+        assert!(Sdi12Addr::iter_all().all(|a| a != '$' && ' ' != a));
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("3".parse::<Sdi12Addr>().unwrap(), Sdi12Addr('3'));
+        assert_eq!("z".parse::<Sdi12Addr>().unwrap(), Sdi12Addr('z'));
+        assert!(matches!("".parse::<Sdi12Addr>(), Err(Sdi12Error::InvalidFormat)));
+        assert!(matches!("12".parse::<Sdi12Addr>(), Err(Sdi12Error::InvalidFormat)));
+        assert!(matches!("*".parse::<Sdi12Addr>(), Err(Sdi12Error::InvalidAddress('*'))));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_as_char() {
+        assert_eq!(serde_json::to_string(&Sdi12Addr('a')).unwrap(), "\"a\"");
+        assert_eq!(serde_json::from_str::<Sdi12Addr>("\"7\"").unwrap(), Sdi12Addr('7'));
+        assert!(serde_json::from_str::<Sdi12Addr>("\"*\"").is_err());
+    }
+
     // test_into_char, test_display, test_as_char, test_is_valid_address_char, test_new_unchecked remain the same
 }
\ No newline at end of file
@@ -1,8 +1,11 @@
 // src/common/command.rs
 
 use super::address::Sdi12Addr;
+use super::error::Sdi12Error;
+use super::timing;
 use core::convert::TryFrom;
 use core::fmt::{self, Write}; // Need core::fmt::Write
+use core::time::Duration;
 use arrayvec::ArrayString; // Use ArrayString for formatting
 
 // --- Conditionally import String ---
@@ -37,6 +40,9 @@ pub enum CommandFormatError {
     BufferOverflow,
     /// A formatting error occurred (e.g., writing number failed).
     FmtError,
+    /// `aAb!` with `?` as the new address, which would leave the sensor
+    /// unreachable by address (see `Command::change_address`).
+    InvalidNewAddress,
 }
 impl From<core::fmt::Error> for CommandFormatError {
     fn from(_: core::fmt::Error) -> Self { CommandFormatError::FmtError }
@@ -48,6 +54,7 @@ impl fmt::Display for CommandFormatError {
         match self {
             CommandFormatError::BufferOverflow => write!(f, "Buffer overflow during formatting"),
             CommandFormatError::FmtError => write!(f, "Internal formatting error"),
+            CommandFormatError::InvalidNewAddress => write!(f, "'?' is not a valid new address"),
         }
     }
 }
@@ -57,6 +64,8 @@ impl fmt::Display for CommandFormatError {
 
 /// Represents the index `n` for M[n], MC[n], C[n], CC[n] commands.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "Option<u8>", into = "Option<u8>"))]
 pub enum MeasurementIndex {
     Base,
     Indexed(u8), // 1-9
@@ -73,10 +82,48 @@ impl MeasurementIndex {
     pub fn as_option(&self) -> Option<u8> {
         match self { Self::Base => None, Self::Indexed(i) => Some(*i) }
     }
+
+    /// Builds an `Indexed` measurement index from a raw ASCII digit (`b'1'`..=`b'9'`).
+    ///
+    /// The base form (`aM!`) has no digit on the wire, so this never returns `Base`.
+    /// `b'0'` and any non-digit byte are rejected with `MeasurementOutOfRange`.
+    pub fn from_ascii_digit(c: u8) -> Result<Self, CommandIndexError> {
+        match c {
+            b'1'..=b'9' => Ok(Self::Indexed(c - b'0')),
+            _ => Err(CommandIndexError::MeasurementOutOfRange),
+        }
+    }
+}
+
+impl TryFrom<Option<u8>> for MeasurementIndex {
+    type Error = CommandIndexError;
+    fn try_from(value: Option<u8>) -> Result<Self, Self::Error> { Self::new(value) }
+}
+impl From<MeasurementIndex> for Option<u8> {
+    fn from(index: MeasurementIndex) -> Self { index.as_option() }
+}
+/// Compares against the `Option<u8>` form: `Base == None`, `Indexed(3) == Some(3)`.
+impl PartialEq<Option<u8>> for MeasurementIndex {
+    fn eq(&self, other: &Option<u8>) -> bool { self.as_option() == *other }
+}
+impl PartialEq<MeasurementIndex> for Option<u8> {
+    fn eq(&self, other: &MeasurementIndex) -> bool { *self == other.as_option() }
+}
+/// Formats the index as it appears on the wire: `Base` writes nothing (`aM!`),
+/// `Indexed(n)` writes the digit (`aM3!`).
+impl fmt::Display for MeasurementIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Base => Ok(()),
+            Self::Indexed(i) => write!(f, "{}", i),
+        }
+    }
 }
 
 /// Represents the index `n` for R[n], RC[n] commands.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u8", into = "u8"))]
 pub struct ContinuousIndex(u8); // 0-9
 
 impl ContinuousIndex {
@@ -84,14 +131,28 @@ impl ContinuousIndex {
         if index <= 9 { Ok(Self(index)) } else { Err(CommandIndexError::ContinuousOutOfRange) }
     }
     pub fn value(&self) -> u8 { self.0 }
+
+    /// Builds a continuous index from a raw ASCII digit (`b'0'`..=`b'9'`).
+    /// Unlike measurement indices, `b'0'` is valid here (`aR0!`).
+    pub fn from_ascii_digit(c: u8) -> Result<Self, CommandIndexError> {
+        if c.is_ascii_digit() { Ok(Self(c - b'0')) } else { Err(CommandIndexError::ContinuousOutOfRange) }
+    }
 }
 impl TryFrom<u8> for ContinuousIndex {
     type Error = CommandIndexError;
     fn try_from(value: u8) -> Result<Self, Self::Error> { Self::new(value) }
 }
+impl From<ContinuousIndex> for u8 {
+    fn from(index: ContinuousIndex) -> Self { index.value() }
+}
+impl fmt::Display for ContinuousIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+}
 
 /// Represents the index `n` for D[n], DB[n] commands.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u16", into = "u16"))]
 pub struct DataIndex(u16); // 0-999
 
 impl DataIndex {
@@ -104,9 +165,18 @@ impl TryFrom<u16> for DataIndex {
     type Error = CommandIndexError;
     fn try_from(value: u16) -> Result<Self, Self::Error> { Self::new(value) }
 }
+impl From<DataIndex> for u16 {
+    fn from(index: DataIndex) -> Self { index.value() }
+}
+/// Unpadded, as in `aD10!`.
+impl fmt::Display for DataIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+}
 
 /// Represents the parameter index `nnn` for Identify Measurement Parameter commands.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u16", into = "u16"))]
 pub struct IdentifyParameterIndex(u16); // 1-999
 
 impl IdentifyParameterIndex {
@@ -119,11 +189,20 @@ impl TryFrom<u16> for IdentifyParameterIndex {
     type Error = CommandIndexError;
     fn try_from(value: u16) -> Result<Self, Self::Error> { Self::new(value) }
 }
+impl From<IdentifyParameterIndex> for u16 {
+    fn from(index: IdentifyParameterIndex) -> Self { index.value() }
+}
+/// Zero-padded to three digits, as in `aIM_001!`. The `_` separator belongs to
+/// the command and is not written.
+impl fmt::Display for IdentifyParameterIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{:03}", self.0) }
+}
 
 
 // --- Main Command Enum ---
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Command {
     AcknowledgeActive { address: Sdi12Addr },
     SendIdentification { address: Sdi12Addr },
@@ -153,92 +232,185 @@ impl Command {
     /// Maximum length of the *formatted* standard command string (e.g., "aICC9_999!").
     const MAX_FORMATTED_LEN: usize = 10;
 
+    /// Upper bound on any single response line, including `<CR><LF>`. Used when the
+    /// originating command isn't known (or is extended/binary).
+    pub const MAX_RESPONSE_LEN: usize = 96;
+
+    /// Builds an `aAb!` command, rejecting `?` as the new address.
+    ///
+    /// A sensor moved to `?` could no longer be addressed individually, so
+    /// `Sdi12Error::InvalidAddress('?')` is returned instead.
+    pub fn change_address(address: Sdi12Addr, new_address: Sdi12Addr) -> Result<Self, Sdi12Error<()>> {
+        if new_address.is_query() {
+            return Err(Sdi12Error::InvalidAddress(new_address.as_char()));
+        }
+        Ok(Command::ChangeAddress { address, new_address })
+    }
+
+    /// Builds the measurement command for the given flags: `aM!`, `aMC!`, `aC!`
+    /// or `aCC!`, with `index` appended as usual.
+    pub fn start_measurement(address: Sdi12Addr, index: MeasurementIndex, crc: bool, concurrent: bool) -> Self {
+        let command = if concurrent {
+            Command::StartConcurrentMeasurement { address, index }
+        } else {
+            Command::StartMeasurement { address, index }
+        };
+        if crc { command.with_crc() } else { command }
+    }
+
+    /// Builds `aDn!`, or `aDBn!` if `binary` is set.
+    pub fn send_data(address: Sdi12Addr, index: DataIndex, binary: bool) -> Self {
+        if binary {
+            Command::SendBinaryData { address, index }
+        } else {
+            Command::SendData { address, index }
+        }
+    }
+
+    /// Builds the parameter metadata query for `base`: `aIM_001!`, `aIMC2_003!`,
+    /// `aIR0_001!`, ... A flat alternative to spelling out the matching
+    /// `IdentifyMeasurementParameterCommand` variant.
+    pub fn identify_parameter(address: Sdi12Addr, base: MeasurementBase, param_index: IdentifyParameterIndex) -> Self {
+        use IdentifyMeasurementParameterCommand as P;
+        Command::IdentifyMeasurementParameter(match base {
+            MeasurementBase::Measurement(m_index) => P::Measurement { address, m_index, param_index },
+            MeasurementBase::MeasurementCRC(m_index) => P::MeasurementCRC { address, m_index, param_index },
+            MeasurementBase::Verification => P::Verification { address, param_index },
+            MeasurementBase::Concurrent(c_index) => P::ConcurrentMeasurement { address, c_index, param_index },
+            MeasurementBase::ConcurrentCRC(c_index) => P::ConcurrentMeasurementCRC { address, c_index, param_index },
+            MeasurementBase::ReadContinuous(r_index) => P::ReadContinuous { address, r_index, param_index },
+            MeasurementBase::ReadContinuousCRC(r_index) => P::ReadContinuousCRC { address, r_index, param_index },
+            MeasurementBase::HighVolumeASCII => P::HighVolumeASCII { address, param_index },
+            MeasurementBase::HighVolumeBinary => P::HighVolumeBinary { address, param_index },
+        })
+    }
+
     /// Formats the command into the standard byte sequence.
     pub fn format_into(&self) -> Result<ArrayString<{Self::MAX_FORMATTED_LEN}>, CommandFormatError> {
-        // ... (rest of format_into implementation as before) ...
         let mut buffer = ArrayString::<{Self::MAX_FORMATTED_LEN}>::new();
+        write!(buffer, "{}", self.address())?;
+        self.body_into(&mut buffer)?;
+        Ok(buffer)
+    }
 
+    /// Appends the command without its address (`M1!`, `DB5!`, `IM_001!`) to `buffer`.
+    ///
+    /// The body doesn't depend on the address, so a scan can format it once
+    /// and prepend each sensor's address. `format_into` is the address
+    /// followed by this.
+    pub fn body_into<const N: usize>(&self, buffer: &mut ArrayString<N>) -> Result<(), CommandFormatError> {
         match self {
-            Command::AcknowledgeActive { address } => write!(buffer, "{}!", address)?,
-            Command::SendIdentification { address } => write!(buffer, "{}I!", address)?,
-            Command::AddressQuery => write!(buffer, "?!")?,
-            Command::ChangeAddress { address, new_address } => write!(buffer, "{}A{}!", address, new_address)?,
-
-            Command::StartMeasurement { address, index } => {
-                write!(buffer, "{}M", address)?;
-                if let MeasurementIndex::Indexed(i) = index { write!(buffer, "{}", i)?; }
-                write!(buffer, "!")?;
-            }
-            Command::StartMeasurementCRC { address, index } => {
-                write!(buffer, "{}MC", address)?;
-                if let MeasurementIndex::Indexed(i) = index { write!(buffer, "{}", i)?; }
-                write!(buffer, "!")?;
-            }
-            Command::StartConcurrentMeasurement { address, index } => {
-                 write!(buffer, "{}C", address)?;
-                if let MeasurementIndex::Indexed(i) = index { write!(buffer, "{}", i)?; }
-                write!(buffer, "!")?;
-            }
-            Command::StartConcurrentMeasurementCRC { address, index } => {
-                 write!(buffer, "{}CC", address)?;
-                if let MeasurementIndex::Indexed(i) = index { write!(buffer, "{}", i)?; }
-                write!(buffer, "!")?;
+            Command::AcknowledgeActive { .. } | Command::AddressQuery => write!(buffer, "!")?,
+            Command::SendIdentification { .. } => write!(buffer, "I!")?,
+            Command::ChangeAddress { new_address, .. } if new_address.is_query() => {
+                return Err(CommandFormatError::InvalidNewAddress);
             }
-            Command::SendData { address, index } => write!(buffer, "{}D{}!", address, index.value())?,
-            Command::SendBinaryData { address, index } => write!(buffer, "{}DB{}!", address, index.value())?,
-            Command::ReadContinuous { address, index } => write!(buffer, "{}R{}!", address, index.value())?,
-            Command::ReadContinuousCRC { address, index } => write!(buffer, "{}RC{}!", address, index.value())?,
-            Command::StartVerification { address } => write!(buffer, "{}V!", address)?,
-            Command::StartHighVolumeASCII { address } => write!(buffer, "{}HA!", address)?,
-            Command::StartHighVolumeBinary { address } => write!(buffer, "{}HB!", address)?,
+            Command::ChangeAddress { new_address, .. } => write!(buffer, "A{}!", new_address)?,
+
+            Command::StartMeasurement { index, .. } => write!(buffer, "M{}!", index)?,
+            Command::StartMeasurementCRC { index, .. } => write!(buffer, "MC{}!", index)?,
+            Command::StartConcurrentMeasurement { index, .. } => write!(buffer, "C{}!", index)?,
+            Command::StartConcurrentMeasurementCRC { index, .. } => write!(buffer, "CC{}!", index)?,
+            Command::SendData { index, .. } => write!(buffer, "D{}!", index.value())?,
+            Command::SendBinaryData { index, .. } => write!(buffer, "DB{}!", index.value())?,
+            Command::ReadContinuous { index, .. } => write!(buffer, "R{}!", index.value())?,
+            Command::ReadContinuousCRC { index, .. } => write!(buffer, "RC{}!", index.value())?,
+            Command::StartVerification { .. } => write!(buffer, "V!")?,
+            Command::StartHighVolumeASCII { .. } => write!(buffer, "HA!")?,
+            Command::StartHighVolumeBinary { .. } => write!(buffer, "HB!")?,
 
             Command::IdentifyMeasurement(cmd) => {
                 match cmd {
-                    IdentifyMeasurementCommand::Measurement { address, index } => { write!(buffer, "{}IM", address)?; if let MeasurementIndex::Indexed(i) = index { write!(buffer, "{}", i)?; } }
-                    IdentifyMeasurementCommand::MeasurementCRC { address, index } => { write!(buffer, "{}IMC", address)?; if let MeasurementIndex::Indexed(i) = index { write!(buffer, "{}", i)?; } }
-                    IdentifyMeasurementCommand::Verification { address } => write!(buffer, "{}IV", address)?,
-                    IdentifyMeasurementCommand::ConcurrentMeasurement { address, index } => { write!(buffer, "{}IC", address)?; if let MeasurementIndex::Indexed(i) = index { write!(buffer, "{}", i)?; } }
-                    IdentifyMeasurementCommand::ConcurrentMeasurementCRC { address, index } => { write!(buffer, "{}ICC", address)?; if let MeasurementIndex::Indexed(i) = index { write!(buffer, "{}", i)?; } }
-                    IdentifyMeasurementCommand::HighVolumeASCII { address } => write!(buffer, "{}IHA", address)?,
-                    IdentifyMeasurementCommand::HighVolumeBinary { address } => write!(buffer, "{}IHB", address)?,
+                    IdentifyMeasurementCommand::Measurement { index, .. } => write!(buffer, "IM{}", index)?,
+                    IdentifyMeasurementCommand::MeasurementCRC { index, .. } => write!(buffer, "IMC{}", index)?,
+                    IdentifyMeasurementCommand::Verification { .. } => write!(buffer, "IV")?,
+                    IdentifyMeasurementCommand::ConcurrentMeasurement { index, .. } => write!(buffer, "IC{}", index)?,
+                    IdentifyMeasurementCommand::ConcurrentMeasurementCRC { index, .. } => write!(buffer, "ICC{}", index)?,
+                    IdentifyMeasurementCommand::HighVolumeASCII { .. } => write!(buffer, "IHA")?,
+                    IdentifyMeasurementCommand::HighVolumeBinary { .. } => write!(buffer, "IHB")?,
                 }
                 write!(buffer, "!")?;
             }
             Command::IdentifyMeasurementParameter(cmd) => {
                 match cmd {
-                     IdentifyMeasurementParameterCommand::Measurement { address, m_index, param_index } => { write!(buffer, "{}IM", address)?; if let MeasurementIndex::Indexed(i) = m_index { write!(buffer, "{}", i)?; } write!(buffer, "_{:03}", param_index.value())?; }
-                     IdentifyMeasurementParameterCommand::MeasurementCRC { address, m_index, param_index } => { write!(buffer, "{}IMC", address)?; if let MeasurementIndex::Indexed(i) = m_index { write!(buffer, "{}", i)?; } write!(buffer, "_{:03}", param_index.value())?; }
-                     IdentifyMeasurementParameterCommand::Verification { address, param_index } => { write!(buffer, "{}IV_{:03}", address, param_index.value())?; }
-                     IdentifyMeasurementParameterCommand::ConcurrentMeasurement { address, c_index, param_index } => { write!(buffer, "{}IC", address)?; if let MeasurementIndex::Indexed(i) = c_index { write!(buffer, "{}", i)?; } write!(buffer, "_{:03}", param_index.value())?; }
-                     IdentifyMeasurementParameterCommand::ConcurrentMeasurementCRC { address, c_index, param_index } => { write!(buffer, "{}ICC", address)?; if let MeasurementIndex::Indexed(i) = c_index { write!(buffer, "{}", i)?; } write!(buffer, "_{:03}", param_index.value())?; }
-                     IdentifyMeasurementParameterCommand::ReadContinuous { address, r_index, param_index } => { write!(buffer, "{}IR{}_{:03}", address, r_index.value(), param_index.value())?; }
-                     IdentifyMeasurementParameterCommand::ReadContinuousCRC { address, r_index, param_index } => { write!(buffer, "{}IRC{}_{:03}", address, r_index.value(), param_index.value())?; }
-                     IdentifyMeasurementParameterCommand::HighVolumeASCII { address, param_index } => { write!(buffer, "{}IHA_{:03}", address, param_index.value())?; }
-                     IdentifyMeasurementParameterCommand::HighVolumeBinary { address, param_index } => { write!(buffer, "{}IHB_{:03}", address, param_index.value())?; }
+                    IdentifyMeasurementParameterCommand::Measurement { m_index, param_index, .. } => write!(buffer, "IM{}_{:03}", m_index, param_index.value())?,
+                    IdentifyMeasurementParameterCommand::MeasurementCRC { m_index, param_index, .. } => write!(buffer, "IMC{}_{:03}", m_index, param_index.value())?,
+                    IdentifyMeasurementParameterCommand::Verification { param_index, .. } => write!(buffer, "IV_{:03}", param_index.value())?,
+                    IdentifyMeasurementParameterCommand::ConcurrentMeasurement { c_index, param_index, .. } => write!(buffer, "IC{}_{:03}", c_index, param_index.value())?,
+                    IdentifyMeasurementParameterCommand::ConcurrentMeasurementCRC { c_index, param_index, .. } => write!(buffer, "ICC{}_{:03}", c_index, param_index.value())?,
+                    IdentifyMeasurementParameterCommand::ReadContinuous { r_index, param_index, .. } => write!(buffer, "IR{}_{:03}", r_index.value(), param_index.value())?,
+                    IdentifyMeasurementParameterCommand::ReadContinuousCRC { r_index, param_index, .. } => write!(buffer, "IRC{}_{:03}", r_index.value(), param_index.value())?,
+                    IdentifyMeasurementParameterCommand::HighVolumeASCII { param_index, .. } => write!(buffer, "IHA_{:03}", param_index.value())?,
+                    IdentifyMeasurementParameterCommand::HighVolumeBinary { param_index, .. } => write!(buffer, "IHB_{:03}", param_index.value())?,
                 }
-                 write!(buffer, "!")?;
+                write!(buffer, "!")?;
             }
 
             #[cfg(feature = "alloc")]
-            Command::ExtendedCommand { address, command_body } => {
-                // Write the address first
-                write!(buffer, "{}", address)?;
-
+            Command::ExtendedCommand { command_body, .. } => {
                 // Check if there's enough space for the command body AND the trailing '!'
                 if buffer.remaining_capacity() < command_body.len() + 1 {
                     return Err(CommandFormatError::BufferOverflow);
                 }
-
-                // Write the command body
                 buffer.try_push_str(command_body)
                       .map_err(|_| CommandFormatError::BufferOverflow)?;
-
-                // Write the terminator
                 buffer.try_push('!')
                       .map_err(|_| CommandFormatError::BufferOverflow)?;
-             }
+            }
         }
-        Ok(buffer)
+        Ok(())
+    }
+
+    /// Number of bytes the command occupies on the wire, address and `!`
+    /// included, without formatting it. Equal to `format_into().len()`
+    /// wherever that succeeds; an `ExtendedCommand` too long for
+    /// `format_into` still gets its full length (`1 + body.len() + 1`).
+    pub fn formatted_len(&self) -> usize {
+        fn digits(n: u16) -> usize {
+            match n {
+                0..=9 => 1,
+                10..=99 => 2,
+                _ => 3,
+            }
+        }
+        let m = |index: &MeasurementIndex| usize::from(index.as_option().is_some());
+        const PARAM: usize = 4; // _nnn
+
+        let body = match self {
+            Command::AcknowledgeActive { .. } | Command::AddressQuery => 0,
+            Command::SendIdentification { .. } | Command::StartVerification { .. } => 1,
+            Command::ChangeAddress { .. } => 2,
+            Command::StartMeasurement { index, .. } | Command::StartConcurrentMeasurement { index, .. } => 1 + m(index),
+            Command::StartMeasurementCRC { index, .. } | Command::StartConcurrentMeasurementCRC { index, .. } => 2 + m(index),
+            Command::SendData { index, .. } => 1 + digits(index.value()),
+            Command::SendBinaryData { index, .. } => 2 + digits(index.value()),
+            Command::ReadContinuous { .. } => 2,
+            Command::ReadContinuousCRC { .. } => 3,
+            Command::StartHighVolumeASCII { .. } | Command::StartHighVolumeBinary { .. } => 2,
+            Command::IdentifyMeasurement(cmd) => match cmd {
+                IdentifyMeasurementCommand::Measurement { index, .. }
+                | IdentifyMeasurementCommand::ConcurrentMeasurement { index, .. } => 2 + m(index),
+                IdentifyMeasurementCommand::MeasurementCRC { index, .. }
+                | IdentifyMeasurementCommand::ConcurrentMeasurementCRC { index, .. } => 3 + m(index),
+                IdentifyMeasurementCommand::Verification { .. } => 2,
+                IdentifyMeasurementCommand::HighVolumeASCII { .. }
+                | IdentifyMeasurementCommand::HighVolumeBinary { .. } => 3,
+            },
+            Command::IdentifyMeasurementParameter(cmd) => PARAM + match cmd {
+                IdentifyMeasurementParameterCommand::Measurement { m_index, .. } => 2 + m(m_index),
+                IdentifyMeasurementParameterCommand::MeasurementCRC { m_index, .. } => 3 + m(m_index),
+                IdentifyMeasurementParameterCommand::ConcurrentMeasurement { c_index, .. } => 2 + m(c_index),
+                IdentifyMeasurementParameterCommand::ConcurrentMeasurementCRC { c_index, .. } => 3 + m(c_index),
+                IdentifyMeasurementParameterCommand::Verification { .. } => 2,
+                IdentifyMeasurementParameterCommand::ReadContinuous { .. }
+                | IdentifyMeasurementParameterCommand::HighVolumeASCII { .. }
+                | IdentifyMeasurementParameterCommand::HighVolumeBinary { .. } => 3,
+                IdentifyMeasurementParameterCommand::ReadContinuousCRC { .. } => 4,
+            },
+            #[cfg(feature = "alloc")]
+            Command::ExtendedCommand { command_body, .. } => command_body.len(),
+        };
+        1 + body + 1
     }
 
     /// Returns the address the command is directed to.
@@ -266,11 +438,184 @@ impl Command {
             Command::ExtendedCommand { address, .. } => *address,
         }
     }
+
+    /// Worst-case bus time for one attempt at this command, for scheduling.
+    ///
+    /// Includes a break with the following marking (the recorder may skip it),
+    /// sending the command, the sensor's response start time and sending the
+    /// longest allowed response (`max_response_len`) at 1200 baud. Not included: retries, and
+    /// the `ttt` seconds a measurement then takes, which only the sensor knows
+    /// (see the timing response).
+    pub fn estimated_duration(&self) -> Duration {
+        let command_len = self.formatted_len();
+        timing::BREAK_DURATION_MIN
+            + timing::POST_BREAK_MARKING_MIN
+            + timing::RESPONSE_START_TIME_MAX
+            + timing::transmission_time(command_len + self.max_response_len())
+    }
+
+    /// The `*CRC` form of a measurement or continuous command (`aM!` becomes
+    /// `aMC!`, `aC!` becomes `aCC!`, `aR0!` becomes `aRC0!`). Any other command,
+    /// including one that already requests a CRC, is returned unchanged.
+    pub fn with_crc(self) -> Command {
+        match self {
+            Command::StartMeasurement { address, index } => Command::StartMeasurementCRC { address, index },
+            Command::StartConcurrentMeasurement { address, index } => {
+                Command::StartConcurrentMeasurementCRC { address, index }
+            }
+            Command::ReadContinuous { address, index } => Command::ReadContinuousCRC { address, index },
+            other => other,
+        }
+    }
+
+    /// The inverse of `with_crc`: `aMC!` becomes `aM!`, and so on.
+    pub fn without_crc(self) -> Command {
+        match self {
+            Command::StartMeasurementCRC { address, index } => Command::StartMeasurement { address, index },
+            Command::StartConcurrentMeasurementCRC { address, index } => {
+                Command::StartConcurrentMeasurement { address, index }
+            }
+            Command::ReadContinuousCRC { address, index } => Command::ReadContinuous { address, index },
+            other => other,
+        }
+    }
+
+    /// Returns true if the command asks the sensor to append a CRC (`MC`, `CC`, `RC`,
+    /// and the matching identify variants such as `IMC` or `IRC0_001`).
+    pub fn is_crc_variant(&self) -> bool {
+        match self {
+            Command::StartMeasurementCRC { .. }
+            | Command::StartConcurrentMeasurementCRC { .. }
+            | Command::ReadContinuousCRC { .. } => true,
+            Command::IdentifyMeasurement(cmd) => cmd.is_crc_variant(),
+            Command::IdentifyMeasurementParameter(cmd) => cmd.is_crc_variant(),
+            _ => false,
+        }
+    }
+
+    /// Returns true if the sensor answers with `atttn[nn]` (M, C, V, HA, HB and
+    /// all Identify Measurement commands).
+    pub fn expects_timing_response(&self) -> bool {
+        matches!(
+            self,
+            Command::StartMeasurement { .. }
+                | Command::StartMeasurementCRC { .. }
+                | Command::StartConcurrentMeasurement { .. }
+                | Command::StartConcurrentMeasurementCRC { .. }
+                | Command::StartVerification { .. }
+                | Command::StartHighVolumeASCII { .. }
+                | Command::StartHighVolumeBinary { .. }
+                | Command::IdentifyMeasurement(_)
+        )
+    }
+
+    /// Returns true if the sensor may signal early completion of the measurement
+    /// this command starts with a service request (`a<CR><LF>`): `M`, `MC`, `V`,
+    /// `HA` and `HB`.
+    ///
+    /// Concurrent measurements (`C`/`CC`) never send one, so their full `ttt` is
+    /// always waited out; continuous (`R`) and all other commands start no
+    /// measurement to wait for.
+    pub fn allows_service_request(&self) -> bool {
+        matches!(
+            self,
+            Command::StartMeasurement { .. }
+                | Command::StartMeasurementCRC { .. }
+                | Command::StartVerification { .. }
+                | Command::StartHighVolumeASCII { .. }
+                | Command::StartHighVolumeBinary { .. }
+        )
+    }
+
+    /// Number of `n` digits in this command's `atttn` timing response: 1 after
+    /// `M`/`MC`/`V`, 2 after `C`/`CC`, 3 after `HA`/`HB` (and the same for the
+    /// matching Identify Measurement commands). `None` for other commands.
+    pub fn timing_count_digits(&self) -> Option<usize> {
+        match self {
+            Command::StartMeasurement { .. }
+            | Command::StartMeasurementCRC { .. }
+            | Command::StartVerification { .. } => Some(1),
+            Command::StartConcurrentMeasurement { .. } | Command::StartConcurrentMeasurementCRC { .. } => Some(2),
+            Command::StartHighVolumeASCII { .. } | Command::StartHighVolumeBinary { .. } => Some(3),
+            Command::IdentifyMeasurement(cmd) => Some(match cmd {
+                IdentifyMeasurementCommand::Measurement { .. }
+                | IdentifyMeasurementCommand::MeasurementCRC { .. }
+                | IdentifyMeasurementCommand::Verification { .. } => 1,
+                IdentifyMeasurementCommand::ConcurrentMeasurement { .. }
+                | IdentifyMeasurementCommand::ConcurrentMeasurementCRC { .. } => 2,
+                IdentifyMeasurementCommand::HighVolumeASCII { .. }
+                | IdentifyMeasurementCommand::HighVolumeBinary { .. } => 3,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Longest response line the spec allows for this command, including the
+    /// address, an optional CRC and `<CR><LF>`.
+    ///
+    /// `<values>` may be up to 35 characters after `M`/`V` and 75 after `C`, `R` or
+    /// `HA`. A `D` command doesn't say which measurement it follows, so data
+    /// responses get the 75 character allowance. Extended and binary commands
+    /// fall back to `MAX_RESPONSE_LEN`.
+    pub fn max_response_len(&self) -> usize {
+        const ADDR: usize = 1;
+        const CRC: usize = 3;
+        const CRLF: usize = 2;
+        const VALUES_MAX: usize = 75;
+        const IDENTIFICATION_MAX: usize = 2 + 8 + 6 + 3 + 13; // ll cccccccc mmmmmm vvv xxx...
+
+        match self {
+            Command::AcknowledgeActive { .. } | Command::AddressQuery | Command::ChangeAddress { .. } => ADDR + CRLF,
+            Command::SendIdentification { .. } => ADDR + IDENTIFICATION_MAX + CRLF,
+            // atttn / atttnn / atttnnn
+            _ if self.expects_timing_response() => ADDR + 3 + 3 + CRLF,
+            Command::SendData { .. }
+            | Command::ReadContinuous { .. }
+            | Command::ReadContinuousCRC { .. }
+            | Command::IdentifyMeasurementParameter(_) => ADDR + VALUES_MAX + CRC + CRLF,
+            _ => Self::MAX_RESPONSE_LEN,
+        }
+    }
+
+    /// Returns true if the sensor answers with ASCII `<values>` (D and R commands).
+    /// `DB` is excluded since its reply is a binary packet.
+    pub fn expects_data_response(&self) -> bool {
+        matches!(
+            self,
+            Command::SendData { .. } | Command::ReadContinuous { .. } | Command::ReadContinuousCRC { .. }
+        )
+    }
 }
 
 // --- Metadata Sub-Enums ---
 
+/// The measurement a metadata query (`aIM!`, `aIM_001!`, ...) asks about, with
+/// its index where the command has one. Used by `Command::identify_parameter`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MeasurementBase {
+    /// `aM!` / `aMn!`
+    Measurement(MeasurementIndex),
+    /// `aMC!` / `aMCn!`
+    MeasurementCRC(MeasurementIndex),
+    /// `aV!`
+    Verification,
+    /// `aC!` / `aCn!`
+    Concurrent(MeasurementIndex),
+    /// `aCC!` / `aCCn!`
+    ConcurrentCRC(MeasurementIndex),
+    /// `aRn!`
+    ReadContinuous(ContinuousIndex),
+    /// `aRCn!`
+    ReadContinuousCRC(ContinuousIndex),
+    /// `aHA!`
+    HighVolumeASCII,
+    /// `aHB!`
+    HighVolumeBinary,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum IdentifyMeasurementCommand {
      Measurement { address: Sdi12Addr, index: MeasurementIndex },
      MeasurementCRC { address: Sdi12Addr, index: MeasurementIndex },
@@ -294,10 +639,16 @@ impl IdentifyMeasurementCommand {
              Self::HighVolumeBinary { address } => *address,
          }
      }
+
+     /// Returns true for `IMC` and `ICC`.
+     pub fn is_crc_variant(&self) -> bool {
+         matches!(self, Self::MeasurementCRC { .. } | Self::ConcurrentMeasurementCRC { .. })
+     }
 }
 
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum IdentifyMeasurementParameterCommand {
      Measurement { address: Sdi12Addr, m_index: MeasurementIndex, param_index: IdentifyParameterIndex },
      MeasurementCRC { address: Sdi12Addr, m_index: MeasurementIndex, param_index: IdentifyParameterIndex },
@@ -325,6 +676,14 @@ impl IdentifyMeasurementParameterCommand {
              Self::HighVolumeBinary { address, .. } => *address,
          }
      }
+
+     /// Returns true for `IMC`, `ICC` and `IRC` parameter queries.
+     pub fn is_crc_variant(&self) -> bool {
+         matches!(
+             self,
+             Self::MeasurementCRC { .. } | Self::ConcurrentMeasurementCRC { .. } | Self::ReadContinuousCRC { .. }
+         )
+     }
 }
 
 
@@ -349,6 +708,29 @@ mod tests {
         assert!(matches!(MeasurementIndex::new(Some(10)), Err(CommandIndexError::MeasurementOutOfRange)));
     }
 
+    #[test]
+    fn test_measurement_index_eq_option() {
+        assert!(MeasurementIndex::Base == None);
+        assert!(MeasurementIndex::Indexed(3) == Some(3));
+        assert!(MeasurementIndex::Indexed(3) != Some(4));
+        assert!(MeasurementIndex::Base != Some(0));
+        assert!(MeasurementIndex::Indexed(1) != None);
+        assert!(Some(9) == MeasurementIndex::Indexed(9));
+    }
+
+    #[test]
+    fn test_index_from_ascii_digit() {
+        assert_eq!(MeasurementIndex::from_ascii_digit(b'1'), Ok(MeasurementIndex::Indexed(1)));
+        assert_eq!(MeasurementIndex::from_ascii_digit(b'9'), Ok(MeasurementIndex::Indexed(9)));
+        assert_eq!(MeasurementIndex::from_ascii_digit(b'0'), Err(CommandIndexError::MeasurementOutOfRange));
+        assert_eq!(MeasurementIndex::from_ascii_digit(b'A'), Err(CommandIndexError::MeasurementOutOfRange));
+
+        assert_eq!(ContinuousIndex::from_ascii_digit(b'0'), Ok(ContinuousIndex(0)));
+        assert_eq!(ContinuousIndex::from_ascii_digit(b'9'), Ok(ContinuousIndex(9)));
+        assert_eq!(ContinuousIndex::from_ascii_digit(b'/'), Err(CommandIndexError::ContinuousOutOfRange));
+        assert_eq!(ContinuousIndex::from_ascii_digit(b':'), Err(CommandIndexError::ContinuousOutOfRange));
+    }
+
     #[test]
     fn test_continuous_index_validation() {
         assert!(ContinuousIndex::new(0).is_ok());
@@ -377,6 +759,21 @@ mod tests {
         assert!(IdentifyParameterIndex::try_from(1000).is_err());
     }
 
+    #[test]
+    fn test_index_display_matches_wire() {
+        fn shown(index: impl fmt::Display) -> ArrayString<8> {
+            let mut out = ArrayString::new();
+            write!(out, "{}", index).unwrap();
+            out
+        }
+        assert_eq!(shown(MeasurementIndex::Base).as_str(), "");
+        assert_eq!(shown(MeasurementIndex::Indexed(3)).as_str(), "3");
+        assert_eq!(shown(ContinuousIndex::new(5).unwrap()).as_str(), "5");
+        assert_eq!(shown(DataIndex::new(10).unwrap()).as_str(), "10");
+        assert_eq!(shown(IdentifyParameterIndex::new(1).unwrap()).as_str(), "001");
+        assert_eq!(shown(IdentifyParameterIndex::new(999).unwrap()).as_str(), "999");
+    }
+
     #[test]
     fn test_command_construction() {
         let cmd = Command::StartConcurrentMeasurementCRC {
@@ -386,6 +783,16 @@ mod tests {
         assert!(matches!(cmd, Command::StartConcurrentMeasurementCRC { .. }));
     }
 
+    #[test]
+    fn test_change_address_rejects_query_target() {
+        assert_eq!(Command::change_address(addr('2'), Sdi12Addr::QUERY_ADDRESS), Err(Sdi12Error::InvalidAddress('?')));
+        let cmd = Command::change_address(addr('2'), addr('A')).unwrap();
+        assert_eq!(cmd.format_into().unwrap().as_str(), "2AA!");
+        // Built directly, bypassing `change_address`: still never put on the wire
+        let to_query = Command::ChangeAddress { address: addr('2'), new_address: addr('?') };
+        assert_eq!(to_query.format_into(), Err(CommandFormatError::InvalidNewAddress));
+    }
+
     #[test]
     fn test_command_formatting_standard() {
         // Basic
@@ -437,6 +844,28 @@ mod tests {
         assert_eq!(Command::IdentifyMeasurementParameter(IdentifyMeasurementParameterCommand::HighVolumeBinary { address: addr('W'), param_index: IdentifyParameterIndex::new(10).unwrap() }).format_into().unwrap().as_str(), "WIHB_010!");
     }
 
+    #[test]
+    fn test_body_into() {
+        let body = |cmd: Command| {
+            let mut buffer = ArrayString::<16>::new();
+            cmd.body_into(&mut buffer).unwrap();
+            buffer
+        };
+        assert_eq!(body(Command::StartMeasurement { address: addr('4'), index: MeasurementIndex::Indexed(1) }).as_str(), "M1!");
+        assert_eq!(body(Command::SendBinaryData { address: addr('0'), index: DataIndex::new(5).unwrap() }).as_str(), "DB5!");
+        let im = IdentifyMeasurementParameterCommand::Measurement { address: addr('0'), m_index: MeasurementIndex::Base, param_index: IdentifyParameterIndex::new(1).unwrap() };
+        assert_eq!(body(Command::IdentifyMeasurementParameter(im)).as_str(), "IM_001!");
+        assert_eq!(body(Command::AddressQuery).as_str(), "!");
+
+        // Appends to what's already there, and fails cleanly when it doesn't fit
+        let mut line = ArrayString::<4>::new();
+        line.push('b');
+        Command::StartMeasurementCRC { address: addr('0'), index: MeasurementIndex::Base }.body_into(&mut line).unwrap();
+        assert_eq!(line.as_str(), "bMC!");
+        let mut small = ArrayString::<2>::new();
+        assert!(Command::StartMeasurementCRC { address: addr('0'), index: MeasurementIndex::Base }.body_into(&mut small).is_err());
+    }
+
     #[test]
     #[cfg(feature = "alloc")]
     fn test_format_extended_command() {
@@ -471,4 +900,227 @@ mod tests {
         // Test address query returns the query address char
         assert_eq!(Command::AddressQuery.address(), Sdi12Addr::QUERY_ADDRESS);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_command_serde_roundtrip() {
+        let commands = [
+            Command::AddressQuery,
+            Command::ChangeAddress { address: addr('0'), new_address: addr('b') },
+            Command::StartMeasurementCRC { address: addr('1'), index: MeasurementIndex::Indexed(3) },
+            Command::SendData { address: addr('2'), index: DataIndex::new(999).unwrap() },
+            Command::ReadContinuous { address: addr('3'), index: ContinuousIndex::new(0).unwrap() },
+            Command::IdentifyMeasurementParameter(IdentifyMeasurementParameterCommand::Measurement {
+                address: addr('4'), m_index: MeasurementIndex::Base, param_index: IdentifyParameterIndex::new(7).unwrap(),
+            }),
+        ];
+        for cmd in commands {
+            let json = serde_json::to_string(&cmd).unwrap();
+            assert_eq!(serde_json::from_str::<Command>(&json).unwrap(), cmd);
+        }
+        assert_eq!(
+            serde_json::to_string(&Command::AcknowledgeActive { address: addr('0') }).unwrap(),
+            r#"{"AcknowledgeActive":{"address":"0"}}"#
+        );
+
+        // Deserialization goes through the same validation as the constructors
+        assert!(serde_json::from_str::<DataIndex>("1000").is_err());
+        assert!(serde_json::from_str::<ContinuousIndex>("10").is_err());
+        assert!(serde_json::from_str::<IdentifyParameterIndex>("0").is_err());
+        assert!(serde_json::from_str::<MeasurementIndex>("0").is_err());
+        assert_eq!(serde_json::from_str::<MeasurementIndex>("null").unwrap(), MeasurementIndex::Base);
+    }
+
+    #[test]
+    fn test_command_classifiers() {
+        let a = addr('0');
+        let p1 = IdentifyParameterIndex::new(1).unwrap();
+        let m = Command::StartMeasurement { address: a, index: MeasurementIndex::Base };
+        let mc = Command::StartMeasurementCRC { address: a, index: MeasurementIndex::Base };
+        let d0 = Command::SendData { address: a, index: DataIndex::new(0).unwrap() };
+        let rc = Command::ReadContinuousCRC { address: a, index: ContinuousIndex::new(0).unwrap() };
+        let db = Command::SendBinaryData { address: a, index: DataIndex::new(0).unwrap() };
+        let imc = Command::IdentifyMeasurement(IdentifyMeasurementCommand::MeasurementCRC { address: a, index: MeasurementIndex::Base });
+        let ihb = Command::IdentifyMeasurement(IdentifyMeasurementCommand::HighVolumeBinary { address: a });
+        let irc = Command::IdentifyMeasurementParameter(IdentifyMeasurementParameterCommand::ReadContinuousCRC {
+            address: a, r_index: ContinuousIndex::new(0).unwrap(), param_index: p1,
+        });
+        let iv = Command::IdentifyMeasurementParameter(IdentifyMeasurementParameterCommand::Verification { address: a, param_index: p1 });
+
+        assert!(!m.is_crc_variant() && mc.is_crc_variant() && rc.is_crc_variant());
+        assert!(imc.is_crc_variant() && irc.is_crc_variant());
+        assert!(!ihb.is_crc_variant() && !iv.is_crc_variant() && !d0.is_crc_variant());
+
+        assert!(m.expects_timing_response() && mc.expects_timing_response());
+        assert!(Command::StartHighVolumeASCII { address: a }.expects_timing_response());
+        assert!(imc.expects_timing_response() && ihb.expects_timing_response());
+        assert!(!irc.expects_timing_response() && !d0.expects_timing_response());
+
+        assert!(d0.expects_data_response() && rc.expects_data_response());
+        assert!(!db.expects_data_response() && !m.expects_data_response() && !iv.expects_data_response());
+        assert!(!Command::AcknowledgeActive { address: a }.expects_data_response());
+    }
+
+    #[test]
+    fn test_with_and_without_crc() {
+        let a = addr('0');
+        let r0 = Command::ReadContinuous { address: a, index: ContinuousIndex::new(0).unwrap() };
+        let rc0 = Command::ReadContinuousCRC { address: a, index: ContinuousIndex::new(0).unwrap() };
+        let c1 = Command::StartConcurrentMeasurement { address: a, index: MeasurementIndex::Indexed(1) };
+        let cc1 = Command::StartConcurrentMeasurementCRC { address: a, index: MeasurementIndex::Indexed(1) };
+        let m = Command::StartMeasurement { address: a, index: MeasurementIndex::Base };
+        let mc = Command::StartMeasurementCRC { address: a, index: MeasurementIndex::Base };
+
+        for (plain, crc) in [(&m, &mc), (&c1, &cc1), (&r0, &rc0)] {
+            assert_eq!(&plain.clone().with_crc(), crc);
+            assert_eq!(&crc.clone().with_crc(), crc);
+            assert_eq!(&crc.clone().without_crc(), plain);
+            assert_eq!(&plain.clone().without_crc(), plain);
+        }
+        // No CRC form: unchanged
+        let v = Command::StartVerification { address: a };
+        assert_eq!(v.clone().with_crc(), v);
+    }
+
+    #[test]
+    fn test_flag_constructors() {
+        let a = addr('3');
+        let i = MeasurementIndex::Indexed(2);
+        let formatted = |cmd: Command| cmd.format_into().unwrap();
+        assert_eq!(formatted(Command::start_measurement(a, MeasurementIndex::Base, false, false)).as_str(), "3M!");
+        assert_eq!(formatted(Command::start_measurement(a, i, true, false)).as_str(), "3MC2!");
+        assert_eq!(formatted(Command::start_measurement(a, i, false, true)).as_str(), "3C2!");
+        assert_eq!(
+            Command::start_measurement(a, i, true, true),
+            Command::StartConcurrentMeasurementCRC { address: a, index: i }
+        );
+        let d = DataIndex::new(12).unwrap();
+        assert_eq!(formatted(Command::send_data(a, d, false)).as_str(), "3D12!");
+        assert_eq!(formatted(Command::send_data(a, d, true)).as_str(), "3DB12!");
+    }
+
+    #[test]
+    fn test_identify_parameter_constructor() {
+        let param = IdentifyParameterIndex::new(1).unwrap();
+        let format = |base| Command::identify_parameter(addr('0'), base, param).format_into().unwrap();
+        assert_eq!(format(MeasurementBase::Measurement(MeasurementIndex::Base)).as_str(), "0IM_001!");
+        assert_eq!(format(MeasurementBase::MeasurementCRC(MeasurementIndex::Indexed(2))).as_str(), "0IMC2_001!");
+        assert_eq!(format(MeasurementBase::Verification).as_str(), "0IV_001!");
+        assert_eq!(format(MeasurementBase::ConcurrentCRC(MeasurementIndex::Base)).as_str(), "0ICC_001!");
+        assert_eq!(format(MeasurementBase::ReadContinuous(ContinuousIndex::new(3).unwrap())).as_str(), "0IR3_001!");
+        assert_eq!(format(MeasurementBase::HighVolumeBinary).as_str(), "0IHB_001!");
+        assert_eq!(
+            Command::identify_parameter(addr('0'), MeasurementBase::ReadContinuousCRC(ContinuousIndex::new(0).unwrap()), param),
+            Command::IdentifyMeasurementParameter(IdentifyMeasurementParameterCommand::ReadContinuousCRC {
+                address: addr('0'),
+                r_index: ContinuousIndex::new(0).unwrap(),
+                param_index: param,
+            })
+        );
+    }
+
+    #[test]
+    fn test_allows_service_request() {
+        let a = addr('0');
+        assert!(Command::StartMeasurement { address: a, index: MeasurementIndex::Base }.allows_service_request());
+        assert!(Command::StartMeasurementCRC { address: a, index: MeasurementIndex::Indexed(2) }.allows_service_request());
+        assert!(Command::StartVerification { address: a }.allows_service_request());
+        assert!(Command::StartHighVolumeBinary { address: a }.allows_service_request());
+        assert!(!Command::StartConcurrentMeasurement { address: a, index: MeasurementIndex::Base }.allows_service_request());
+        assert!(!Command::StartConcurrentMeasurementCRC { address: a, index: MeasurementIndex::Base }.allows_service_request());
+        assert!(!Command::ReadContinuous { address: a, index: ContinuousIndex::new(0).unwrap() }.allows_service_request());
+        assert!(!Command::AcknowledgeActive { address: a }.allows_service_request());
+    }
+
+    #[test]
+    fn test_max_response_len() {
+        let a = addr('0');
+        assert_eq!(Command::AcknowledgeActive { address: a }.max_response_len(), 3);
+        assert_eq!(Command::SendIdentification { address: a }.max_response_len(), 35);
+        assert_eq!(Command::StartHighVolumeASCII { address: a }.max_response_len(), 9); // "0045300\r\n"
+        assert_eq!(Command::SendData { address: a, index: DataIndex::new(0).unwrap() }.max_response_len(), 81);
+        assert_eq!(Command::SendBinaryData { address: a, index: DataIndex::new(0).unwrap() }.max_response_len(), Command::MAX_RESPONSE_LEN);
+    }
+
+    #[test]
+    fn test_formatted_len_matches_format_into() {
+        use IdentifyMeasurementCommand as I;
+        let a = addr('0');
+        let check = |cmd: Command| assert_eq!(cmd.formatted_len(), cmd.format_into().unwrap().len(), "{:?}", cmd);
+
+        check(Command::AcknowledgeActive { address: a });
+        check(Command::SendIdentification { address: a });
+        check(Command::AddressQuery);
+        check(Command::ChangeAddress { address: a, new_address: addr('1') });
+        check(Command::StartVerification { address: a });
+        check(Command::StartHighVolumeASCII { address: a });
+        check(Command::StartHighVolumeBinary { address: a });
+        check(Command::IdentifyMeasurement(I::Verification { address: a }));
+        check(Command::IdentifyMeasurement(I::HighVolumeASCII { address: a }));
+        check(Command::IdentifyMeasurement(I::HighVolumeBinary { address: a }));
+        for index in [MeasurementIndex::Base, MeasurementIndex::Indexed(1), MeasurementIndex::Indexed(9)] {
+            for crc in [false, true] {
+                for concurrent in [false, true] {
+                    check(Command::start_measurement(a, index, crc, concurrent));
+                }
+            }
+            check(Command::IdentifyMeasurement(I::Measurement { address: a, index }));
+            check(Command::IdentifyMeasurement(I::MeasurementCRC { address: a, index }));
+            check(Command::IdentifyMeasurement(I::ConcurrentMeasurement { address: a, index }));
+            check(Command::IdentifyMeasurement(I::ConcurrentMeasurementCRC { address: a, index }));
+        }
+        for i in [0, 9, 10, 99, 100, 999] {
+            let index = DataIndex::new(i).unwrap();
+            check(Command::send_data(a, index, false));
+            check(Command::send_data(a, index, true));
+        }
+        for i in [0, 9] {
+            let index = ContinuousIndex::new(i).unwrap();
+            check(Command::ReadContinuous { address: a, index });
+            check(Command::ReadContinuousCRC { address: a, index });
+        }
+        let bases = [
+            MeasurementBase::Measurement(MeasurementIndex::Base),
+            MeasurementBase::Measurement(MeasurementIndex::Indexed(3)),
+            MeasurementBase::MeasurementCRC(MeasurementIndex::Base),
+            MeasurementBase::MeasurementCRC(MeasurementIndex::Indexed(3)),
+            MeasurementBase::Verification,
+            MeasurementBase::Concurrent(MeasurementIndex::Base),
+            MeasurementBase::Concurrent(MeasurementIndex::Indexed(3)),
+            MeasurementBase::ConcurrentCRC(MeasurementIndex::Base),
+            MeasurementBase::ConcurrentCRC(MeasurementIndex::Indexed(9)),
+            MeasurementBase::ReadContinuous(ContinuousIndex::new(0).unwrap()),
+            MeasurementBase::ReadContinuousCRC(ContinuousIndex::new(9).unwrap()),
+            MeasurementBase::HighVolumeASCII,
+            MeasurementBase::HighVolumeBinary,
+        ];
+        for base in bases {
+            for p in [1, 999] {
+                check(Command::identify_parameter(a, base, IdentifyParameterIndex::new(p).unwrap()));
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_formatted_len_extended() {
+        let short = Command::ExtendedCommand { address: addr('0'), command_body: "XOK".to_string() };
+        assert_eq!(short.formatted_len(), short.format_into().unwrap().len());
+        // Too long for format_into, but the length is still known
+        let long = Command::ExtendedCommand { address: addr('0'), command_body: "XSETPOINT=12.5".to_string() };
+        assert!(long.format_into().is_err());
+        assert_eq!(long.formatted_len(), 16);
+    }
+
+    #[test]
+    fn test_estimated_duration() {
+        let a = addr('0');
+        let overhead = timing::BREAK_DURATION_MIN + timing::POST_BREAK_MARKING_MIN + timing::RESPONSE_START_TIME_MAX;
+        // "0!" out, "0<CR><LF>" back
+        assert_eq!(Command::AcknowledgeActive { address: a }.estimated_duration(), overhead + timing::BYTE_DURATION * 5);
+        // "0M!" out, up to "atttnnn<CR><LF>" back; the ttt wait is not included
+        let m = Command::StartMeasurement { address: a, index: MeasurementIndex::Base };
+        assert_eq!(m.estimated_duration(), overhead + timing::BYTE_DURATION * 12);
+        assert!(Command::SendIdentification { address: a }.estimated_duration() > m.estimated_duration());
+    }
 }
\ No newline at end of file
@@ -1,7 +1,7 @@
 // src/common/crc.rs
 
 use super::error::Sdi12Error;
-use crc::{Crc, Algorithm};
+use crc::{Crc, Algorithm, Digest};
 
 /// Custom CRC algorithm matching SDI-12 specification (CRC-16/ARC).
 /// Polynomial: 0x8005 (normal representation of 0xA001 reversed)
@@ -22,8 +22,9 @@ pub const SDI12_CRC: Algorithm<u16> = Algorithm {
     residue: 0x0000,
 };
 
-// Create a Crc instance for the SDI-12 algorithm for reuse.
-const CRC_COMPUTER: Crc<u16> = Crc::<u16>::new(&SDI12_CRC);
+// Create a Crc instance for the SDI-12 algorithm for reuse. A `static` so that
+// `Sdi12CrcDigest` can borrow it for `'static`.
+static CRC_COMPUTER: Crc<u16> = Crc::<u16>::new(&SDI12_CRC);
 
 /// Calculates the SDI-12 CRC-16 (CRC-16/ARC) for the given data buffer.
 ///
@@ -44,6 +45,56 @@ pub fn calculate_crc16(data: &[u8]) -> u16 {
     CRC_COMPUTER.checksum(data)
 }
 
+/// Incremental SDI-12 CRC-16 calculation.
+///
+/// Produces the same value as [`calculate_crc16`] but accepts the data in any
+/// number of pieces, e.g. the two halves of a wrapped ring buffer or bytes as
+/// they arrive from the UART.
+///
+/// ```
+/// use sdi12::common::crc::{calculate_crc16, Sdi12CrcDigest};
+///
+/// let mut digest = Sdi12CrcDigest::new();
+/// digest.update(b"0+3.");
+/// digest.update(b"14");
+/// assert_eq!(digest.finalize(), calculate_crc16(b"0+3.14"));
+/// ```
+#[derive(Clone)]
+pub struct Sdi12CrcDigest {
+    digest: Digest<'static, u16>,
+}
+
+impl Sdi12CrcDigest {
+    /// Starts a new CRC calculation.
+    pub fn new() -> Self {
+        Sdi12CrcDigest { digest: CRC_COMPUTER.digest() }
+    }
+
+    /// Feeds more bytes into the calculation.
+    #[inline]
+    pub fn update(&mut self, data: &[u8]) {
+        self.digest.update(data);
+    }
+
+    /// Consumes the digest and returns the CRC of all bytes fed so far.
+    #[inline]
+    pub fn finalize(self) -> u16 {
+        self.digest.finalize()
+    }
+}
+
+impl Default for Sdi12CrcDigest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl core::fmt::Debug for Sdi12CrcDigest {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Sdi12CrcDigest").finish_non_exhaustive()
+    }
+}
+
 /// Encodes a 16-bit CRC value into three ASCII characters according to SDI-12 standard.
 ///
 /// Follows section 4.4.12.2 of the SDI-12 specification v1.4.
@@ -74,13 +125,22 @@ pub fn encode_crc_ascii(crc_value: u16) -> [u8; 3] {
 ///
 /// # Panics
 ///
-/// Panics if `crc_chars` does not have a length of exactly 3.
+/// Panics if `crc_chars` does not have a length of exactly 3. Use
+/// `try_decode_crc_ascii` for input that hasn't been length-checked.
 pub fn decode_crc_ascii(crc_chars: &[u8]) -> u16 {
-    assert_eq!(crc_chars.len(), 3, "ASCII CRC must be 3 bytes long");
-    let byte1 = u16::from(crc_chars[0] & 0x3F);
-    let byte2 = u16::from(crc_chars[1] & 0x3F);
-    let byte3 = u16::from(crc_chars[2] & 0x3F);
-    (byte1 << 12) | (byte2 << 6) | byte3
+    try_decode_crc_ascii(crc_chars).expect("ASCII CRC must be 3 bytes long")
+}
+
+/// Decodes three SDI-12 ASCII-encoded CRC characters, or returns `None` if
+/// `crc_chars` isn't exactly 3 bytes long.
+pub fn try_decode_crc_ascii(crc_chars: &[u8]) -> Option<u16> {
+    let &[char1, char2, char3] = crc_chars else {
+        return None;
+    };
+    let byte1 = u16::from(char1 & 0x3F);
+    let byte2 = u16::from(char2 & 0x3F);
+    let byte3 = u16::from(char3 & 0x3F);
+    Some((byte1 << 12) | (byte2 << 6) | byte3)
 }
 
 /// Verifies an SDI-12 response string that includes an ASCII CRC.
@@ -108,7 +168,7 @@ where
     let received_crc_bytes = &response_with_crc[data_len..];
 
     let calculated_crc = calculate_crc16(data_part);
-    let received_crc = decode_crc_ascii(received_crc_bytes);
+    let received_crc = try_decode_crc_ascii(received_crc_bytes).ok_or(Sdi12Error::InvalidFormat)?;
 
     if calculated_crc == received_crc {
         Ok(())
@@ -117,6 +177,70 @@ where
     }
 }
 
+/// Builds a complete CRC-protected response line, the sensor-side mirror of
+/// `verify_response_crc_ascii`.
+///
+/// Copies `payload` into `buf`, then appends its ASCII CRC and `<CR><LF>`.
+///
+/// # Arguments
+///
+/// * `payload`: The response including the address, e.g. `b"0+3.14"`.
+/// * `buf`: Destination for the line; needs `payload.len() + 5` bytes.
+///
+/// # Returns
+///
+/// The number of bytes written (`payload.len() + 5`), or `None` if `buf` is
+/// too short for the line, in which case nothing is written.
+pub fn format_response_with_crc(payload: &[u8], buf: &mut [u8]) -> Option<usize> {
+    if buf.len() < payload.len() + 5 {
+        return None;
+    }
+    buf[..payload.len()].copy_from_slice(payload);
+    append_crc_and_terminator(buf, payload.len())
+}
+
+/// `format_response_with_crc` for a payload already in `buf[..payload_len]`:
+/// appends its ASCII CRC and `<CR><LF>` and returns the line length, or
+/// `None` (writing nothing) if `buf` is too short.
+pub(crate) fn append_crc_and_terminator(buf: &mut [u8], payload_len: usize) -> Option<usize> {
+    let len = payload_len + 5;
+    if buf.len() < len {
+        return None;
+    }
+    let crc = encode_crc_ascii(calculate_crc16(&buf[..payload_len]));
+    buf[payload_len..payload_len + 3].copy_from_slice(&crc);
+    buf[payload_len + 3..len].copy_from_slice(b"\r\n");
+    Some(len)
+}
+
+/// Heuristically checks whether a response ends with an ASCII-encoded CRC.
+///
+/// Each CRC character is `0x40 | (6 bits)`, so its top two bits are always `01`.
+/// Data values (`+`, `-`, `.`, digits) never have that pattern, so three such
+/// bytes following a non-CRC-looking byte are a strong hint a CRC is present.
+/// Free-text payloads (e.g. identification) can still produce false positives.
+///
+/// # Arguments
+///
+/// * `response`: The response buffer including the address but *excluding* `<CR><LF>`.
+///
+/// # Returns
+///
+/// `true` if the last three bytes look like an ASCII CRC.
+pub fn looks_like_crc_ascii(response: &[u8]) -> bool {
+    let is_crc_char = |b: &u8| (b & 0xC0) == 0x40;
+    // Need at least the address plus three CRC characters.
+    if response.len() < 4 {
+        return false;
+    }
+    let crc_start = response.len() - 3;
+    if !response[crc_start..].iter().all(is_crc_char) {
+        return false;
+    }
+    // The address itself may be a letter, so only inspect real payload bytes.
+    crc_start == 1 || !is_crc_char(&response[crc_start - 1])
+}
+
 /// Encodes a 16-bit CRC value into two bytes (LSB first) for binary responses.
 ///
 /// # Arguments
@@ -142,10 +266,19 @@ pub fn encode_crc_binary(crc_value: u16) -> [u8; 2] {
 ///
 /// # Panics
 ///
-/// Panics if `crc_bytes` does not have a length of exactly 2.
+/// Panics if `crc_bytes` does not have a length of exactly 2. Use
+/// `try_decode_crc_binary` for input that hasn't been length-checked.
 pub fn decode_crc_binary(crc_bytes: &[u8]) -> u16 {
-    assert_eq!(crc_bytes.len(), 2, "Binary CRC must be 2 bytes long");
-    u16::from_le_bytes([crc_bytes[0], crc_bytes[1]])
+    try_decode_crc_binary(crc_bytes).expect("Binary CRC must be 2 bytes long")
+}
+
+/// Decodes two bytes (LSB first) into a 16-bit CRC value, or returns `None`
+/// if `crc_bytes` isn't exactly 2 bytes long.
+pub fn try_decode_crc_binary(crc_bytes: &[u8]) -> Option<u16> {
+    let &[lsb, msb] = crc_bytes else {
+        return None;
+    };
+    Some(u16::from_le_bytes([lsb, msb]))
 }
 
 /// Verifies an SDI-12 high-volume binary response packet including its binary CRC.
@@ -173,7 +306,7 @@ where
     let received_crc_bytes = &packet_with_crc[data_len..];
 
     let calculated_crc = calculate_crc16(data_part);
-    let received_crc = decode_crc_binary(received_crc_bytes);
+    let received_crc = try_decode_crc_binary(received_crc_bytes).ok_or(Sdi12Error::InvalidFormat)?;
 
     if calculated_crc == received_crc {
         Ok(())
@@ -442,6 +575,42 @@ mod tests {
         assert!(verify_packet_crc_binary::<MockIoError>(&packet).is_ok(), "Binary Ex DB2: Verification failed");
     }
 
+    #[test]
+    fn test_digest_matches_single_shot_on_spec_examples() {
+        let examples: [&[u8]; 4] = [
+            b"0+3.14",
+            b"0+3.14+2.718+1.414",
+            b"0+1.11+2.22+3.33+4.44+5.55+6.66",
+            &[0x31, 0x08, 0x00, 0x09, 0xC3, 0xF5, 0x48, 0x40, 0x00, 0x00, 0x80, 0x3F],
+        ];
+        for data in examples {
+            // Every split point, including empty first/second halves
+            for split in 0..=data.len() {
+                let (a, b) = data.split_at(split);
+                let mut digest = Sdi12CrcDigest::new();
+                digest.update(a);
+                digest.update(b);
+                assert_eq!(digest.finalize(), calculate_crc16(data), "split at {}", split);
+            }
+            // Byte at a time, as from a UART
+            let mut digest = Sdi12CrcDigest::default();
+            data.iter().for_each(|b| digest.update(core::slice::from_ref(b)));
+            assert_eq!(digest.finalize(), calculate_crc16(data));
+        }
+        assert_eq!(Sdi12CrcDigest::new().finalize(), calculate_crc16(&[]));
+    }
+
+    #[test]
+    fn test_format_response_with_crc_spec_examples() {
+        let mut buf = [0u8; 48];
+        let len = format_response_with_crc(b"0+3.14", &mut buf).unwrap();
+        assert_eq!(&buf[..len], b"0+3.14OqZ\r\n");
+        let len = format_response_with_crc(b"0+3.14+2.718+1.414", &mut buf).unwrap();
+        assert_eq!(&buf[..len], b"0+3.14+2.718+1.414Ipz\r\n");
+        // Round trip through the recorder-side check
+        assert!(verify_response_crc_ascii::<MockIoError>(&buf[..len - 2]).is_ok());
+    }
+
     // --- Optional: Keep basic roundtrip/error tests if desired ---
     #[test]
     fn test_crc_ascii_encoding_decoding_roundtrip_extra() {
@@ -503,6 +672,30 @@ mod tests {
         assert!(matches!(verify_packet_crc_binary::<MockIoError>(b""), Err(Sdi12Error::InvalidFormat)));
     }
 
+    #[test]
+    fn test_looks_like_crc_ascii() {
+        assert!(looks_like_crc_ascii(b"0+3.14OqZ"));
+        assert!(looks_like_crc_ascii(b"0Ba@")); // Bare acknowledge with CRC
+        assert!(looks_like_crc_ascii(b"aBa@")); // Letter address is not payload
+        assert!(!looks_like_crc_ascii(b"0+3.14"));
+        assert!(!looks_like_crc_ascii(b"0+3.14-2.5"));
+        assert!(!looks_like_crc_ascii(b"00013"));
+        assert!(!looks_like_crc_ascii(b"014VENDORXXMODEL1"));
+        assert!(!looks_like_crc_ascii(b"0Oq"));
+        assert!(!looks_like_crc_ascii(b""));
+    }
+
+    #[test]
+    fn test_try_decode_rejects_wrong_lengths() {
+        assert_eq!(try_decode_crc_ascii(b"OqZ"), Some(decode_crc_ascii(b"OqZ")));
+        assert_eq!(try_decode_crc_ascii(b"Oq"), None);
+        assert_eq!(try_decode_crc_ascii(b"OqZZ"), None);
+        assert_eq!(try_decode_crc_ascii(b""), None);
+        assert_eq!(try_decode_crc_binary(&[0xC2, 0xAC]), Some(0xACC2));
+        assert_eq!(try_decode_crc_binary(&[0xC2]), None);
+        assert_eq!(try_decode_crc_binary(&[0xC2, 0xAC, 0x00]), None);
+    }
+
     // Panic tests for decode functions remain useful
     #[test]
     #[should_panic]
@@ -516,4 +709,11 @@ mod tests {
     #[test]
     #[should_panic]
     fn test_decode_binary_panic_long() { decode_crc_binary(&[0xC2, 0xAC, 0x00]); }
+    #[test]
+    fn test_format_response_with_crc_short_buffer() {
+        let mut buf = [0u8; 10];
+        assert_eq!(format_response_with_crc(b"0+3.14", &mut buf), None);
+        assert_eq!(buf, [0u8; 10]);
+        assert_eq!(format_response_with_crc(b"0+3.1", &mut buf), Some(10));
+    }
 }
\ No newline at end of file
@@ -5,8 +5,25 @@ use alloc::string::String;
 
 // Import the specific command error types
 use crate::common::command::{CommandFormatError, CommandIndexError};
-
-// No more cfg_attr needed here, thiserror is always available
+use crate::common::frame::BaudRate;
+use crate::common::response::ResponseParseError;
+
+/// The crate's top-level error.
+///
+/// Parsers and formatters return narrower errors, all of which convert into
+/// `Sdi12Error<()>` with `?`, so code mixing them can use one error type:
+///
+/// | Entry point | Error |
+/// |---|---|
+/// | `sensor::parse_command` | `Sdi12Error<()>` |
+/// | `parse_response` and its variants | `ResponseParseError` |
+/// | `Command::format_into`, `Command::body_into` | `CommandFormatError` |
+/// | Index constructors (`MeasurementIndex::new`, ...) | `CommandIndexError` |
+/// | `SyncRecorder` methods | `Sdi12Error<IF::Error>` |
+///
+/// With an I/O error type other than `()` the `From` impls are unavailable
+/// (see `from_parse_error`). `ResponseParseError::try_from` gets a parse error
+/// back out.
 #[derive(Debug, PartialEq, Eq, thiserror::Error)]
 pub enum Sdi12Error<E = ()>
 where
@@ -20,6 +37,21 @@ where
     #[error("Operation timed out")]
     Timeout,
 
+    /// No response started within the allowed time (sensor absent, asleep or
+    /// didn't hear the command).
+    #[error("No response from sensor")]
+    ResponseTimeout,
+
+    /// A response started but stalled between characters before `<CR><LF>`.
+    #[error("Response truncated: inter-character timeout")]
+    InterCharTimeout,
+
+    /// Every attempt of a retried transaction failed (or its deadline left no
+    /// time for more, see `execute_transaction_with_deadline`); `last` says how
+    /// the final one did. A single timeout is only seen without retries (`send_raw`).
+    #[error("Gave up after {attempts} attempts: {last}")]
+    RetriesExhausted { attempts: usize, last: RetryCause },
+
     /// Invalid character received where it's not allowed (e.g., non-printable ASCII).
     #[error("Invalid character received: {0:#04x}")]
     InvalidCharacter(u8),
@@ -32,6 +64,10 @@ where
     #[error("Invalid response format")]
     InvalidFormat,
 
+    /// The sensor kept sending past the longest response the spec allows for the command.
+    #[error("Response too long: exceeded {max} bytes")]
+    ResponseTooLong { max: usize },
+
     /// Buffer provided was too small.
     #[error("Buffer overflow: needed {needed}, got {got}")]
     BufferOverflow { needed: usize, got: usize },
@@ -53,6 +89,7 @@ where
     UnexpectedResponse, // Consider adding details later
 
     /// Bus contention detected (multiple devices responding simultaneously).
+    /// Reported when a received line has a garbled address or non-printable characters.
     #[error("Bus contention detected")]
     BusContention,
 
@@ -60,6 +97,15 @@ where
     #[error("Invalid command index: {0}")] // Uses Display impl of CommandIndexError
     InvalidCommandIndex(CommandIndexError), // Wrap CommandIndexError
 
+    /// A response could not be parsed; carries the specific reason.
+    #[error("Response parse error: {0}")] // Uses Display impl of ResponseParseError
+    ParseError(ResponseParseError),
+
+    /// The recorder is configured for a baud rate the interface can't switch
+    /// to (see `Sdi12Serial::supports_baud_rate`).
+    #[error("Baud rate not supported by the interface: {0:?}")]
+    UnsupportedBaudRate(BaudRate),
+
     /// Error during command formatting.
     #[error("Command formatting failed: {0}")] // Uses Display impl of CommandFormatError
     CommandFormatFailed(CommandFormatError), // Wrap CommandFormatError
@@ -73,6 +119,17 @@ where
     // Add other variants as needed...
 }
 
+/// The failures a transaction retries, as reported by `Sdi12Error::RetriesExhausted`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RetryCause {
+    /// See `Sdi12Error::ResponseTimeout`.
+    #[error("No response from sensor")]
+    ResponseTimeout,
+    /// See `Sdi12Error::InterCharTimeout`.
+    #[error("Response truncated: inter-character timeout")]
+    InterCharTimeout,
+}
+
 // Allow mapping from underlying HAL error if From is implemented
 impl<E: core::fmt::Debug> From<E> for Sdi12Error<E> {
     fn from(e: E) -> Self {
@@ -80,6 +137,30 @@ impl<E: core::fmt::Debug> From<E> for Sdi12Error<E> {
     }
 }
 
+impl<E: core::fmt::Debug> Sdi12Error<E> {
+    /// Converts a `ResponseParseError` for any I/O error type, keeping CRC
+    /// mismatches as `CrcMismatch` and wrapping everything else in `ParseError`.
+    ///
+    /// The blanket `From<E>` impl above rules out a generic `From<ResponseParseError>`,
+    /// so this is what the library uses internally.
+    pub fn from_parse_error(e: ResponseParseError) -> Self {
+        match e {
+            ResponseParseError::CrcMismatch { expected, calculated } => {
+                Sdi12Error::CrcMismatch { expected, calculated }
+            }
+            other => Sdi12Error::ParseError(other),
+        }
+    }
+}
+
+// Map response parse errors into the main error type (with default E=())
+// This resolves the E0119 conflict.
+impl From<ResponseParseError> for Sdi12Error<()> {
+    fn from(e: ResponseParseError) -> Self {
+        Sdi12Error::from_parse_error(e)
+    }
+}
+
 // Map command index errors into the main error type (with default E=())
 // This resolves the E0119 conflict.
 impl From<CommandIndexError> for Sdi12Error<()> {
@@ -94,4 +175,47 @@ impl From<CommandFormatError> for Sdi12Error<()> {
     fn from(e: CommandFormatError) -> Self {
         Sdi12Error::CommandFormatFailed(e)
     }
-}
\ No newline at end of file
+}
+
+/// Recovers the parse error wrapped by `from_parse_error`, including a
+/// `CrcMismatch`; any other error is handed back unchanged.
+impl<E: core::fmt::Debug> TryFrom<Sdi12Error<E>> for ResponseParseError {
+    type Error = Sdi12Error<E>;
+    fn try_from(e: Sdi12Error<E>) -> Result<Self, Self::Error> {
+        match e {
+            Sdi12Error::ParseError(parse_error) => Ok(parse_error),
+            Sdi12Error::CrcMismatch { expected, calculated } => Ok(ResponseParseError::CrcMismatch { expected, calculated }),
+            other => Err(other),
+        }
+    }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::command::{Command, MeasurementIndex};
+    use crate::common::response::{parse_response, Response};
+    use crate::sensor::parse_command;
+
+    // A tool that parses both sides of an exchange with one `?` chain
+    fn exchange_matches(command: &[u8], response: &[u8]) -> Result<bool, Sdi12Error> {
+        let command = parse_command(command)?;
+        let index = MeasurementIndex::new(Some(1))?;
+        let response = parse_response(response)?;
+        Ok(matches!((command, response), (Command::StartMeasurement { index: i, .. }, Response::Timing(_)) if i == index))
+    }
+
+    #[test]
+    fn test_errors_convert_both_ways() {
+        assert_eq!(exchange_matches(b"0M1!", b"00012\r\n"), Ok(true));
+        assert_eq!(exchange_matches(b"0M!", b"00012\r\n"), Ok(false));
+        assert_eq!(exchange_matches(b"*M1!", b"00012\r\n"), Err(Sdi12Error::InvalidAddress('*')));
+
+        let error = exchange_matches(b"0M1!", b"00012").unwrap_err();
+        assert_eq!(ResponseParseError::try_from(error), Ok(ResponseParseError::MissingCrLf));
+        let crc: Sdi12Error<u8> = Sdi12Error::from_parse_error(ResponseParseError::CrcMismatch { expected: 1, calculated: 2 });
+        assert_eq!(ResponseParseError::try_from(crc), Ok(ResponseParseError::CrcMismatch { expected: 1, calculated: 2 }));
+        assert_eq!(ResponseParseError::try_from(Sdi12Error::<u8>::Timeout), Err(Sdi12Error::Timeout));
+    }
+}
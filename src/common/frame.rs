@@ -1,10 +1,157 @@
 // src/common/frame.rs
 
+use core::time::Duration;
+
 /// Represents the serial frame formats used in SDI-12.
+///
+/// Everything on the bus (commands, ASCII responses, and the data of `aHA!`
+/// measurements) is 7E1. Only the packets returned by `aDBn!` after an `aHB!`
+/// measurement are 8N1; the recorder switches back to 7E1 before the next
+/// command. Both run at 1200 baud, unless the recorder is configured with a
+/// non-standard `BaudRate`.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum FrameFormat {
     /// Standard SDI-12 format: 1200 baud, 7 data bits, Even parity, 1 stop bit.
     Sdi12_7e1,
     /// Format for High-Volume Binary data: 1200 baud, 8 data bits, No parity, 1 stop bit.
     Binary8N1,
-}
\ No newline at end of file
+}
+
+/// Parity setting of a `FrameFormat`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Parity {
+    None,
+    Even,
+}
+
+impl FrameFormat {
+    /// The format of commands and ASCII responses (7E1).
+    pub const fn for_ascii() -> Self {
+        FrameFormat::Sdi12_7e1
+    }
+
+    /// The format of `aDBn!` binary packets (8N1).
+    pub const fn for_binary() -> Self {
+        FrameFormat::Binary8N1
+    }
+
+    /// Looks up the format with the given UART settings; `None` for any
+    /// combination SDI-12 doesn't use.
+    pub const fn from_parts(data_bits: u8, parity: Parity, stop_bits: u8) -> Option<Self> {
+        match (data_bits, parity, stop_bits) {
+            (7, Parity::Even, 1) => Some(FrameFormat::Sdi12_7e1),
+            (8, Parity::None, 1) => Some(FrameFormat::Binary8N1),
+            _ => None,
+        }
+    }
+
+    /// Data bits per character: 7 or 8.
+    pub const fn data_bits(&self) -> u8 {
+        match self {
+            FrameFormat::Sdi12_7e1 => 7,
+            FrameFormat::Binary8N1 => 8,
+        }
+    }
+
+    /// Even for 7E1, none for 8N1.
+    pub const fn parity(&self) -> Parity {
+        match self {
+            FrameFormat::Sdi12_7e1 => Parity::Even,
+            FrameFormat::Binary8N1 => Parity::None,
+        }
+    }
+
+    /// Always 1 in SDI-12.
+    pub const fn stop_bits(&self) -> u8 {
+        1
+    }
+}
+
+/// Bit rate of the bus.
+///
+/// SDI-12 runs at 1200 baud, always. Other rates are non-standard: no
+/// compliant sensor answers at them, but some vendors' test modes and bench
+/// setups use SDI-12 framing at higher speeds. The recorder passes its
+/// `RecorderConfig::baud_rate` to the interface with
+/// `Sdi12Serial::set_baud_rate` and scales its read and write timeouts to it;
+/// the protocol timings in `timing` (break, marking, response start) don't
+/// depend on the baud rate and stay as they are.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BaudRate(u32);
+
+impl BaudRate {
+    /// 1200 baud, the only rate the spec allows.
+    pub const STANDARD: Self = BaudRate(1200);
+
+    /// A bus at `bits_per_second`; `None` for 0.
+    pub const fn new(bits_per_second: u32) -> Option<Self> {
+        if bits_per_second == 0 { None } else { Some(BaudRate(bits_per_second)) }
+    }
+
+    pub const fn bits_per_second(&self) -> u32 {
+        self.0
+    }
+
+    /// Whether this is the standard 1200 baud.
+    pub const fn is_standard(&self) -> bool {
+        self.0 == Self::STANDARD.0
+    }
+
+    /// Nominal duration of one bit; `timing::BIT_DURATION` at 1200 baud.
+    pub const fn bit_duration(&self) -> Duration {
+        Duration::from_nanos(1_000_000_000 / self.0 as u64)
+    }
+
+    /// Nominal duration of one 10-bit character (7E1 or 8N1, both with start
+    /// and stop bits), in whole microseconds; `timing::BYTE_DURATION` at 1200 baud.
+    pub const fn byte_duration(&self) -> Duration {
+        Duration::from_micros(10_000_000 / self.0 as u64)
+    }
+
+    /// Nominal time to send `bytes` bytes; `timing::transmission_time` at 1200
+    /// baud, capped the same way.
+    pub fn transmission_time(&self, bytes: usize) -> Duration {
+        let bytes = u16::try_from(bytes).unwrap_or(u16::MAX);
+        self.byte_duration() * u32::from(bytes)
+    }
+}
+
+impl Default for BaudRate {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_format_parts_round_trip() {
+        for format in [FrameFormat::for_ascii(), FrameFormat::for_binary()] {
+            assert_eq!(FrameFormat::from_parts(format.data_bits(), format.parity(), format.stop_bits()), Some(format));
+        }
+        assert_eq!(FrameFormat::for_ascii().parity(), Parity::Even);
+        assert_eq!(FrameFormat::for_binary().data_bits(), 8);
+        assert_eq!(FrameFormat::from_parts(8, Parity::Even, 1), None);
+        assert_eq!(FrameFormat::from_parts(7, Parity::Even, 2), None);
+    }
+
+    #[test]
+    fn test_baud_rate_durations() {
+        use crate::common::timing;
+        let standard = BaudRate::default();
+        assert!(standard.is_standard());
+        assert_eq!(standard.bit_duration(), timing::BIT_DURATION);
+        assert_eq!(standard.byte_duration(), timing::BYTE_DURATION);
+        assert_eq!(standard.transmission_time(81), timing::transmission_time(81));
+        assert_eq!(standard.transmission_time(usize::MAX), timing::transmission_time(usize::MAX));
+
+        let fast = BaudRate::new(9600).unwrap();
+        assert!(!fast.is_standard());
+        assert_eq!(fast.byte_duration(), Duration::from_micros(1041));
+        assert_eq!(fast.transmission_time(8), Duration::from_micros(8328));
+        assert_eq!(BaudRate::new(0), None);
+    }
+}
@@ -4,6 +4,15 @@ use core::fmt::Debug; // Already there
 
 // Define an opaque Instant marker trait within our library
 // This avoids depending directly on embedded_hal::timer::Instant in the core trait
+/// A point in time from `Sdi12Timer::now`.
+///
+/// The recorder subtracts instants to decide whether a break can be skipped and
+/// compares them against deadlines. It assumes `now` never goes backwards; if it
+/// does (a hardware counter wrapped), the recorder sends a break rather than trust
+/// the difference. A counter that wraps must therefore have a period well above
+/// `timing::PRE_COMMAND_BREAK_MARKING_THRESHOLD` (87 ms): a 16-bit counter at 1 kHz
+/// (65 s) is fine, a 16-bit counter at 1 MHz (65 ms) is not. Prefer extending
+/// such counters to 32 or 64 bits in the HAL.
 pub trait Sdi12Instant: Sized + Copy + Debug + Ord + core::ops::Add<Duration, Output = Self> + core::ops::Sub<Self, Output = Duration> {}
 
 // Blanket implementation for any type that satisfies the bounds
@@ -27,7 +36,7 @@ pub trait Sdi12Timer {
 }
 
 // ... (rest of hal_traits.rs remains the same)
-use super::frame::FrameFormat;
+use super::frame::{BaudRate, FrameFormat};
 
 // We need these traits potentially for the NativeSdi12Uart bounds
 #[cfg(feature = "impl-native")]
@@ -66,15 +75,55 @@ pub trait Sdi12Serial {
     /// This might block or return `WouldBlock` depending on the implementation strategy.
     fn send_break(&mut self) -> nb::Result<(), Self::Error>;
 
+    /// Tells the interface how long `send_break` should hold spacing.
+    ///
+    /// The recorder calls this before each break with `RecorderConfig::break_duration`
+    /// (never less than `timing::BREAK_DURATION_MIN`). Interfaces whose break length
+    /// is fixed by hardware keep the default, which ignores it.
+    fn set_break_duration(&mut self, _duration: Duration) {}
+
     /// Changes the serial configuration (e.g., between 7E1 and 8N1).
     ///
     /// This operation might be blocking or complex, hence `Result` instead of `nb::Result`.
     /// Errors could occur if the hardware doesn't support the format or reconfiguration fails.
     fn set_config(&mut self, config: FrameFormat) -> Result<(), Self::Error>;
+
+    /// Changes the bit rate, for the non-standard buses `BaudRate` describes.
+    ///
+    /// The recorder calls this with `RecorderConfig::baud_rate` just before each
+    /// `set_config`, so the two always change together, and only for rates
+    /// `supports_baud_rate` accepts. Interfaces fixed at 1200 baud keep the
+    /// default, which ignores it.
+    fn set_baud_rate(&mut self, _baud: BaudRate) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Reports whether `set_baud_rate` can switch the interface to `baud`.
+    ///
+    /// A recorder configured for a rate this rejects fails every command with
+    /// `Sdi12Error::UnsupportedBaudRate` rather than talking at 1200 baud with
+    /// timeouts sized for another rate. The default accepts only
+    /// `BaudRate::STANDARD`; interfaces implementing `set_baud_rate` override
+    /// this too.
+    fn supports_baud_rate(&self, baud: BaudRate) -> bool {
+        baud.is_standard()
+    }
+
+    /// Reports whether the line has stayed marking (idle) since this recorder last used it.
+    ///
+    /// The recorder only consults this when its own timing says a break can be skipped.
+    /// Returning `false` (e.g. the line is spacing, or another master was seen talking)
+    /// forces a break before the next command, as required by spec section 7.1.
+    /// Interfaces that can't observe the line keep the default, which always
+    /// trusts the recorder's timing.
+    fn line_is_marking(&mut self) -> bool {
+        true
+    }
 }
 
 /// Abstraction for asynchronous SDI-12 serial communication (requires 'async' feature).
 #[cfg(feature = "async")]
+#[allow(async_fn_in_trait)] // Single-threaded embedded executors don't need `Send` futures
 pub trait Sdi12SerialAsync {
     /// Associated error type for communication errors.
     /// Must implement Debug for error reporting.
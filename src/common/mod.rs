@@ -20,20 +20,21 @@ pub use address::Sdi12Addr;
 pub use command::{
     Command, CommandIndexError, CommandFormatError, // Added FormatError
     MeasurementIndex, ContinuousIndex, DataIndex, IdentifyParameterIndex,
-    IdentifyMeasurementCommand, IdentifyMeasurementParameterCommand,
+    IdentifyMeasurementCommand, IdentifyMeasurementParameterCommand, MeasurementBase,
 };
 
 // From crc.rs
 pub use crc::{
-    calculate_crc16, encode_crc_ascii, decode_crc_ascii, verify_response_crc_ascii,
-    encode_crc_binary, decode_crc_binary, verify_packet_crc_binary,
+    calculate_crc16, encode_crc_ascii, decode_crc_ascii, try_decode_crc_ascii, verify_response_crc_ascii,
+    encode_crc_binary, decode_crc_binary, try_decode_crc_binary, verify_packet_crc_binary,
+    looks_like_crc_ascii, Sdi12CrcDigest,
 };
 
 // From error.rs
-pub use error::Sdi12Error;
+pub use error::{RetryCause, Sdi12Error};
 
 // From frame.rs
-pub use frame::FrameFormat;
+pub use frame::{BaudRate, FrameFormat, Parity};
 
 // From hal_traits.rs
 pub use hal_traits::{Sdi12Serial, Sdi12Timer}; // Core sync traits
@@ -43,9 +44,20 @@ pub use response::{
     ResponseParseError, // The error enum for frame/crc/address issues
     MeasurementTiming,  // The struct for specifically parsed timing responses
     PayloadSlice,       // The wrapper for returned raw payloads
+    ResponseLines,      // Multiple raw lines read back-to-back
+    Response, IdentificationInfoRef, ParseOptions, // Optional parsing helpers
+    parse_response, parse_response_expecting, parse_response_with_options,
+    parse_response_for, parse_response_for_with_options,
+    BINARY_PACKET_MAX_PAYLOAD, BINARY_PACKET_OVERHEAD, // Sizing a buffer for `aDBn!`
 };
 
-// From timing.rs (constants)
+// From timing.rs (constants), the ones needed to compute custom timeouts
+pub use timing::{
+    BIT_DURATION, BYTE_DURATION, transmission_time,
+    BREAK_DURATION_MIN, POST_BREAK_MARKING_MIN,
+    RESPONSE_START_TIME_MAX, INTER_CHARACTER_MARKING_MAX,
+    RETRY_WAIT_MIN, SENSOR_WAKEUP_TIME_MAX,
+};
 
 // From types.rs
 pub use types::{BinaryDataType, Sdi12ParsingError, Sdi12Value};
@@ -63,6 +75,9 @@ pub use hal_traits::NativeSdi12Uart;
 #[cfg(all(feature = "async", feature = "impl-native"))]
 pub use hal_traits::NativeSdi12UartAsync;
 
-// Note: No alloc-dependent response types re-exported from common::response
-// Types like IdentificationInfo, DataInfo etc. are now internal details
-// potentially used by optional parsing helpers.
\ No newline at end of file
+// Owned response types for the optional parsing helpers (from response.rs)
+#[cfg(feature = "alloc")]
+pub use response::{parse_binary_packet, BinaryDataInfo, DataInfo, IdentificationInfo, MetadataInfo};
+// Fixed-capacity alternatives (from response.rs)
+#[cfg(feature = "use_heapless")]
+pub use response::{parse_response_heapless, DataInfoN};
\ No newline at end of file
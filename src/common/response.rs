@@ -1,9 +1,16 @@
 // src/common/response.rs
 
 use crate::common::address::Sdi12Addr;
-use crate::common::types::Sdi12ParsingError; // Keep for error composition
+use crate::common::command::Command;
+use crate::common::crc::{try_decode_crc_ascii, calculate_crc16, looks_like_crc_ascii};
 use core::fmt;
 
+use crate::common::crc::try_decode_crc_binary;
+use crate::common::types::{BinaryDataType, Sdi12Value};
+
+#[cfg(feature = "alloc")]
+use alloc::{string::{String, ToString}, vec::Vec};
+
 /// Error type specific to parsing the framing/address/CRC of an SDI-12 response.
 /// Does not cover errors from parsing the actual payload content (data values, ID fields etc.).
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -17,14 +24,20 @@ pub enum ResponseParseError {
     /// Invalid or unexpected address character at the start (e.g., '?').
     InvalidAddressChar,
     /// CRC validation failed.
-    CrcMismatch,
+    CrcMismatch { expected: u16, calculated: u16 },
     /// Binary packet size/structure inconsistent (if library handles binary framing).
     InconsistentBinaryPacketSize,
     /// Feature needed for a specific check/parse is not enabled.
     FeatureNotEnabled,
     /// Generic framing or structural format error.
     InvalidFormat,
-    // NOTE: Errors like ValueError, NumericError, InvalidIdentificationLength etc.
+    /// More values arrived than the fixed-capacity output can hold.
+    BufferOverflow,
+    /// Identification response with more than 13 characters in its optional field.
+    InvalidIdentificationLength,
+    /// A binary packet was read as a different type than it declares.
+    BinaryTypeMismatch { requested: BinaryDataType, actual: BinaryDataType },
+    // NOTE: Errors like ValueError, NumericError etc.
     // are removed as they relate to parsing the *payload*, which is now the user's responsibility
     // or handled by optional helpers. ResponseParseError focuses on the layer the library handles.
 }
@@ -45,6 +58,7 @@ impl std::error::Error for ResponseParseError {}
 /// (Example: `aTTTN<CR><LF>`)
 /// This is one structure the library *might* still parse directly, as it's not payload data.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MeasurementTiming {
     /// The address of the responding sensor.
     pub address: Sdi12Addr,
@@ -54,6 +68,32 @@ pub struct MeasurementTiming {
     pub values_count: u16,
 }
 
+impl MeasurementTiming {
+    /// Parses a timing payload (`tttn`, `tttnn` or `tttnnn`: the response without
+    /// address, CRC and `<CR><LF>`).
+    ///
+    /// The first three digits are always the time. Without the command, a faulty
+    /// four-digit `tttt` followed by `n` can't be told from `tttnn`; prefer
+    /// `from_payload_for` when the command is known.
+    pub fn from_payload(address: Sdi12Addr, payload: &[u8]) -> Result<Self, ResponseParseError> {
+        if !(4..=6).contains(&payload.len()) || !payload.iter().all(u8::is_ascii_digit) {
+            return Err(ResponseParseError::InvalidFormat);
+        }
+        parse_timing(address, payload)
+    }
+
+    /// Like `from_payload`, but requires exactly the count width `command`
+    /// calls for (`Command::timing_count_digits`), so `01234` answers `aM!` but
+    /// is `InvalidFormat` after `aC!`. Non-timing commands are `InvalidFormat`.
+    pub fn from_payload_for(command: &Command, payload: &[u8]) -> Result<Self, ResponseParseError> {
+        let count_digits = command.timing_count_digits().ok_or(ResponseParseError::InvalidFormat)?;
+        if payload.len() != 3 + count_digits {
+            return Err(ResponseParseError::InvalidFormat);
+        }
+        Self::from_payload(command.address(), payload)
+    }
+}
+
 
 // --- Placeholder for the Payload Slice Wrapper ---
 // This struct would be returned by recorder methods after validating
@@ -75,9 +115,19 @@ impl<'a> PayloadSlice<'a> {
         core::str::from_utf8(self.0)
     }
 
-    // Optional: Add helper methods here later under features?
-    // #[cfg(feature = "alloc")]
-    // pub fn parse_data_values(&self) -> Result<Vec<Sdi12Value>, ResponseParseError> { ... }
+    /// Parses the payload of an `aDn!`/`aRn!` reply as `<values>` and returns
+    /// an iterator over them, without allocating.
+    ///
+    /// The whole payload is checked up front, so a malformed value (or non-ASCII
+    /// bytes) is an `InvalidFormat` error here rather than a gap in the
+    /// iteration. An empty payload yields no values.
+    pub fn parse_values(&self) -> Result<impl Iterator<Item = Sdi12Value> + 'a, ResponseParseError> {
+        let text = self.as_str().map_err(|_| ResponseParseError::InvalidFormat)?;
+        if split_values(text).any(|v| Sdi12Value::parse_single(v).is_err()) {
+            return Err(ResponseParseError::InvalidFormat);
+        }
+        Ok(split_values(text).filter_map(|v| Sdi12Value::parse_single(v).ok()))
+    }
 }
 
 impl<'a> AsRef<[u8]> for PayloadSlice<'a> {
@@ -86,8 +136,894 @@ impl<'a> AsRef<[u8]> for PayloadSlice<'a> {
     }
 }
 
-// No parsing functions like parse_response defined here anymore.
-// That logic moves into internal recorder helpers or optional user-facing helpers.
+/// Several back-to-back `<CR><LF>`-terminated lines read into one buffer.
+/// Iterating yields each raw line, including its terminator (a bare `<LF>`
+/// also ends a line).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ResponseLines<'a> {
+    remaining: &'a [u8],
+}
+
+impl<'a> ResponseLines<'a> {
+    /// Wraps a buffer holding zero or more complete `<CR><LF>`-terminated lines.
+    pub fn new(data: &'a [u8]) -> Self {
+        ResponseLines { remaining: data }
+    }
+
+    /// Returns the not-yet-iterated bytes.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.remaining
+    }
+}
+
+impl<'a> Iterator for ResponseLines<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_empty() {
+            return None;
+        }
+        // Split after each <LF>, so lines from sensors that drop the <CR> work too.
+        let end = self.remaining
+            .iter()
+            .position(|&b| b == b'\n')
+            .map(|i| i + 1)
+            .unwrap_or(self.remaining.len()); // Trailing partial line, yield as-is
+        let (line, rest) = self.remaining.split_at(end);
+        self.remaining = rest;
+        Some(line)
+    }
+}
+
+// --- Optional response parsing helpers ---
+// The recorder itself only validates framing/address/CRC and hands back payload
+// indices. These helpers interpret a complete response line for users who want
+// structured data, e.g. when sniffing a bus or post-processing logged lines.
+
+/// Identification response (`aI!`) with fields borrowed from the input buffer.
+///
+/// Format: `allccccccccmmmmmmvvvxxx...xx`. Fixed-width fields keep any space
+/// padding the sensor sent.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IdentificationInfoRef<'a> {
+    /// The address of the responding sensor.
+    pub address: Sdi12Addr,
+    /// SDI-12 version supported, e.g. `"14"` for v1.4 (2 chars).
+    pub sdi12_version: &'a str,
+    /// Vendor identification (8 chars).
+    pub vendor: &'a str,
+    /// Sensor model number (6 chars).
+    pub model: &'a str,
+    /// Sensor version (3 chars).
+    pub sensor_version: &'a str,
+    /// Optional serial number or other info (0-13 chars).
+    pub optional: &'a str,
+}
+
+/// Owned copy of an identification response.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IdentificationInfo {
+    pub address: Sdi12Addr,
+    pub sdi12_version: String,
+    pub vendor: String,
+    pub model: String,
+    pub sensor_version: String,
+    pub optional: String,
+}
+
+#[cfg(feature = "alloc")]
+impl From<IdentificationInfoRef<'_>> for IdentificationInfo {
+    fn from(info: IdentificationInfoRef<'_>) -> Self {
+        IdentificationInfo {
+            address: info.address,
+            sdi12_version: info.sdi12_version.to_string(),
+            vendor: info.vendor.to_string(),
+            model: info.model.to_string(),
+            sensor_version: info.sensor_version.to_string(),
+            optional: info.optional.to_string(),
+        }
+    }
+}
+
+/// Data values returned by `aDn!` / `aRn!` (and their CRC variants).
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DataInfo {
+    pub address: Sdi12Addr,
+    pub values: Vec<Sdi12Value>,
+    /// The CRC carried by the response, if any. Data read over several `aDn!`
+    /// responses keeps the CRC of the last one.
+    pub crc: Option<u16>,
+    /// Whether the CRCs checked: `None` if the data had none, `Some(false)`
+    /// if any failed. Parsers reject a bad CRC, so only a recorder configured
+    /// with `CrcFailurePolicy::WarnAndAccept` returns `Some(false)`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub crc_valid: Option<bool>,
+}
+
+#[cfg(feature = "alloc")]
+impl DataInfo {
+    /// The values in response order; the same as the `values` field.
+    pub fn values(&self) -> &[Sdi12Value] {
+        &self.values
+    }
+}
+
+/// Iterating a `DataInfo` yields its values, so `for value in &data` works.
+#[cfg(feature = "alloc")]
+impl IntoIterator for DataInfo {
+    type Item = Sdi12Value;
+    type IntoIter = alloc::vec::IntoIter<Sdi12Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.into_iter()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> IntoIterator for &'a DataInfo {
+    type Item = &'a Sdi12Value;
+    type IntoIter = core::slice::Iter<'a, Sdi12Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.iter()
+    }
+}
+
+/// Fixed-capacity `DataInfo` for targets without an allocator.
+/// Holds at most `N` values.
+#[cfg(feature = "use_heapless")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataInfoN<const N: usize> {
+    pub address: Sdi12Addr,
+    pub values: heapless::Vec<Sdi12Value, N>,
+    /// The CRC carried by the response, if any (already verified).
+    pub crc: Option<u16>,
+}
+
+#[cfg(feature = "use_heapless")]
+impl<const N: usize> DataInfoN<N> {
+    /// The values in response order; the same as the `values` field.
+    pub fn values(&self) -> &[Sdi12Value] {
+        &self.values
+    }
+}
+
+#[cfg(feature = "use_heapless")]
+impl<const N: usize> IntoIterator for DataInfoN<N> {
+    type Item = Sdi12Value;
+    type IntoIter = <heapless::Vec<Sdi12Value, N> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.into_iter()
+    }
+}
+
+#[cfg(feature = "use_heapless")]
+impl<'a, const N: usize> IntoIterator for &'a DataInfoN<N> {
+    type Item = &'a Sdi12Value;
+    type IntoIter = core::slice::Iter<'a, Sdi12Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.iter()
+    }
+}
+
+/// Metadata returned by the identify-parameter commands (`aIM_001!` etc.).
+/// Format: `a,field1,field2[,...];`
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MetadataInfo {
+    pub address: Sdi12Addr,
+    /// The comma separated fields after the address, without the terminating `;`.
+    /// The address is not repeated here, so `fields[0]` is the parameter identifier.
+    pub fields: Vec<String>,
+    /// The CRC carried by the response, if any (already verified).
+    pub crc: Option<u16>,
+}
+
+#[cfg(feature = "alloc")]
+impl MetadataInfo {
+    /// Parameter identifier, e.g. a SHEF code like `"RP"` (spec field one).
+    pub fn parameter(&self) -> Option<&str> {
+        self.fields.first().map(String::as_str)
+    }
+
+    /// Units of the parameter (spec field two). May be `" "` for unit-less values.
+    pub fn units(&self) -> Option<&str> {
+        self.fields.get(1).map(String::as_str)
+    }
+
+    /// First optional field, conventionally a longer description.
+    pub fn description(&self) -> Option<&str> {
+        self.fields.get(2).map(String::as_str)
+    }
+
+    /// Any further sensor-specific fields after the description.
+    pub fn extra_fields(&self) -> &[String] {
+        self.fields.get(3..).unwrap_or(&[])
+    }
+}
+
+/// A high-volume binary data packet returned by `aDBn!` (spec 5.2.1).
+/// Layout: address, packet size (u16 LE), data type, payload, CRC (u16 LE).
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BinaryDataInfo {
+    pub address: Sdi12Addr,
+    pub data_type: BinaryDataType,
+    /// Raw little-endian values, `packet size` bytes long.
+    pub payload: Vec<u8>,
+    /// The packet CRC (already verified).
+    pub crc: u16,
+}
+
+#[cfg(feature = "alloc")]
+impl BinaryDataInfo {
+    /// Number of values in the payload (0 for an `InvalidRequest` packet).
+    pub fn value_count(&self) -> usize {
+        match self.data_type.size_in_bytes() {
+            0 => 0,
+            size => self.payload.len() / size,
+        }
+    }
+
+    /// Decodes every value into an `Sdi12Value`. 64-bit integers and `Float64`
+    /// are narrowed to `f32`.
+    pub fn decode_values(&self) -> Vec<Sdi12Value> {
+        self.values()
+            .map(|value| Sdi12Value::new(value.as_f64() as f32))
+            .collect()
+    }
+
+    /// The raw payload as space separated hex bytes (`"ff ff 01 00"`), formatted lazily.
+    pub fn payload_hex(&self) -> impl fmt::Display + '_ {
+        PayloadHex(&self.payload)
+    }
+
+    /// The payload interpreted per `data_type` (`"-1, 1"`), formatted lazily and
+    /// without narrowing, so 64-bit values print exactly.
+    pub fn display_values(&self) -> impl fmt::Display + '_ {
+        TypedPayload(self)
+    }
+
+    /// The payload as `i8` values; `BinaryTypeMismatch` unless the packet is `SignedI8`.
+    pub fn as_i8_slice(&self) -> Result<Vec<i8>, ResponseParseError> {
+        self.decode_as(BinaryDataType::SignedI8, i8::from_le_bytes)
+    }
+
+    /// The payload as `u8` values; `BinaryTypeMismatch` unless the packet is `UnsignedU8`.
+    pub fn as_u8_slice(&self) -> Result<Vec<u8>, ResponseParseError> {
+        self.decode_as(BinaryDataType::UnsignedU8, u8::from_le_bytes)
+    }
+
+    /// The payload as `i16` values; `BinaryTypeMismatch` unless the packet is `SignedI16`.
+    pub fn as_i16_slice(&self) -> Result<Vec<i16>, ResponseParseError> {
+        self.decode_as(BinaryDataType::SignedI16, i16::from_le_bytes)
+    }
+
+    /// The payload as `u16` values; `BinaryTypeMismatch` unless the packet is `UnsignedU16`.
+    pub fn as_u16_slice(&self) -> Result<Vec<u16>, ResponseParseError> {
+        self.decode_as(BinaryDataType::UnsignedU16, u16::from_le_bytes)
+    }
+
+    /// The payload as `i32` values; `BinaryTypeMismatch` unless the packet is `SignedI32`.
+    pub fn as_i32_slice(&self) -> Result<Vec<i32>, ResponseParseError> {
+        self.decode_as(BinaryDataType::SignedI32, i32::from_le_bytes)
+    }
+
+    /// The payload as `u32` values; `BinaryTypeMismatch` unless the packet is `UnsignedU32`.
+    pub fn as_u32_slice(&self) -> Result<Vec<u32>, ResponseParseError> {
+        self.decode_as(BinaryDataType::UnsignedU32, u32::from_le_bytes)
+    }
+
+    /// The payload as `i64` values; `BinaryTypeMismatch` unless the packet is `SignedI64`.
+    pub fn as_i64_slice(&self) -> Result<Vec<i64>, ResponseParseError> {
+        self.decode_as(BinaryDataType::SignedI64, i64::from_le_bytes)
+    }
+
+    /// The payload as `u64` values; `BinaryTypeMismatch` unless the packet is `UnsignedU64`.
+    pub fn as_u64_slice(&self) -> Result<Vec<u64>, ResponseParseError> {
+        self.decode_as(BinaryDataType::UnsignedU64, u64::from_le_bytes)
+    }
+
+    /// The payload as `f32` values; `BinaryTypeMismatch` unless the packet is `Float32`.
+    pub fn as_f32_slice(&self) -> Result<Vec<f32>, ResponseParseError> {
+        self.decode_as(BinaryDataType::Float32, f32::from_le_bytes)
+    }
+
+    /// The payload as `f64` values; `BinaryTypeMismatch` unless the packet is `Float64`.
+    pub fn as_f64_slice(&self) -> Result<Vec<f64>, ResponseParseError> {
+        self.decode_as(BinaryDataType::Float64, f64::from_le_bytes)
+    }
+
+    /// Decodes the payload at its native width, after checking the declared type.
+    fn decode_as<T, const N: usize>(
+        &self,
+        requested: BinaryDataType,
+        from_le_bytes: fn([u8; N]) -> T,
+    ) -> Result<Vec<T>, ResponseParseError> {
+        if self.data_type != requested {
+            return Err(ResponseParseError::BinaryTypeMismatch { requested, actual: self.data_type });
+        }
+        let values = self.payload.chunks_exact(N);
+        if !values.remainder().is_empty() {
+            return Err(ResponseParseError::InconsistentBinaryPacketSize);
+        }
+        Ok(values.map(|raw| from_le_bytes(raw.try_into().expect("chunk of N bytes"))).collect())
+    }
+
+    fn chunks(&self) -> core::slice::ChunksExact<'_, u8> {
+        match self.data_type.size_in_bytes() {
+            // InvalidRequest has no values, even with a (hand-built) non-empty payload.
+            0 => self.payload[..0].chunks_exact(1),
+            size => self.payload.chunks_exact(size),
+        }
+    }
+
+    fn values(&self) -> impl Iterator<Item = BinaryValue> + '_ {
+        self.chunks().filter_map(|raw| decode_binary_value(self.data_type, raw))
+    }
+}
+
+/// One decoded element of a binary payload, at full width.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum BinaryValue {
+    Signed(i64),
+    Unsigned(u64),
+    Float32(f32), // Kept narrow so it displays with f32 precision
+    Float64(f64),
+}
+
+#[cfg(feature = "alloc")]
+impl BinaryValue {
+    fn as_f64(self) -> f64 {
+        match self {
+            BinaryValue::Signed(v) => v as f64,
+            BinaryValue::Unsigned(v) => v as f64,
+            BinaryValue::Float32(v) => v.into(),
+            BinaryValue::Float64(v) => v,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for BinaryValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BinaryValue::Signed(v) => write!(f, "{}", v),
+            BinaryValue::Unsigned(v) => write!(f, "{}", v),
+            BinaryValue::Float32(v) => write!(f, "{}", v),
+            BinaryValue::Float64(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+/// Decodes one little-endian element. `raw` must be `data_type.size_in_bytes()`
+/// long. `None` for `InvalidRequest`, which has no values.
+#[cfg(feature = "alloc")]
+fn decode_binary_value(data_type: BinaryDataType, raw: &[u8]) -> Option<BinaryValue> {
+    fn le<const N: usize>(raw: &[u8]) -> [u8; N] {
+        raw.try_into().expect("chunk length matches data type")
+    }
+    Some(match data_type {
+        BinaryDataType::SignedI8 => BinaryValue::Signed(i8::from_le_bytes(le(raw)).into()),
+        BinaryDataType::UnsignedU8 => BinaryValue::Unsigned(u8::from_le_bytes(le(raw)).into()),
+        BinaryDataType::SignedI16 => BinaryValue::Signed(i16::from_le_bytes(le(raw)).into()),
+        BinaryDataType::UnsignedU16 => BinaryValue::Unsigned(u16::from_le_bytes(le(raw)).into()),
+        BinaryDataType::SignedI32 => BinaryValue::Signed(i32::from_le_bytes(le(raw)).into()),
+        BinaryDataType::UnsignedU32 => BinaryValue::Unsigned(u32::from_le_bytes(le(raw)).into()),
+        BinaryDataType::SignedI64 => BinaryValue::Signed(i64::from_le_bytes(le(raw))),
+        BinaryDataType::UnsignedU64 => BinaryValue::Unsigned(u64::from_le_bytes(le(raw))),
+        BinaryDataType::Float32 => BinaryValue::Float32(f32::from_le_bytes(le(raw))),
+        BinaryDataType::Float64 => BinaryValue::Float64(f64::from_le_bytes(le(raw))),
+        BinaryDataType::InvalidRequest => return None,
+    })
+}
+
+#[cfg(feature = "alloc")]
+struct PayloadHex<'a>(&'a [u8]);
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for PayloadHex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, byte) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "alloc")]
+struct TypedPayload<'a>(&'a BinaryDataInfo);
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for TypedPayload<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, value) in self.0.values().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}", value)?;
+        }
+        Ok(())
+    }
+}
+
+/// A response line interpreted by `parse_response`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Response<'a> {
+    /// Address only (`a<CR><LF>`), e.g. reply to `a!`, `aAb!` or `?!`.
+    Acknowledge { address: Sdi12Addr },
+    /// `atttn`, `atttnn` or `atttnnn` from measurement style commands.
+    Timing(MeasurementTiming),
+    /// Reply to `aI!`.
+    Identification(#[cfg_attr(feature = "serde", serde(borrow))] IdentificationInfoRef<'a>),
+    /// One or more `<values>`.
+    #[cfg(feature = "alloc")]
+    Data(DataInfo),
+    /// Reply to an identify-parameter command.
+    #[cfg(feature = "alloc")]
+    Metadata(MetadataInfo),
+    /// Any other printable payload, typically the reply to a vendor `aX...!`
+    /// command. Only produced with `ParseOptions::accept_extended_responses`.
+    Extended { address: Sdi12Addr, body: &'a str },
+}
+
+impl<'a> Response<'a> {
+    /// Whether the sensor answered without anything useful: a timing response
+    /// announcing no values (the measurement was aborted, or the sensor can't
+    /// make it) or a data response with no values (an empty register).
+    ///
+    /// A bare address only counts through `parse_response_for`: to `aDn!`
+    /// that reads as empty `Data`, while `parse_response` can't tell it from
+    /// the reply to `a!` and returns `Acknowledge`, which is never error-like.
+    pub fn is_error_like(&self) -> bool {
+        match self {
+            Response::Timing(timing) => timing.values_count == 0,
+            #[cfg(feature = "alloc")]
+            Response::Data(data) => data.values.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// The timing response, if this is one.
+    pub fn as_timing(&self) -> Option<&MeasurementTiming> {
+        match self {
+            Response::Timing(timing) => Some(timing),
+            _ => None,
+        }
+    }
+
+    /// The identification, if this is one.
+    pub fn as_identification(&self) -> Option<&IdentificationInfoRef<'a>> {
+        match self {
+            Response::Identification(info) => Some(info),
+            _ => None,
+        }
+    }
+
+    /// The data values, if this is a data response.
+    #[cfg(feature = "alloc")]
+    pub fn as_data(&self) -> Option<&DataInfo> {
+        match self {
+            Response::Data(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// The parameter metadata, if this is a metadata response.
+    #[cfg(feature = "alloc")]
+    pub fn as_metadata(&self) -> Option<&MetadataInfo> {
+        match self {
+            Response::Metadata(meta) => Some(meta),
+            _ => None,
+        }
+    }
+}
+
+/// Options for `parse_response_with_options`. The default matches `parse_response`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Whether the line ends in a CRC: `None` detects it structurally,
+    /// `Some(_)` is as for `parse_response_expecting`.
+    pub crc_expected: Option<bool>,
+    /// Return payloads that fit no standard response shape as
+    /// `Response::Extended` instead of failing with `InvalidFormat`.
+    ///
+    /// Off by default, since it also turns malformed standard responses
+    /// (a garbled identification, say) into extended ones.
+    pub accept_extended_responses: bool,
+    /// Accept lines ending in a bare `<LF>`, as sent by sensors that drop the `<CR>`.
+    pub accept_bare_lf: bool,
+}
+
+/// Length of the fixed part of an identification payload (ll + 8 + 6 + 3).
+const IDENTIFICATION_FIXED_LEN: usize = 2 + 8 + 6 + 3;
+/// Maximum length of the optional identification field.
+const IDENTIFICATION_OPTIONAL_MAX: usize = 13;
+
+/// Parses a complete ASCII response line (including `<CR><LF>`) into a `Response`.
+///
+/// The response kind is inferred from the payload shape, since the line alone
+/// doesn't say which command it answers.
+/// Timing responses are `atttn` (`M`), `atttnn` (`C`) or `atttnnn` (`HA`/`HB`);
+/// the first three digits are always the time. A trailing CRC is detected with
+/// `crc::looks_like_crc_ascii` and verified.
+///
+/// The CRC check is structural, so a payload that happens to end in three
+/// `@`..`DEL` characters (an identification string ending in `4KEY`, say) is
+/// mistaken for one. When the command is known, use `parse_response_expecting`.
+///
+/// Without the `alloc` feature, data and metadata responses return
+/// `ResponseParseError::FeatureNotEnabled`.
+pub fn parse_response(buffer: &[u8]) -> Result<Response<'_>, ResponseParseError> {
+    parse_response_with_options(buffer, ParseOptions::default())
+}
+
+/// Like `parse_response`, but told by the caller whether the line carries a CRC.
+///
+/// `crc_expected` should be true exactly when the originating command asked for
+/// one (`aMC!`, `aCC!`, `aRCn!`, ...). The CRC is then required and verified; when
+/// false, the trailing bytes are always treated as payload, however CRC-like.
+pub fn parse_response_expecting(buffer: &[u8], crc_expected: bool) -> Result<Response<'_>, ResponseParseError> {
+    parse_response_with_options(buffer, ParseOptions { crc_expected: Some(crc_expected), ..ParseOptions::default() })
+}
+
+/// `parse_response` with every option spelled out; see `ParseOptions`.
+pub fn parse_response_with_options(buffer: &[u8], options: ParseOptions) -> Result<Response<'_>, ResponseParseError> {
+    let crc = match options.crc_expected {
+        None => CrcHandling::Detect,
+        Some(true) => CrcHandling::Expected,
+        Some(false) => CrcHandling::Absent,
+    };
+    let (address, payload, crc) = split_response_line(buffer, crc, options.accept_bare_lf)?;
+
+    if payload.is_empty() {
+        return Ok(Response::Acknowledge { address });
+    }
+
+    let parsed = match payload[0] {
+        b',' => parse_metadata(address, payload, crc),
+        b'+' | b'-' => parse_data(address, payload, crc),
+        _ if payload.iter().all(u8::is_ascii_digit) && (4..=6).contains(&payload.len()) => {
+            parse_timing(address, payload).map(Response::Timing)
+        }
+        _ if payload.len() >= IDENTIFICATION_FIXED_LEN => {
+            parse_identification(address, payload).map(Response::Identification)
+        }
+        _ => Err(ResponseParseError::InvalidFormat),
+    };
+    match parsed {
+        Err(ResponseParseError::InvalidFormat | ResponseParseError::InvalidIdentificationLength)
+            if options.accept_extended_responses =>
+        {
+            parse_extended(address, payload)
+        }
+        other => other,
+    }
+}
+
+/// Parses a complete ASCII response line to `command` into a `Response`.
+///
+/// Unlike `parse_response`, nothing is guessed from the payload: the command
+/// decides the shape (acknowledge for `a!`/`?!`/`aAb!`, timing for `M`/`C`/`V`/`H`
+/// and `aIM!`-style commands, identification for `aI!`, data for `D`/`R`,
+/// metadata for identify-parameter commands, extended for `aX...!`) and whether
+/// a CRC must be there. Only `aDn!` leaves the CRC to detection, since the data
+/// command doesn't say whether `M` or `MC` started the measurement.
+///
+/// The response address isn't compared with the command's; see
+/// `recorder::validate_response` for that. `aDBn!` replies are binary packets
+/// (`parse_binary_packet`) and give `InvalidFormat` here.
+pub fn parse_response_for<'a>(command: &Command, buffer: &'a [u8]) -> Result<Response<'a>, ResponseParseError> {
+    parse_response_for_with_options(command, buffer, ParseOptions::default())
+}
+
+/// `parse_response_for` with `ParseOptions`. `crc_expected: None` takes the
+/// CRC expectation from the command; `Some(_)` overrides it.
+pub fn parse_response_for_with_options<'a>(
+    command: &Command,
+    buffer: &'a [u8],
+    options: ParseOptions,
+) -> Result<Response<'a>, ResponseParseError> {
+    let crc = match options.crc_expected {
+        Some(true) => CrcHandling::Expected,
+        Some(false) => CrcHandling::Absent,
+        None => CrcHandling::for_command(command),
+    };
+    let (address, payload, crc) = split_response_line(buffer, crc, options.accept_bare_lf)?;
+
+    let parsed = match command {
+        Command::AcknowledgeActive { .. } | Command::AddressQuery | Command::ChangeAddress { .. } => {
+            if payload.is_empty() {
+                Ok(Response::Acknowledge { address })
+            } else {
+                Err(ResponseParseError::InvalidFormat)
+            }
+        }
+        Command::SendIdentification { .. } => parse_identification(address, payload).map(Response::Identification),
+        Command::SendData { .. } | Command::ReadContinuous { .. } | Command::ReadContinuousCRC { .. } => {
+            parse_data(address, payload, crc)
+        }
+        Command::IdentifyMeasurementParameter(_) => parse_metadata(address, payload, crc),
+        Command::SendBinaryData { .. } => Err(ResponseParseError::InvalidFormat),
+        #[cfg(feature = "alloc")]
+        Command::ExtendedCommand { .. } if payload.is_empty() => Ok(Response::Acknowledge { address }),
+        #[cfg(feature = "alloc")]
+        Command::ExtendedCommand { .. } => parse_extended(address, payload),
+        // Everything else answers with `atttn[nn]`
+        _ => MeasurementTiming::from_payload_for(command, payload)
+            .map(|timing| Response::Timing(MeasurementTiming { address, ..timing })),
+    };
+    match parsed {
+        Err(ResponseParseError::InvalidFormat | ResponseParseError::InvalidIdentificationLength)
+            if options.accept_extended_responses && !payload.is_empty() =>
+        {
+            parse_extended(address, payload)
+        }
+        other => other,
+    }
+}
+
+/// Parses a data response (`aDn!` / `aRn!`) into a fixed-capacity `DataInfoN<N>`.
+///
+/// Sibling of `parse_response` for no-alloc targets. An address-only line yields
+/// an empty value list. Any payload that isn't `<values>` is `InvalidFormat`, and
+/// more than `N` values is `BufferOverflow`.
+#[cfg(feature = "use_heapless")]
+pub fn parse_response_heapless<const N: usize>(buffer: &[u8]) -> Result<DataInfoN<N>, ResponseParseError> {
+    let (address, payload, crc) = split_response_line(buffer, CrcHandling::Detect, false)?;
+    if !payload.is_empty() && !matches!(payload[0], b'+' | b'-') {
+        return Err(ResponseParseError::InvalidFormat);
+    }
+    let text = core::str::from_utf8(payload).map_err(|_| ResponseParseError::InvalidFormat)?;
+
+    let mut values = heapless::Vec::new();
+    for v in split_values(text) {
+        let value = Sdi12Value::parse_single(v).map_err(|_| ResponseParseError::InvalidFormat)?;
+        values.push(value).map_err(|_| ResponseParseError::BufferOverflow)?;
+    }
+    Ok(DataInfoN { address, values, crc })
+}
+
+/// Largest binary payload a sensor may send in one packet (spec 5.2.1).
+pub const BINARY_PACKET_MAX_PAYLOAD: usize = 1000;
+
+/// Bytes a binary packet adds to its payload: address, packet size (2), data
+/// type and CRC (2). A buffer of `BINARY_PACKET_MAX_PAYLOAD +
+/// BINARY_PACKET_OVERHEAD` bytes holds any packet.
+pub const BINARY_PACKET_OVERHEAD: usize = 6;
+
+/// Parses a complete high-volume binary packet (reply to `aDBn!`).
+///
+/// Checks that the declared packet size matches the buffer, is at most 1000
+/// bytes and is a whole number of `data_type` values, then verifies the CRC.
+#[cfg(feature = "alloc")]
+pub fn parse_binary_packet(buffer: &[u8]) -> Result<BinaryDataInfo, ResponseParseError> {
+    let (address, data_type, payload, crc) = split_binary_packet(buffer)?;
+    Ok(BinaryDataInfo { address, data_type, payload: payload.to_vec(), crc })
+}
+
+/// `parse_binary_packet` without the copy: the address, data type, payload
+/// slice and CRC of a checked packet.
+pub(crate) fn split_binary_packet(
+    buffer: &[u8],
+) -> Result<(Sdi12Addr, BinaryDataType, &[u8], u16), ResponseParseError> {
+    const HEADER_LEN: usize = 4; // address + size (2) + type
+    const CRC_LEN: usize = 2;
+    if buffer.is_empty() {
+        return Err(ResponseParseError::EmptyInput);
+    }
+    if buffer.len() < HEADER_LEN + CRC_LEN {
+        return Err(ResponseParseError::TooShort);
+    }
+
+    // The address byte is raw binary here: reject non-ASCII before widening it to a char.
+    if !buffer[0].is_ascii() {
+        return Err(ResponseParseError::InvalidAddressChar);
+    }
+    let address = Sdi12Addr::new(buffer[0] as char).map_err(|_| ResponseParseError::InvalidAddressChar)?;
+    let packet_size = usize::from(u16::from_le_bytes([buffer[1], buffer[2]]));
+    let data_type = BinaryDataType::from_u8(buffer[3]).ok_or(ResponseParseError::InvalidFormat)?;
+
+    let value_size = data_type.size_in_bytes();
+    let whole_values = match value_size {
+        0 => packet_size == 0,
+        size => packet_size % size == 0,
+    };
+    if packet_size > BINARY_PACKET_MAX_PAYLOAD
+        || buffer.len() != HEADER_LEN + packet_size + CRC_LEN
+        || !whole_values
+    {
+        return Err(ResponseParseError::InconsistentBinaryPacketSize);
+    }
+
+    let (body, crc_bytes) = buffer.split_at(buffer.len() - CRC_LEN);
+    let expected = try_decode_crc_binary(crc_bytes).ok_or(ResponseParseError::TooShort)?;
+    let calculated = calculate_crc16(body);
+    if expected != calculated {
+        return Err(ResponseParseError::CrcMismatch { expected, calculated });
+    }
+
+    Ok((address, data_type, &body[HEADER_LEN..], expected))
+}
+
+/// Whether a response line ends in an ASCII CRC.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum CrcHandling {
+    /// Unknown: guess with `looks_like_crc_ascii`.
+    Detect,
+    /// The command requested a CRC, so it must be there.
+    Expected,
+    /// No CRC was requested; the whole line is payload.
+    Absent,
+}
+
+impl CrcHandling {
+    /// What the reply to `command` alone says about its CRC: `aRCn!` and the
+    /// CRC metadata queries carry one, timing replies (even to `aMC!`) and
+    /// the other commands don't, and `aDn!` data has one only if the
+    /// measurement was started with a CRC command, which it doesn't say.
+    pub(crate) fn for_command(command: &Command) -> Self {
+        match command {
+            Command::SendData { .. } => CrcHandling::Detect,
+            Command::ReadContinuousCRC { .. } => CrcHandling::Expected,
+            Command::IdentifyMeasurementParameter(parameter) if parameter.is_crc_variant() => CrcHandling::Expected,
+            _ => CrcHandling::Absent,
+        }
+    }
+}
+
+/// Checks framing and address, verifies and strips a trailing CRC if present.
+/// Returns the address, the payload (after the address) and the CRC.
+fn split_response_line(
+    buffer: &[u8],
+    crc: CrcHandling,
+    accept_bare_lf: bool,
+) -> Result<(Sdi12Addr, &[u8], Option<u16>), ResponseParseError> {
+    if buffer.is_empty() {
+        return Err(ResponseParseError::EmptyInput);
+    }
+    let body = buffer
+        .strip_suffix(b"\r\n")
+        .or_else(|| buffer.strip_suffix(b"\n").filter(|_| accept_bare_lf))
+        .ok_or(ResponseParseError::MissingCrLf)?;
+    let (&addr_byte, _) = body.split_first().ok_or(ResponseParseError::TooShort)?;
+
+    let addr_char = addr_byte as char;
+    if !Sdi12Addr::is_valid_address_char(addr_char) {
+        return Err(ResponseParseError::InvalidAddressChar);
+    }
+    let address = Sdi12Addr::new(addr_char).map_err(|_| ResponseParseError::InvalidAddressChar)?;
+
+    let has_crc = match crc {
+        CrcHandling::Detect => looks_like_crc_ascii(body),
+        CrcHandling::Expected if body.len() < 4 => return Err(ResponseParseError::TooShort),
+        CrcHandling::Expected => true,
+        CrcHandling::Absent => false,
+    };
+    if has_crc {
+        let crc_start = body.len() - 3;
+        let received = try_decode_crc_ascii(&body[crc_start..]).ok_or(ResponseParseError::TooShort)?;
+        let calculated = calculate_crc16(&body[..crc_start]);
+        if calculated != received {
+            return Err(ResponseParseError::CrcMismatch { expected: received, calculated });
+        }
+        Ok((address, &body[1..crc_start], Some(received)))
+    } else {
+        Ok((address, &body[1..], None))
+    }
+}
+
+fn parse_timing(address: Sdi12Addr, payload: &[u8]) -> Result<MeasurementTiming, ResponseParseError> {
+    // Digits were checked by the caller, so only the length can go wrong here.
+    let number = |digits: &[u8]| digits.iter().fold(0u16, |acc, d| acc * 10 + u16::from(d - b'0'));
+    let (ttt, n) = payload.split_at(3);
+    Ok(MeasurementTiming {
+        address,
+        time_seconds: number(ttt),
+        values_count: number(n),
+    })
+}
+
+fn parse_identification(address: Sdi12Addr, payload: &[u8]) -> Result<IdentificationInfoRef<'_>, ResponseParseError> {
+    let too_short = ResponseParseError::InvalidIdentificationLength;
+    let version = payload.get(..2).ok_or(too_short)?;
+    if !version.iter().all(u8::is_ascii_digit) {
+        return Err(ResponseParseError::InvalidFormat);
+    }
+    // The optional field is up to 13 characters; more means a malformed reply, not one to truncate.
+    if payload.len() > IDENTIFICATION_FIXED_LEN + IDENTIFICATION_OPTIONAL_MAX {
+        return Err(ResponseParseError::InvalidIdentificationLength);
+    }
+    // Printable ASCII only, which also makes the str conversion infallible.
+    if !payload.iter().all(|b| (0x20..=0x7E).contains(b)) {
+        return Err(ResponseParseError::InvalidFormat);
+    }
+    let text = core::str::from_utf8(payload).map_err(|_| ResponseParseError::InvalidFormat)?;
+    // Checked slicing, so a short payload is an error whatever the caller checked.
+    let field = |range: core::ops::Range<usize>| text.get(range).ok_or(too_short);
+
+    Ok(IdentificationInfoRef {
+        address,
+        sdi12_version: field(0..2)?,
+        vendor: field(2..10)?,
+        model: field(10..16)?,
+        sensor_version: field(16..19)?,
+        optional: text.get(IDENTIFICATION_FIXED_LEN..).ok_or(too_short)?,
+    })
+}
+
+fn parse_extended(address: Sdi12Addr, payload: &[u8]) -> Result<Response<'_>, ResponseParseError> {
+    if !payload.iter().all(|b| (0x20..=0x7E).contains(b)) {
+        return Err(ResponseParseError::InvalidFormat);
+    }
+    let body = core::str::from_utf8(payload).map_err(|_| ResponseParseError::InvalidFormat)?;
+    Ok(Response::Extended { address, body })
+}
+
+#[cfg(feature = "alloc")]
+fn parse_data(address: Sdi12Addr, payload: &[u8], crc: Option<u16>) -> Result<Response<'_>, ResponseParseError> {
+    let text = core::str::from_utf8(payload).map_err(|_| ResponseParseError::InvalidFormat)?;
+    let values = split_values(text)
+        .map(|v| Sdi12Value::parse_single(v).map_err(|_| ResponseParseError::InvalidFormat))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Response::Data(DataInfo { address, values, crc, crc_valid: crc.map(|_| true) }))
+}
+
+#[cfg(not(feature = "alloc"))]
+fn parse_data(_address: Sdi12Addr, _payload: &[u8], _crc: Option<u16>) -> Result<Response<'_>, ResponseParseError> {
+    Err(ResponseParseError::FeatureNotEnabled)
+}
+
+#[cfg(feature = "alloc")]
+fn parse_metadata(address: Sdi12Addr, payload: &[u8], crc: Option<u16>) -> Result<Response<'_>, ResponseParseError> {
+    let text = core::str::from_utf8(payload).map_err(|_| ResponseParseError::InvalidFormat)?;
+    // Payload is ",field1,field2;" - drop the leading comma and trailing semicolon.
+    let fields = text
+        .strip_prefix(',')
+        .and_then(|t| t.strip_suffix(';'))
+        .ok_or(ResponseParseError::InvalidFormat)?;
+    let fields = fields.split(',').map(String::from).collect();
+    Ok(Response::Metadata(MetadataInfo { address, fields, crc }))
+}
+
+#[cfg(not(feature = "alloc"))]
+fn parse_metadata(_address: Sdi12Addr, _payload: &[u8], _crc: Option<u16>) -> Result<Response<'_>, ResponseParseError> {
+    Err(ResponseParseError::FeatureNotEnabled)
+}
+
+/// Splits a `<values>` string like `+1.23-4.5+6` into `"+1.23"`, `"-4.5"`, `"+6"`.
+/// Each value starts at a sign character.
+pub(crate) fn split_values(values: &str) -> impl Iterator<Item = &str> {
+    let mut rest = values;
+    core::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        // Search past the first character, which may be multi-byte in a garbled line
+        let end = rest
+            .char_indices()
+            .skip(1)
+            .find(|&(_, c)| c == '+' || c == '-')
+            .map_or(rest.len(), |(i, _)| i);
+        let (value, tail) = rest.split_at(end);
+        rest = tail;
+        Some(value)
+    })
+}
 
 // --- Tests ---
 #[cfg(test)]
@@ -106,6 +1042,30 @@ mod tests {
         assert_eq!(mt.time_seconds, 15);
     }
 
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_data_info_iteration() {
+        let data = DataInfo { address: addr('0'), values: alloc::vec![Sdi12Value::new(1.5), Sdi12Value::new(-2.0)], crc: None, crc_valid: None };
+        let mut sum = 0.0;
+        for value in &data {
+            sum += value.as_f32();
+        }
+        assert_eq!(sum, -0.5);
+        assert_eq!(data.values(), &data.values[..]);
+        assert_eq!(data.into_iter().collect::<Vec<_>>(), [Sdi12Value::new(1.5), Sdi12Value::new(-2.0)]);
+    }
+
+    #[test]
+    #[cfg(feature = "use_heapless")]
+    fn test_data_info_n_iteration() {
+        let mut values = heapless::Vec::<Sdi12Value, 4>::new();
+        values.push(Sdi12Value::new(3.0)).unwrap();
+        let data = DataInfoN { address: addr('0'), values, crc: None };
+        assert_eq!((&data).into_iter().count(), 1);
+        assert_eq!(data.values(), [Sdi12Value::new(3.0)]);
+        assert_eq!(data.into_iter().next(), Some(Sdi12Value::new(3.0)));
+    }
+
      #[test]
     fn test_payload_slice_wrapper() {
         let data: &[u8] = b"+1.23-45";
@@ -117,5 +1077,486 @@ mod tests {
         let non_utf8: &[u8] = &[0x80, 0x81]; // Invalid UTF-8
         let payload_bad = PayloadSlice(non_utf8);
         assert!(payload_bad.as_str().is_err());
+        assert!(payload_bad.parse_values().is_err());
+    }
+
+    #[test]
+    fn test_payload_slice_parse_values() {
+        let values = PayloadSlice(b"+1.23-45+0").parse_values().unwrap();
+        assert!(values.eq([Sdi12Value::new(1.23), Sdi12Value::new(-45.0), Sdi12Value::new(0.0)]));
+        assert_eq!(PayloadSlice(b"").parse_values().map(|v| v.count()), Ok(0));
+        assert!(matches!(PayloadSlice(b"+1.5-2x").parse_values(), Err(ResponseParseError::InvalidFormat)));
+        assert!(matches!(PayloadSlice(b"1.5").parse_values(), Err(ResponseParseError::InvalidFormat)));
+    }
+
+    #[test]
+    fn test_parse_response_acknowledge_and_timing() {
+        assert_eq!(parse_response(b"0\r\n"), Ok(Response::Acknowledge { address: addr('0') }));
+        assert_eq!(
+            parse_response(b"00015\r\n"),
+            Ok(Response::Timing(MeasurementTiming { address: addr('0'), time_seconds: 1, values_count: 5 }))
+        );
+        assert_eq!(
+            parse_response(b"a12312\r\n"),
+            Ok(Response::Timing(MeasurementTiming { address: addr('a'), time_seconds: 123, values_count: 12 }))
+        );
+    }
+
+    #[test]
+    fn test_response_accessors_and_is_error_like() {
+        use crate::sensor::parse_command;
+        let cmd = |bytes: &[u8]| parse_command(bytes).unwrap();
+
+        let timing = parse_response(b"00012\r\n").unwrap();
+        assert_eq!(timing.as_timing().map(|t| t.values_count), Some(2));
+        assert!(timing.as_identification().is_none());
+        assert!(!timing.is_error_like());
+        // No values to come: aborted or unsupported
+        assert!(parse_response(b"00000\r\n").unwrap().is_error_like());
+
+        let line = b"013VENDORXYMODEL1100\r\n";
+        let identification = parse_response(line).unwrap();
+        assert_eq!(identification.as_identification().map(|i| i.vendor), Some("VENDORXY"));
+        assert!(identification.as_timing().is_none());
+        assert!(!identification.is_error_like());
+
+        // A bare address is only error-like where data was expected
+        let ack = parse_response(b"0\r\n").unwrap();
+        assert!(!ack.is_error_like());
+        assert!(ack.as_timing().is_none());
+        #[cfg(feature = "alloc")]
+        {
+            let empty = parse_response_for(&cmd(b"0D0!"), b"0\r\n").unwrap();
+            assert!(empty.as_data().is_some_and(|d| d.values.is_empty()));
+            assert!(empty.is_error_like());
+            let data = parse_response_for(&cmd(b"0D0!"), b"0+1.5\r\n").unwrap();
+            assert_eq!(data.as_data().map(|d| d.values.len()), Some(1));
+            assert!(!data.is_error_like());
+            assert!(data.as_metadata().is_none());
+            let meta = parse_response_for(&cmd(b"0IM_001!"), b"0,TA,C;\r\n").unwrap();
+            assert_eq!(meta.as_metadata().map(|m| m.fields.len()), Some(2));
+        }
+        assert!(!parse_response_for(&cmd(b"0!"), b"0\r\n").unwrap().is_error_like());
+    }
+
+    #[test]
+    fn test_parse_response_high_volume_timing() {
+        // aHA!/aHB! always answer atttnnn with a three-digit count
+        assert_eq!(
+            parse_response(b"0045300\r\n"),
+            Ok(Response::Timing(MeasurementTiming { address: addr('0'), time_seconds: 45, values_count: 300 }))
+        );
+        assert_eq!(
+            parse_response(b"Z999999\r\n"),
+            Ok(Response::Timing(MeasurementTiming { address: addr('Z'), time_seconds: 999, values_count: 999 }))
+        );
+        // Leading zeros in nnn are still a count, not part of the time
+        assert_eq!(
+            parse_response(b"1000007\r\n"),
+            Ok(Response::Timing(MeasurementTiming { address: addr('1'), time_seconds: 0, values_count: 7 }))
+        );
+    }
+
+    #[test]
+    fn test_parse_response_identification_borrowed() {
+        let line = b"113NRSYSINC100000001.2101\r\n";
+        let Ok(Response::Identification(info)) = parse_response(line) else {
+            panic!("expected identification");
+        };
+        assert_eq!(info.address, addr('1'));
+        assert_eq!(info.sdi12_version, "13");
+        assert_eq!(info.vendor, "NRSYSINC");
+        assert_eq!(info.model, "100000");
+        assert_eq!(info.sensor_version, "001");
+        assert_eq!(info.optional, ".2101");
+
+        // Fixed fields only, no optional part
+        let Ok(Response::Identification(info)) = parse_response(b"014VENDOR  MODEL 1.0\r\n") else {
+            panic!("expected identification");
+        };
+        assert_eq!(info.vendor, "VENDOR  ");
+        assert_eq!(info.sensor_version, "1.0");
+        assert_eq!(info.optional, "");
+
+        // 13 optional characters is the maximum; a 14th is an error, not truncated
+        let Ok(Response::Identification(info)) = parse_response_expecting(b"014VENDOR  MODEL 1.0SN-1234567890\r\n", false) else {
+            panic!("expected identification");
+        };
+        assert_eq!(info.optional, "SN-1234567890");
+        assert_eq!(
+            parse_response_expecting(b"014VENDOR  MODEL 1.0SN-12345678901\r\n", false),
+            Err(ResponseParseError::InvalidIdentificationLength)
+        );
+    }
+
+    #[test]
+    fn test_identification_truncated_at_every_length() {
+        let full = b"14VENDOR  MODEL 1.0SN-1234567890";
+        for len in 0..IDENTIFICATION_FIXED_LEN {
+            let truncated = &full[..len];
+            assert_eq!(
+                parse_identification(addr('0'), truncated),
+                Err(ResponseParseError::InvalidIdentificationLength),
+                "length {len}"
+            );
+
+            // The public parser must not panic on these either, whatever it makes of them
+            let mut line = [0u8; IDENTIFICATION_FIXED_LEN + 3];
+            line[0] = b'0';
+            line[1..=len].copy_from_slice(truncated);
+            line[len + 1..len + 3].copy_from_slice(b"\r\n");
+            let _ = parse_response_expecting(&line[..len + 3], false);
+        }
+        assert!(parse_identification(addr('0'), &full[..IDENTIFICATION_FIXED_LEN]).is_ok());
+    }
+
+    #[test]
+    fn test_parse_response_expecting_crc_context() {
+        // Optional field ends in "4KEY": a digit then three letters, which the
+        // structural check takes for a CRC
+        let line = b"013VENDORXYMODEL1100SN4KEY\r\n";
+        assert!(matches!(parse_response(line), Err(ResponseParseError::CrcMismatch { .. })));
+        let Ok(Response::Identification(info)) = parse_response_expecting(line, false) else {
+            panic!("expected identification");
+        };
+        assert_eq!(info.optional, "SN4KEY");
+
+        // A requested CRC must be present and valid
+        assert_eq!(parse_response_expecting(b"0\r\n", true), Err(ResponseParseError::TooShort));
+        assert!(matches!(parse_response_expecting(b"0+3.14OqX\r\n", true), Err(ResponseParseError::CrcMismatch { .. })));
+        let timing = b"00101";
+        let mut line = [0u8; 10];
+        line[..5].copy_from_slice(timing);
+        line[5..8].copy_from_slice(&crate::common::crc::encode_crc_ascii(calculate_crc16(timing)));
+        line[8..].copy_from_slice(b"\r\n");
+        assert_eq!(
+            parse_response_expecting(&line, true),
+            Ok(Response::Timing(MeasurementTiming { address: addr('0'), time_seconds: 10, values_count: 1 }))
+        );
+    }
+
+    #[test]
+    fn test_parse_response_for_command() {
+        use crate::sensor::parse_command;
+        let cmd = |bytes: &[u8]| parse_command(bytes).unwrap();
+
+        assert_eq!(
+            parse_response_for(&cmd(b"0M!"), b"00012\r\n"),
+            Ok(Response::Timing(MeasurementTiming { address: addr('0'), time_seconds: 1, values_count: 2 }))
+        );
+        // `atttn` to aM! vs `atttnn` to aC!
+        assert_eq!(parse_response_for(&cmd(b"0M!"), b"001234\r\n"), Err(ResponseParseError::InvalidFormat));
+        assert_eq!(
+            parse_response_for(&cmd(b"0C!"), b"001234\r\n").map(|r| matches!(r, Response::Timing(t) if t.values_count == 34)),
+            Ok(true)
+        );
+        assert_eq!(parse_response_for(&cmd(b"0!"), b"0\r\n"), Ok(Response::Acknowledge { address: addr('0') }));
+        assert_eq!(parse_response_for(&cmd(b"0!"), b"00012\r\n"), Err(ResponseParseError::InvalidFormat));
+        assert_eq!(parse_response_for(&cmd(b"0I!"), b"00012\r\n"), Err(ResponseParseError::InvalidIdentificationLength));
+
+        // The heuristic reads the optional field's tail as a CRC; the command says otherwise
+        let line = b"013VENDORXYMODEL1100SN4KEY\r\n";
+        assert!(parse_response(line).is_err());
+        let Ok(Response::Identification(info)) = parse_response_for(&cmd(b"0I!"), line) else {
+            panic!("expected identification");
+        };
+        assert_eq!(info.optional, "SN4KEY");
+
+        // aRCn! requires a CRC
+        assert_eq!(parse_response_for(&cmd(b"0RC0!"), b"0+3\r\n"), Err(ResponseParseError::TooShort));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    #[allow(clippy::approx_constant)] // Spec CRC example uses +3.14
+    fn test_parse_response_for_data_and_metadata() {
+        use crate::sensor::parse_command;
+        let cmd = |bytes: &[u8]| parse_command(bytes).unwrap();
+
+        let Ok(Response::Data(data)) = parse_response_for(&cmd(b"0D0!"), b"0+3.14OqZ\r\n") else { panic!("expected data") };
+        assert_eq!(data.crc, Some(0xFC5A));
+        assert_eq!(data.values[0].as_f32(), 3.14);
+        let Ok(Response::Data(data)) = parse_response_for(&cmd(b"0R0!"), b"0+1.5-2\r\n") else { panic!("expected data") };
+        assert_eq!((data.values.len(), data.crc), (2, None));
+        // An empty register is data, not an acknowledge
+        assert!(matches!(parse_response_for(&cmd(b"0D1!"), b"0\r\n"), Ok(Response::Data(d)) if d.values.is_empty()));
+
+        let Ok(Response::Metadata(meta)) = parse_response_for(&cmd(b"0IM_001!"), b"0,TA,C;\r\n") else { panic!("expected metadata") };
+        assert_eq!(meta.crc, None);
+        assert_eq!(parse_response_for(&cmd(b"0IM_001!"), b"0+1.5\r\n"), Err(ResponseParseError::InvalidFormat));
+    }
+
+    #[test]
+    fn test_timing_digit_counts() {
+        // Exactly three time digits plus one to three count digits
+        assert_eq!(parse_response(b"0123\r\n"), Err(ResponseParseError::InvalidFormat));
+        assert_eq!(
+            parse_response(b"01234\r\n"),
+            Ok(Response::Timing(MeasurementTiming { address: addr('0'), time_seconds: 123, values_count: 4 }))
+        );
+        assert_eq!(parse_response(b"01234567\r\n"), Err(ResponseParseError::InvalidFormat));
+
+        // With the command known, a `tttt` + `n` reply to aM! is caught
+        let m = Command::StartMeasurement { address: addr('0'), index: crate::common::command::MeasurementIndex::Base };
+        let c = Command::StartConcurrentMeasurement { address: addr('0'), index: crate::common::command::MeasurementIndex::Base };
+        let ha = Command::StartHighVolumeASCII { address: addr('0') };
+        assert_eq!(
+            MeasurementTiming::from_payload_for(&m, b"1234"),
+            Ok(MeasurementTiming { address: addr('0'), time_seconds: 123, values_count: 4 })
+        );
+        assert_eq!(MeasurementTiming::from_payload_for(&m, b"12345"), Err(ResponseParseError::InvalidFormat));
+        assert_eq!(MeasurementTiming::from_payload_for(&c, b"1234"), Err(ResponseParseError::InvalidFormat));
+        assert_eq!(MeasurementTiming::from_payload_for(&c, b"12345").map(|t| t.values_count), Ok(45));
+        assert_eq!(MeasurementTiming::from_payload_for(&ha, b"001999").map(|t| t.values_count), Ok(999));
+        assert_eq!(
+            MeasurementTiming::from_payload_for(&Command::AcknowledgeActive { address: addr('0') }, b"1234"),
+            Err(ResponseParseError::InvalidFormat)
+        );
+    }
+
+    #[test]
+    fn test_measurement_timing_from_payload() {
+        assert_eq!(
+            MeasurementTiming::from_payload(addr('3'), b"01012"),
+            Ok(MeasurementTiming { address: addr('3'), time_seconds: 10, values_count: 12 })
+        );
+        assert_eq!(MeasurementTiming::from_payload(addr('3'), b"010"), Err(ResponseParseError::InvalidFormat));
+        assert_eq!(MeasurementTiming::from_payload(addr('3'), b"0101234"), Err(ResponseParseError::InvalidFormat));
+        assert_eq!(MeasurementTiming::from_payload(addr('3'), b"01a1"), Err(ResponseParseError::InvalidFormat));
+    }
+
+    #[test]
+    fn test_parse_response_extended_opt_in() {
+        let line = b"0XOK=12\r\n";
+        assert_eq!(parse_response(line), Err(ResponseParseError::InvalidFormat));
+
+        let options = ParseOptions { accept_extended_responses: true, ..ParseOptions::default() };
+        assert_eq!(parse_response_with_options(line, options), Ok(Response::Extended { address: addr('0'), body: "XOK=12" }));
+        // Standard shapes still win, and framing errors are not masked
+        assert!(matches!(parse_response_with_options(b"00012\r\n", options), Ok(Response::Timing(_))));
+        assert_eq!(parse_response_with_options(b"0XOK", options), Err(ResponseParseError::MissingCrLf));
+        assert_eq!(parse_response_with_options(b"0X\x01\r\n", options), Err(ResponseParseError::InvalidFormat));
+    }
+
+    #[test]
+    fn test_parse_response_bare_lf() {
+        assert_eq!(parse_response(b"0\n"), Err(ResponseParseError::MissingCrLf));
+        let options = ParseOptions { accept_bare_lf: true, ..ParseOptions::default() };
+        assert_eq!(parse_response_with_options(b"0\n", options), Ok(Response::Acknowledge { address: addr('0') }));
+        assert_eq!(parse_response_with_options(b"0\r\n", options), Ok(Response::Acknowledge { address: addr('0') }));
+        assert!(matches!(parse_response_with_options(b"00012\n", options), Ok(Response::Timing(_))));
+    }
+
+    #[test]
+    fn test_parse_response_framing_errors() {
+        assert_eq!(parse_response(b""), Err(ResponseParseError::EmptyInput));
+        assert_eq!(parse_response(b"0+1.0"), Err(ResponseParseError::MissingCrLf));
+        assert_eq!(parse_response(b"\r\n"), Err(ResponseParseError::TooShort));
+        assert_eq!(parse_response(b"?\r\n"), Err(ResponseParseError::InvalidAddressChar));
+        assert!(matches!(parse_response(b"0+3.14OqX\r\n"), Err(ResponseParseError::CrcMismatch { .. })));
+        assert_eq!(parse_response(b"0ab\r\n"), Err(ResponseParseError::InvalidFormat));
+    }
+
+    #[test]
+    fn test_parse_error_into_sdi12_error() {
+        use crate::common::error::Sdi12Error;
+        let crc_err = parse_response(b"0+3.14OqX\r\n").unwrap_err();
+        assert!(matches!(Sdi12Error::<()>::from(crc_err), Sdi12Error::CrcMismatch { calculated, .. } if calculated == calculate_crc16(b"0+3.14")));
+        assert_eq!(Sdi12Error::from(ResponseParseError::MissingCrLf), Sdi12Error::<()>::ParseError(ResponseParseError::MissingCrLf));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_parse_binary_packet_spec_examples() {
+        use alloc::string::ToString;
+        // Spec 5.2.2: two i16 values, -1 and 1
+        let db0 = [0x31, 0x04, 0x00, 0x03, 0xFF, 0xFF, 0x01, 0x00, 0xC2, 0xAC];
+        let info = parse_binary_packet(&db0).unwrap();
+        assert_eq!(info.address, addr('1'));
+        assert_eq!(info.data_type, BinaryDataType::SignedI16);
+        assert_eq!(info.crc, 0xACC2);
+        assert_eq!(info.value_count(), 2);
+        assert_eq!(info.decode_values(), [Sdi12Value::new(-1.0), Sdi12Value::new(1.0)]);
+        assert_eq!(info.as_i16_slice(), Ok(alloc::vec![-1, 1]));
+        assert_eq!(info.payload_hex().to_string(), "ff ff 01 00");
+        assert_eq!(info.display_values().to_string(), "-1, 1");
+
+        // Two f32 values, 3.14 (approximately) and 1.0
+        let db1 = [0x31, 0x08, 0x00, 0x09, 0xC3, 0xF5, 0x48, 0x40, 0x00, 0x00, 0x80, 0x3F, 0x3B, 0x6E];
+        let info = parse_binary_packet(&db1).unwrap();
+        assert_eq!(info.data_type, BinaryDataType::Float32);
+        assert_eq!(info.decode_values()[1], Sdi12Value::new(1.0));
+        assert_eq!(info.decode_values()[0], Sdi12Value::new(f32::from_le_bytes([0xC3, 0xF5, 0x48, 0x40])));
+        assert_eq!(info.as_f32_slice(), Ok(alloc::vec![f32::from_le_bytes([0xC3, 0xF5, 0x48, 0x40]), 1.0]));
+        assert_eq!(
+            info.as_i16_slice(),
+            Err(ResponseParseError::BinaryTypeMismatch { requested: BinaryDataType::SignedI16, actual: BinaryDataType::Float32 })
+        );
+        assert_eq!(info.display_values().to_string(), "3.14, 1");
+
+        // Empty packet: no values behind this DBn index
+        let db2 = [0x31, 0x00, 0x00, 0x00, 0x0E, 0xFC];
+        let info = parse_binary_packet(&db2).unwrap();
+        assert_eq!(info.data_type, BinaryDataType::InvalidRequest);
+        assert_eq!(info.value_count(), 0);
+        assert!(info.decode_values().is_empty());
+        assert_eq!(info.display_values().to_string(), "");
+
+        // Same for one built by hand (or deserialized) with stray payload bytes
+        let info = BinaryDataInfo { payload: alloc::vec![1, 2, 3], ..info };
+        assert_eq!(info.value_count(), 0);
+        assert!(info.decode_values().is_empty());
+        assert_eq!(info.display_values().to_string(), "");
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_parse_binary_packet_errors_and_wide_values() {
+        use alloc::string::ToString;
+        let packet = |size: u16, data_type: u8, payload: &[u8]| {
+            let mut p = alloc::vec![b'0'];
+            p.extend_from_slice(&size.to_le_bytes());
+            p.push(data_type);
+            p.extend_from_slice(payload);
+            let crc = calculate_crc16(&p);
+            p.extend_from_slice(&crate::common::crc::encode_crc_binary(crc));
+            p
+        };
+
+        // u64 beyond f32 precision still displays exactly
+        let info = parse_binary_packet(&packet(8, 8, &u64::MAX.to_le_bytes())).unwrap();
+        assert_eq!(info.display_values().to_string(), "18446744073709551615");
+        assert_eq!(info.as_u64_slice(), Ok(alloc::vec![u64::MAX])); // No f32 promotion
+
+        assert_eq!(parse_binary_packet(&[]), Err(ResponseParseError::EmptyInput));
+        assert_eq!(parse_binary_packet(&[0x31, 0x00, 0x00]), Err(ResponseParseError::TooShort));
+        // Address byte outside ASCII ('ÿ' as a char)
+        let mut bad_address = packet(0, 0, &[]);
+        bad_address[0] = 0xFF;
+        assert_eq!(parse_binary_packet(&bad_address), Err(ResponseParseError::InvalidAddressChar));
+        // Declared size disagrees with the buffer
+        assert_eq!(parse_binary_packet(&packet(4, 3, &[1, 0])), Err(ResponseParseError::InconsistentBinaryPacketSize));
+        // Not a whole number of i32 values
+        assert_eq!(parse_binary_packet(&packet(3, 5, &[1, 2, 3])), Err(ResponseParseError::InconsistentBinaryPacketSize));
+        assert_eq!(parse_binary_packet(&packet(1, 11, &[0])), Err(ResponseParseError::InvalidFormat));
+        let mut corrupted = packet(2, 2, &[7, 8]);
+        corrupted[4] ^= 0xFF;
+        assert!(matches!(parse_binary_packet(&corrupted), Err(ResponseParseError::CrcMismatch { .. })));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    #[allow(clippy::approx_constant)] // Spec CRC example uses +3.14
+    fn test_parse_response_data_and_metadata() {
+        assert_eq!(
+            parse_response(b"0+3.14OqZ\r\n"),
+            Ok(Response::Data(DataInfo {
+                address: addr('0'),
+                values: alloc::vec![Sdi12Value::new(3.14)],
+                crc: try_decode_crc_ascii(b"OqZ"),
+                crc_valid: Some(true),
+            }))
+        );
+        let Ok(Response::Data(data)) = parse_response(b"1+1.5-2+30.25\r\n") else { panic!("expected data") };
+        assert_eq!(data.values, alloc::vec![Sdi12Value::new(1.5), Sdi12Value::new(-2.0), Sdi12Value::new(30.25)]);
+        assert_eq!(data.crc, None);
+        let Ok(Response::Data(data)) = parse_response(b"0+0+0+0\r\n") else { panic!("expected data") };
+        assert_eq!(data.values, alloc::vec![Sdi12Value::new(0.0); 3]);
+        let Ok(Response::Data(data)) = parse_response(b"0+0000000-0+0.0\r\n") else { panic!("expected data") };
+        assert_eq!(data.values.len(), 3);
+        // Bare signs are empty fields, not zeros
+        assert_eq!(parse_response(b"0+++\r\n"), Err(ResponseParseError::InvalidFormat));
+
+        let Ok(Response::Metadata(meta)) = parse_response(b"0,RP,mm/h,rainfall rate;\r\n") else { panic!("expected metadata") };
+        assert_eq!(meta.fields, ["RP", "mm/h", "rainfall rate"]);
+        assert_eq!(meta.parameter(), Some("RP"));
+        assert_eq!(meta.units(), Some("mm/h"));
+        assert_eq!(meta.description(), Some("rainfall rate"));
+        assert!(meta.extra_fields().is_empty());
+
+        // Only the two required fields
+        let Ok(Response::Metadata(meta)) = parse_response(b"0,TA, ;\r\n") else { panic!("expected metadata") };
+        assert_eq!(meta.units(), Some(" "));
+        assert_eq!(meta.description(), None);
+        let Ok(Response::Metadata(meta)) = parse_response(b"0,TA,C,air temp,SN123;\r\n") else { panic!("expected metadata") };
+        assert_eq!(meta.extra_fields(), ["SN123"]);
+
+        let info = IdentificationInfo::from(IdentificationInfoRef {
+            address: addr('0'), sdi12_version: "14", vendor: "VENDOR  ", model: "MODEL ", sensor_version: "1.0", optional: "",
+        });
+        assert_eq!(info.vendor, "VENDOR  ");
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    #[test]
+    fn test_parse_response_data_needs_alloc() {
+        assert_eq!(parse_response(b"0+1.0\r\n"), Err(ResponseParseError::FeatureNotEnabled));
+    }
+
+    #[cfg(feature = "use_heapless")]
+    #[test]
+    fn test_parse_response_heapless() {
+        let data = parse_response_heapless::<4>(b"0+1.5-2+30.25\r\n").unwrap();
+        assert_eq!(data.address, addr('0'));
+        assert_eq!(data.values.as_slice(), [Sdi12Value::new(1.5), Sdi12Value::new(-2.0), Sdi12Value::new(30.25)]);
+        assert_eq!(data.crc, None);
+
+        let data = parse_response_heapless::<1>(b"0+3.14OqZ\r\n").unwrap();
+        assert_eq!(data.values.len(), 1);
+        assert!(data.crc.is_some());
+
+        assert!(parse_response_heapless::<4>(b"0\r\n").unwrap().values.is_empty());
+        assert_eq!(parse_response_heapless::<2>(b"0+1+2+3\r\n"), Err(ResponseParseError::BufferOverflow));
+        assert_eq!(parse_response_heapless::<4>(b"00015\r\n"), Err(ResponseParseError::InvalidFormat));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_response_serde_roundtrip() {
+        let line = b"113NRSYSINC100000001.2101\r\n";
+        let responses = [
+            parse_response(b"0\r\n").unwrap(),
+            parse_response(b"a12312\r\n").unwrap(),
+            parse_response(line).unwrap(),
+        ];
+        for response in responses {
+            let json = serde_json::to_string(&response).unwrap();
+            assert_eq!(serde_json::from_str::<Response>(&json).unwrap(), response);
+        }
+
+        #[cfg(feature = "alloc")]
+        {
+            let data = parse_response(b"0+3.14OqZ\r\n").unwrap();
+            let json = serde_json::to_string(&data).unwrap();
+            assert_eq!(serde_json::from_str::<Response>(&json).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_split_values() {
+        let mut values = split_values("+1.23-4.5+6");
+        assert_eq!(values.next(), Some("+1.23"));
+        assert_eq!(values.next(), Some("-4.5"));
+        assert_eq!(values.next(), Some("+6"));
+        assert_eq!(values.next(), None);
+        assert_eq!(split_values("").count(), 0);
+        // Zeros are values like any other, never merged
+        assert!(split_values("+0+0-0").eq(["+0", "+0", "-0"]));
+        // A leading multi-byte character (garbage) is kept whole
+        assert!(split_values("\u{210}02-1").eq(["\u{210}02", "-1"]));
+    }
+
+    #[test]
+    fn test_response_lines_iteration() {
+        let mut lines = ResponseLines::new(b"0+1.0\r\n0+2.0\r\n0\r\n");
+        assert_eq!(lines.next(), Some(&b"0+1.0\r\n"[..]));
+        assert_eq!(lines.next(), Some(&b"0+2.0\r\n"[..]));
+        assert_eq!(lines.next(), Some(&b"0\r\n"[..]));
+        assert_eq!(lines.next(), None);
+        assert_eq!(ResponseLines::new(b"").count(), 0);
+
+        let mut lines = ResponseLines::new(b"0+1.0\n0\r\n");
+        assert_eq!(lines.next(), Some(&b"0+1.0\n"[..]));
+        assert_eq!(lines.next(), Some(&b"0\r\n"[..]));
     }
 }
\ No newline at end of file
@@ -65,5 +65,20 @@ pub const MULTILINE_INTER_LINE_DELAY_MAX: Duration = Duration::from_millis(150);
 
 /// Nominal duration of a single bit at 1200 baud.
 pub const BIT_DURATION: Duration = Duration::from_nanos(833_333); // Approx 0.833 ms
-/// Nominal duration of a single byte (10 bits total) at 1200 baud (7E1 format).
-pub const BYTE_DURATION: Duration = Duration::from_micros(8333); // Approx 8.33 ms
\ No newline at end of file
+/// Nominal duration of a single byte at 1200 baud (7E1 format).
+///
+/// 1 start + 7 data + 1 parity + 1 stop = 10 bits, and 10 / 1200 s = 8.33 ms.
+/// Rounded down to whole microseconds; the 1/3 µs per byte lost is far below
+/// the spec's tolerances. Use `transmission_time` for multiples.
+pub const BYTE_DURATION: Duration = Duration::from_micros(8333); // Approx 8.33 ms
+
+/// Nominal time to send `bytes` bytes at 1200 baud: `BYTE_DURATION * bytes`.
+/// For other rates see `BaudRate::transmission_time`.
+///
+/// Never overflows: the count is capped at 65535 bytes (about 9 minutes),
+/// far beyond any SDI-12 line, so timeouts built from the result can be added
+/// to without panicking.
+pub fn transmission_time(bytes: usize) -> Duration {
+    let bytes = u16::try_from(bytes).unwrap_or(u16::MAX);
+    BYTE_DURATION * u32::from(bytes)
+}
@@ -1,6 +1,7 @@
 // src/common/types.rs
 
-use core::fmt;
+use arrayvec::ArrayString;
+use core::fmt::{self, Write};
 use core::str::FromStr; // For parsing strings to numbers
 
 // --- SDI-12 Standard Data Value (`<values>`) ---
@@ -12,9 +13,15 @@ use core::str::FromStr; // For parsing strings to numbers
 /// Using f32 might be simplest for representation, but parsing needs care.
 /// Alternatively, parse into integer + scale factor. Let's try f32 for now.
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
 pub struct Sdi12Value(f32); // Store as f32 for simplicity
 
 impl Sdi12Value {
+    /// Explicit "no data" value (NaN). SDI-12 itself has no missing-value
+    /// encoding, so this never comes out of `parse_single` and can't be
+    /// formatted; it's for marking gaps in collected data.
+    pub const MISSING: Sdi12Value = Sdi12Value(f32::NAN);
+
     /// Creates a new Sdi12Value.
     pub fn new(value: f32) -> Self {
         // TODO: Potentially add checks/clamping based on SDI-12 format limits?
@@ -22,13 +29,104 @@ impl Sdi12Value {
         Self(value)
     }
 
+    /// Creates a value from an integer reading.
+    ///
+    /// Every integer SDI-12 can carry (at most 7 digits, `±9999999`) is exact
+    /// in the internal `f32`, so it formats back as the same digits (`+42`,
+    /// never `+42.0`) and `as_i64` returns it unchanged. Larger `i32`s round
+    /// to the nearest `f32`, but have too many digits to format anyway.
+    pub fn from_i32(value: i32) -> Self {
+        Self(value as f32)
+    }
+
+    /// Creates a value from an unsigned integer reading. See `from_i32`.
+    pub fn from_u32(value: u32) -> Self {
+        Self(value as f32)
+    }
+
+    /// Creates a value from an integer reading; always exact. See `from_i32`.
+    pub fn from_i16(value: i16) -> Self {
+        Self(f32::from(value))
+    }
+
+    /// Creates a value from an unsigned integer reading; always exact. See `from_i32`.
+    pub fn from_u16(value: u16) -> Self {
+        Self(f32::from(value))
+    }
+
+    /// Creates a value from an integer reading; always exact. See `from_i32`.
+    pub fn from_i8(value: i8) -> Self {
+        Self(f32::from(value))
+    }
+
+    /// Creates a value from an unsigned integer reading; always exact. See `from_i32`.
+    pub fn from_u8(value: u8) -> Self {
+        Self(f32::from(value))
+    }
+
     /// Returns the value as f32.
     pub fn as_f32(&self) -> f32 {
         self.0
     }
 
+    /// Returns true for `MISSING` (any NaN) or a value equal to `sentinel`.
+    ///
+    /// Missing-value conventions are sensor-specific (`+9999999`, `-9999`, ...),
+    /// so the sentinel comes from the sensor's manual.
+    pub fn is_missing(&self, sentinel: f32) -> bool {
+        self.0.is_nan() || self.0 == sentinel
+    }
+
+    /// Returns the value as an integer if it is integral and fits in `i64`.
+    ///
+    /// Non-integral values (`+1.5`), NaN/infinities and out-of-range values
+    /// return `None`. Every SDI-12 integer (at most 7 digits) is exact in the
+    /// internal `f32`, so counts parsed from a response come back unchanged.
+    pub fn as_i64(&self) -> Option<i64> {
+        // `i64::MIN as f32` is exactly -2^63; `i64::MAX as f32` rounds up to 2^63.
+        if !(self.0 >= i64::MIN as f32 && self.0 < i64::MAX as f32) {
+            return None;
+        }
+        let int = self.0 as i64;
+        (int as f32 == self.0).then_some(int)
+    }
+
+    /// Returns the value as an unsigned integer if it is integral, non-negative
+    /// and fits in `u64`. See `as_i64`.
+    pub fn as_u64(&self) -> Option<u64> {
+        // `-0.0` passes and maps to 0; `u64::MAX as f32` rounds up to 2^64.
+        if !(self.0 >= 0.0 && self.0 < u64::MAX as f32) {
+            return None;
+        }
+        let int = self.0 as u64;
+        (int as f32 == self.0).then_some(int)
+    }
+
+    /// Total ordering of values, as `f32::total_cmp`.
+    ///
+    /// The derived `PartialOrd` has no answer for NaN (`MISSING`), so sorting
+    /// with it panics or scrambles; `values.sort_by(Sdi12Value::total_cmp)`
+    /// doesn't. `-0` sorts before `+0` and positive NaN after every number.
+    pub fn total_cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+
+    /// Returns true if the two values differ by at most `epsilon`.
+    ///
+    /// `==` compares the parsed `f32`s exactly, which is what a round trip
+    /// through the wire format needs, but values computed elsewhere (`+0.1`
+    /// against `0.1 + 1e-9`) rarely match bit for bit. NaN is never
+    /// approximately equal to anything, itself included.
+    pub fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        (self.0 - other.0).abs() <= epsilon
+    }
+
     /// Parses a single value string (like "+1.23", "-10", "+1234567") into an Sdi12Value.
     /// Does not handle multiple values in one string.
+    ///
+    /// Zero may be written any way the format allows (`+0`, `+0.0`, `+0000000`);
+    /// `-0` gives negative zero, which compares equal to zero. A lone sign has no
+    /// digits and is `InvalidDigitCount`, never zero.
     pub fn parse_single(s: &str) -> Result<Self, Sdi12ParsingError> {
         // Validate basic structure and length (max 9 chars: sign + 7 digits + opt decimal)
         if s.is_empty() || s.len() > 9 {
@@ -66,8 +164,43 @@ impl Sdi12Value {
         Ok(Self(sign * num_part))
     }
 
-    // TODO: Implement formatting logic later if needed (e.g., for sensor implementation)
-    // pub fn format(&self, buffer: &mut [u8]) -> Result<usize, Sdi12FormattingError> { ... }
+    /// Formats the value in SDI-12 `<values>` form (e.g. `"+1.23"`, `"-10"`).
+    ///
+    /// Uses as many decimals as fit in the 7 digit limit, then drops trailing zeros.
+    /// Values needing more than 7 integer digits are `InvalidDigitCount`;
+    /// NaN and infinities are `InvalidFormat`.
+    pub fn format_sdi12(&self) -> Result<ArrayString<9>, Sdi12ParsingError> {
+        if !self.0.is_finite() {
+            return Err(Sdi12ParsingError::InvalidFormat);
+        }
+        let magnitude = self.0.abs();
+        let mut int_digits = 1;
+        let mut limit = 10.0f32;
+        while magnitude >= limit && int_digits <= 7 {
+            int_digits += 1;
+            limit *= 10.0;
+        }
+        if int_digits > 7 {
+            return Err(Sdi12ParsingError::InvalidDigitCount);
+        }
+
+        let mut digits = ArrayString::<16>::new();
+        write!(digits, "{:.*}", 7 - int_digits, magnitude).map_err(|_| Sdi12ParsingError::InvalidFormat)?;
+        let mut digits = digits.as_str();
+        if digits.contains('.') {
+            digits = digits.trim_end_matches('0').trim_end_matches('.');
+        }
+        // Rounding can carry into an extra digit (e.g. 9999999.6)
+        if digits.bytes().filter(u8::is_ascii_digit).count() > 7 {
+            return Err(Sdi12ParsingError::InvalidDigitCount);
+        }
+
+        let negative = self.0 < 0.0 && digits.bytes().any(|b| b.is_ascii_digit() && b != b'0');
+        let mut out = ArrayString::new();
+        out.push(if negative { '-' } else { '+' });
+        out.try_push_str(digits).map_err(|_| Sdi12ParsingError::InvalidFormat)?;
+        Ok(out)
+    }
 }
 
 /// Error during parsing of SDI-12 <values>.
@@ -100,6 +233,7 @@ impl fmt::Display for Sdi12ParsingError {
 
 /// Data types used in High-Volume Binary command responses.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum BinaryDataType {
     InvalidRequest = 0, // Indicates an invalid DBn request index
@@ -172,6 +306,31 @@ mod tests {
         assert_eq!(Sdi12Value::parse_single("+0").unwrap(), Sdi12Value(0.0));
     }
 
+    #[test]
+    fn test_sdi12value_parsing_zero_forms() {
+        for zero in ["+0", "-0", "+0.0", "-0.0", "+0000000", "+000000.0", "+.0"] {
+            let value = Sdi12Value::parse_single(zero).unwrap();
+            assert_eq!(value, Sdi12Value(0.0), "{zero}");
+        }
+        assert!(Sdi12Value::parse_single("-0").unwrap().0.is_sign_negative());
+        assert!(Sdi12Value::parse_single("+0").unwrap().0.is_sign_positive());
+        assert_eq!(Sdi12Value::parse_single("+00000000"), Err(Sdi12ParsingError::InvalidDigitCount));
+    }
+
+    #[test]
+    fn test_sdi12value_total_cmp_and_approx_eq() {
+        use core::cmp::Ordering;
+        let mut values = [Sdi12Value(2.5), Sdi12Value::MISSING, Sdi12Value(-1.0), Sdi12Value(0.0), Sdi12Value(-0.0)];
+        values.sort_by(Sdi12Value::total_cmp);
+        assert_eq!(values[..4], [Sdi12Value(-1.0), Sdi12Value(-0.0), Sdi12Value(0.0), Sdi12Value(2.5)]);
+        assert!(values[4].as_f32().is_nan());
+        assert_eq!(Sdi12Value::MISSING.total_cmp(&Sdi12Value::MISSING), Ordering::Equal);
+
+        assert!(Sdi12Value(0.1).approx_eq(&Sdi12Value(0.1 + 1e-6), 1e-5));
+        assert!(!Sdi12Value(0.1).approx_eq(&Sdi12Value(0.2), 1e-5));
+        assert!(!Sdi12Value::MISSING.approx_eq(&Sdi12Value::MISSING, f32::INFINITY));
+    }
+
     #[test]
     fn test_sdi12value_parsing_invalid() {
         assert_eq!(Sdi12Value::parse_single(""), Err(Sdi12ParsingError::InvalidFormat));
@@ -206,6 +365,75 @@ mod tests {
         assert_eq!(Sdi12Value::parse_single("+123456789"), Err(Sdi12ParsingError::InvalidFormat)); // Too long (len 10)
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_sdi12value_serde_roundtrip() {
+        for v in [1.23f32, -0.456, 1234567.0, 0.1] {
+            let json = serde_json::to_string(&Sdi12Value(v)).unwrap();
+            assert_eq!(serde_json::from_str::<Sdi12Value>(&json).unwrap(), Sdi12Value(v));
+        }
+        assert_eq!(serde_json::to_string(&Sdi12Value(-5.0)).unwrap(), "-5.0");
+    }
+
+    #[test]
+    fn test_sdi12value_format() {
+        let fmt = |v: f32| Sdi12Value(v).format_sdi12();
+        assert_eq!(fmt(1.23).unwrap().as_str(), "+1.23");
+        assert_eq!(fmt(-10.0).unwrap().as_str(), "-10");
+        assert_eq!(fmt(0.0).unwrap().as_str(), "+0");
+        assert_eq!(fmt(-0.0).unwrap().as_str(), "+0");
+        assert_eq!(fmt(1234567.0).unwrap().as_str(), "+1234567");
+        assert_eq!(fmt(-0.456).unwrap().as_str(), "-0.456");
+        assert_eq!(fmt(0.1234567).unwrap().as_str(), "+0.123457"); // Rounded to 7 digits
+        assert_eq!(fmt(12345678.0), Err(Sdi12ParsingError::InvalidDigitCount));
+        assert_eq!(fmt(f32::NAN), Err(Sdi12ParsingError::InvalidFormat));
+
+        // Output always parses back
+        for v in [3.5f32, -22.125, 998.0, 0.001] {
+            let s = fmt(v).unwrap();
+            assert_eq!(Sdi12Value::parse_single(&s).unwrap(), Sdi12Value(v));
+        }
+    }
+
+    #[test]
+    fn test_sdi12value_missing() {
+        assert!(Sdi12Value::MISSING.is_missing(-9999.0));
+        assert!(Sdi12Value::parse_single("+9999999").unwrap().is_missing(9_999_999.0));
+        assert!(!Sdi12Value::parse_single("+9999998").unwrap().is_missing(9_999_999.0));
+        assert_eq!(Sdi12Value::MISSING.format_sdi12(), Err(Sdi12ParsingError::InvalidFormat));
+    }
+
+    #[test]
+    fn test_sdi12value_integer_accessors() {
+        let parse = |s: &str| Sdi12Value::parse_single(s).unwrap();
+        assert_eq!(parse("+9999999").as_i64(), Some(9_999_999));
+        assert_eq!(parse("-1234567").as_i64(), Some(-1_234_567));
+        assert_eq!(parse("+42.000").as_u64(), Some(42));
+        assert_eq!(parse("-0").as_u64(), Some(0));
+        assert_eq!(parse("+1.5").as_i64(), None);
+        assert_eq!(parse("-3").as_u64(), None);
+
+        assert_eq!(Sdi12Value(f32::NAN).as_i64(), None);
+        assert_eq!(Sdi12Value(f32::INFINITY).as_u64(), None);
+        assert_eq!(Sdi12Value(1e19).as_i64(), None);
+        assert_eq!(Sdi12Value(1e19).as_u64(), Some(1e19f32 as u64));
+        assert_eq!(Sdi12Value(1e20).as_u64(), None);
+        assert_eq!(Sdi12Value(i64::MIN as f32).as_i64(), Some(i64::MIN));
+    }
+
+    #[test]
+    fn test_sdi12value_from_integers() {
+        assert_eq!(Sdi12Value::from_i32(-5).format_sdi12().unwrap().as_str(), "-5");
+        assert_eq!(Sdi12Value::from_u16(42).format_sdi12().unwrap().as_str(), "+42");
+        assert_eq!(Sdi12Value::from_i32(9_999_999).format_sdi12().unwrap().as_str(), "+9999999");
+        assert_eq!(Sdi12Value::from_i32(-9_999_999).as_i64(), Some(-9_999_999));
+        assert_eq!(Sdi12Value::from_u32(1_234_567).as_u64(), Some(1_234_567));
+        assert_eq!(Sdi12Value::from_i8(i8::MIN).as_i64(), Some(-128));
+        assert_eq!(Sdi12Value::from_u8(0).format_sdi12().unwrap().as_str(), "+0");
+        assert_eq!(Sdi12Value::from_i16(i16::MIN), Sdi12Value::parse_single("-32768").unwrap());
+        assert_eq!(Sdi12Value::from_i32(10_000_000).format_sdi12(), Err(Sdi12ParsingError::InvalidDigitCount));
+    }
+
     #[test]
     fn test_binary_data_type_from_u8() {
         assert_eq!(BinaryDataType::from_u8(0), Some(BinaryDataType::InvalidRequest));
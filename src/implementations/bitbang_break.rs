@@ -0,0 +1,254 @@
+// src/implementations/bitbang_break.rs
+
+use crate::common::{
+    frame::{BaudRate, FrameFormat},
+    hal_traits::{Sdi12Serial, Sdi12Timer},
+    timing,
+};
+use core::fmt::Debug;
+use core::time::Duration;
+use embedded_hal::digital::OutputPin;
+
+/// Error type for `BitBangBreakAdapter`, keeping UART and pin failures apart.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BreakAdapterError<UE, PE> {
+    /// Error from the wrapped UART.
+    Uart(UE),
+    /// Error while driving the TX pin during a break.
+    Pin(PE),
+}
+
+/// Adds a software-timed break to a UART that cannot assert one natively.
+///
+/// All regular serial operations are delegated to the wrapped UART. `send_break`
+/// instead drives `tx` low (spacing) for the configured duration using the timer,
+/// then releases it high (marking) so the UART can take over again.
+///
+/// # Timing
+///
+/// The break is only as accurate as `T::delay_us`. The spec requires the recorder
+/// to hold spacing for at least 12 ms (`timing::BREAK_DURATION_MIN`), and a sensor
+/// will ignore anything shorter than 6.5 ms, so `delay_us` must never return early.
+/// Overshooting is harmless. The post-break marking period is still handled by the
+/// recorder (`timing::POST_BREAK_MARKING_MIN`).
+///
+/// # Wiring
+///
+/// `tx` must be able to drive the line while the UART transmitter is idle: either
+/// the UART's TX pin itself (if the HAL allows toggling it as a GPIO between
+/// transfers) or a separate GPIO wired in parallel through the line driver.
+#[derive(Debug)]
+pub struct BitBangBreakAdapter<U, TX, T> {
+    uart: U,
+    tx: TX,
+    timer: T,
+    break_duration: Duration,
+}
+
+impl<U, TX, T> BitBangBreakAdapter<U, TX, T>
+where
+    U: Sdi12Serial,
+    TX: OutputPin,
+    T: Sdi12Timer,
+{
+    /// Creates an adapter holding the break for `timing::BREAK_DURATION_MIN`.
+    pub fn new(uart: U, tx: TX, timer: T) -> Self {
+        BitBangBreakAdapter {
+            uart,
+            tx,
+            timer,
+            break_duration: timing::BREAK_DURATION_MIN,
+        }
+    }
+
+    /// Returns the duration the TX line is held low for each break.
+    pub fn break_duration(&self) -> Duration {
+        self.break_duration
+    }
+
+    /// Releases the wrapped UART, pin and timer.
+    pub fn release(self) -> (U, TX, T) {
+        (self.uart, self.tx, self.timer)
+    }
+}
+
+impl<U, TX, T> Sdi12Serial for BitBangBreakAdapter<U, TX, T>
+where
+    U: Sdi12Serial,
+    TX: OutputPin,
+    TX::Error: Debug,
+    T: Sdi12Timer,
+{
+    type Error = BreakAdapterError<U::Error, TX::Error>;
+
+    fn read_byte(&mut self) -> nb::Result<u8, Self::Error> {
+        self.uart.read_byte().map_err(|e| e.map(BreakAdapterError::Uart))
+    }
+
+    fn write_byte(&mut self, byte: u8) -> nb::Result<(), Self::Error> {
+        self.uart.write_byte(byte).map_err(|e| e.map(BreakAdapterError::Uart))
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        self.uart.flush().map_err(|e| e.map(BreakAdapterError::Uart))
+    }
+
+    fn send_break(&mut self) -> nb::Result<(), Self::Error> {
+        // Make sure nothing is still shifting out before we take over the line.
+        self.uart.flush().map_err(|e| e.map(BreakAdapterError::Uart))?;
+
+        self.tx.set_low().map_err(|e| nb::Error::Other(BreakAdapterError::Pin(e)))?;
+        // Whole milliseconds first: `delay_us` alone wraps past ~71 minutes.
+        let millis = u32::try_from(self.break_duration.as_millis()).unwrap_or(u32::MAX);
+        self.timer.delay_ms(millis);
+        self.timer.delay_us(self.break_duration.subsec_micros() % 1000);
+        self.tx.set_high().map_err(|e| nb::Error::Other(BreakAdapterError::Pin(e)))?;
+        Ok(())
+    }
+
+    /// Sets the break length, never below `timing::BREAK_DURATION_MIN`.
+    fn set_break_duration(&mut self, duration: Duration) {
+        self.break_duration = duration.max(timing::BREAK_DURATION_MIN);
+    }
+
+    fn set_config(&mut self, config: FrameFormat) -> Result<(), Self::Error> {
+        self.uart.set_config(config).map_err(BreakAdapterError::Uart)
+    }
+
+    fn set_baud_rate(&mut self, baud: BaudRate) -> Result<(), Self::Error> {
+        self.uart.set_baud_rate(baud).map_err(BreakAdapterError::Uart)
+    }
+
+    fn supports_baud_rate(&self, baud: BaudRate) -> bool {
+        self.uart.supports_baud_rate(baud)
+    }
+
+    fn line_is_marking(&mut self) -> bool {
+        self.uart.line_is_marking()
+    }
+}
+
+impl<U, TX, T> Sdi12Timer for BitBangBreakAdapter<U, TX, T>
+where
+    T: Sdi12Timer,
+{
+    type Instant = T::Instant;
+
+    fn delay_us(&mut self, us: u32) {
+        self.timer.delay_us(us)
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        self.timer.delay_ms(ms)
+    }
+
+    fn now(&self) -> Self::Instant {
+        self.timer.now()
+    }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+    use core::convert::Infallible;
+    use embedded_hal::digital::ErrorType;
+    use nb::Result as NbResult;
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+    struct MockInstant(u64);
+    impl core::ops::Add<Duration> for MockInstant { type Output = Self; fn add(self, rhs: Duration) -> Self { MockInstant(self.0.saturating_add(rhs.as_micros() as u64)) } }
+    impl core::ops::Sub<MockInstant> for MockInstant { type Output = Duration; fn sub(self, rhs: MockInstant) -> Duration { Duration::from_micros(self.0.saturating_sub(rhs.0)) } }
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    struct MockCommError;
+
+    // The pin and timer share one clock so the pin can record when it changed.
+    struct MockTimer<'a> { clock_us: &'a Cell<u64> }
+    impl Sdi12Timer for MockTimer<'_> {
+        type Instant = MockInstant;
+        fn delay_us(&mut self, us: u32) { self.clock_us.set(self.clock_us.get() + us as u64); }
+        fn delay_ms(&mut self, ms: u32) { self.clock_us.set(self.clock_us.get() + ms as u64 * 1000); }
+        fn now(&self) -> Self::Instant { MockInstant(self.clock_us.get()) }
+    }
+
+    struct MockPin<'a> { clock_us: &'a Cell<u64>, low_at: Option<u64>, high_at: Option<u64> }
+    impl ErrorType for MockPin<'_> { type Error = Infallible; }
+    impl OutputPin for MockPin<'_> {
+        fn set_low(&mut self) -> Result<(), Self::Error> { self.low_at = Some(self.clock_us.get()); Ok(()) }
+        fn set_high(&mut self) -> Result<(), Self::Error> { self.high_at = Some(self.clock_us.get()); Ok(()) }
+    }
+
+    #[derive(Default)]
+    struct MockUart { written: usize, native_breaks: usize }
+    impl Sdi12Serial for MockUart {
+        type Error = MockCommError;
+        fn read_byte(&mut self) -> NbResult<u8, Self::Error> { Err(nb::Error::WouldBlock) }
+        fn write_byte(&mut self, _byte: u8) -> NbResult<(), Self::Error> { self.written += 1; Ok(()) }
+        fn flush(&mut self) -> NbResult<(), Self::Error> { Ok(()) }
+        fn send_break(&mut self) -> NbResult<(), Self::Error> { self.native_breaks += 1; Ok(()) }
+        fn set_config(&mut self, _config: FrameFormat) -> Result<(), Self::Error> { Ok(()) }
+    }
+
+    #[test]
+    fn test_break_holds_line_low_for_min_duration() {
+        let clock = Cell::new(1_000);
+        let pin = MockPin { clock_us: &clock, low_at: None, high_at: None };
+        let mut adapter = BitBangBreakAdapter::new(MockUart::default(), pin, MockTimer { clock_us: &clock });
+
+        assert!(adapter.send_break().is_ok());
+
+        let (uart, pin, _timer) = adapter.release();
+        let held_us = pin.high_at.unwrap() - pin.low_at.unwrap();
+        assert!(held_us as u128 >= timing::BREAK_DURATION_MIN.as_micros());
+        assert_eq!(pin.low_at, Some(1_000));
+        assert_eq!(uart.native_breaks, 0); // The UART's own break is never used
+    }
+
+    #[test]
+    fn test_break_duration_is_configurable() {
+        let clock = Cell::new(0);
+        let pin = MockPin { clock_us: &clock, low_at: None, high_at: None };
+        let mut adapter = BitBangBreakAdapter::new(MockUart::default(), pin, MockTimer { clock_us: &clock });
+
+        adapter.set_break_duration(Duration::from_millis(25));
+        assert!(adapter.send_break().is_ok());
+        let (_uart, pin, _timer) = adapter.release();
+        assert_eq!(pin.high_at.unwrap() - pin.low_at.unwrap(), 25_000);
+
+        let pin = MockPin { clock_us: &clock, low_at: None, high_at: None };
+        let mut adapter = BitBangBreakAdapter::new(MockUart::default(), pin, MockTimer { clock_us: &clock });
+        adapter.set_break_duration(Duration::from_millis(5));
+        assert_eq!(adapter.break_duration(), timing::BREAK_DURATION_MIN);
+    }
+
+    #[test]
+    fn test_long_break_does_not_wrap() {
+        let clock = Cell::new(0);
+        let pin = MockPin { clock_us: &clock, low_at: None, high_at: None };
+        let mut adapter = BitBangBreakAdapter::new(MockUart::default(), pin, MockTimer { clock_us: &clock });
+
+        // Longer than u32::MAX microseconds
+        adapter.set_break_duration(Duration::from_secs(4_300) + Duration::from_micros(250));
+        assert!(adapter.send_break().is_ok());
+        let (_uart, pin, _timer) = adapter.release();
+        assert_eq!(pin.high_at.unwrap() - pin.low_at.unwrap(), 4_300_000_250);
+    }
+
+    #[test]
+    fn test_other_operations_delegate_to_uart() {
+        let clock = Cell::new(0);
+        let pin = MockPin { clock_us: &clock, low_at: None, high_at: None };
+        let mut adapter = BitBangBreakAdapter::new(MockUart::default(), pin, MockTimer { clock_us: &clock });
+
+        assert!(adapter.write_byte(b'0').is_ok());
+        assert!(matches!(adapter.read_byte(), Err(nb::Error::WouldBlock)));
+        adapter.delay_ms(2);
+        assert_eq!(adapter.now(), MockInstant(2_000));
+
+        let (uart, pin, _timer) = adapter.release();
+        assert_eq!(uart.written, 1);
+        assert!(pin.low_at.is_none());
+    }
+}
@@ -0,0 +1,103 @@
+// src/implementations/delay_adapter.rs
+
+use crate::common::hal_traits::{Sdi12Instant, Sdi12Timer};
+use embedded_hal::delay::DelayNs;
+
+/// A source of the current time, the half of `Sdi12Timer` that `DelayNs` lacks.
+///
+/// Implemented for any `Fn() -> I`, so a closure reading the HAL's monotonic
+/// counter is usually enough.
+pub trait Monotonic {
+    /// The instant type handed to the recorder as `Sdi12Timer::Instant`.
+    type Instant: Sdi12Instant;
+
+    /// Returns the current time.
+    fn now(&self) -> Self::Instant;
+}
+
+impl<F, I> Monotonic for F
+where
+    F: Fn() -> I,
+    I: Sdi12Instant,
+{
+    type Instant = I;
+
+    fn now(&self) -> I {
+        self()
+    }
+}
+
+/// Implements `Sdi12Timer` from an embedded-hal 1.0 `DelayNs` and a `Monotonic` clock.
+///
+/// `delay_us`/`delay_ms` are forwarded to the delay, `now` to the clock. The
+/// result can serve as the timer of a `BitBangBreakAdapter`, or be delegated
+/// to from the type implementing `Sdi12Serial` for the recorder.
+#[derive(Debug)]
+pub struct DelayAdapter<D, M> {
+    delay: D,
+    clock: M,
+}
+
+impl<D, M> DelayAdapter<D, M>
+where
+    D: DelayNs,
+    M: Monotonic,
+{
+    pub fn new(delay: D, clock: M) -> Self {
+        DelayAdapter { delay, clock }
+    }
+
+    /// Releases the wrapped delay and clock.
+    pub fn release(self) -> (D, M) {
+        (self.delay, self.clock)
+    }
+}
+
+impl<D, M> Sdi12Timer for DelayAdapter<D, M>
+where
+    D: DelayNs,
+    M: Monotonic,
+{
+    type Instant = M::Instant;
+
+    fn delay_us(&mut self, us: u32) {
+        self.delay.delay_us(us)
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        self.delay.delay_ms(ms)
+    }
+
+    fn now(&self) -> Self::Instant {
+        self.clock.now()
+    }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+    use core::time::Duration;
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+    struct MockInstant(u64);
+    impl core::ops::Add<Duration> for MockInstant { type Output = Self; fn add(self, rhs: Duration) -> Self { MockInstant(self.0 + rhs.as_nanos() as u64) } }
+    impl core::ops::Sub<MockInstant> for MockInstant { type Output = Duration; fn sub(self, rhs: MockInstant) -> Duration { Duration::from_nanos(self.0.saturating_sub(rhs.0)) } }
+
+    struct MockDelay<'a> { clock_ns: &'a Cell<u64> }
+    impl DelayNs for MockDelay<'_> {
+        fn delay_ns(&mut self, ns: u32) { self.clock_ns.set(self.clock_ns.get() + ns as u64); }
+    }
+
+    #[test]
+    fn test_delays_forwarded_and_clock_read() {
+        let clock_ns = Cell::new(0);
+        let mut timer = DelayAdapter::new(MockDelay { clock_ns: &clock_ns }, || MockInstant(clock_ns.get()));
+
+        timer.delay_us(250);
+        assert_eq!(timer.now(), MockInstant(250_000));
+        timer.delay_ms(12);
+        assert_eq!(timer.now() - MockInstant(250_000), Duration::from_millis(12));
+    }
+}
@@ -0,0 +1,18 @@
+// src/implementations/mod.rs
+
+// Optional, feature-gated adapters that bridge HAL peripherals to the
+// library's `Sdi12Serial` / `Sdi12Timer` traits.
+
+// Software-timed break for UARTs without a native break API.
+#[cfg(feature = "impl-bitbang")]
+pub mod bitbang_break;
+
+#[cfg(feature = "impl-bitbang")]
+pub use bitbang_break::{BitBangBreakAdapter, BreakAdapterError};
+
+// `Sdi12Timer` from an embedded-hal `DelayNs` plus a user-supplied clock.
+#[cfg(any(feature = "impl-native", feature = "impl-generic-hal"))]
+pub mod delay_adapter;
+
+#[cfg(any(feature = "impl-native", feature = "impl-generic-hal"))]
+pub use delay_adapter::{DelayAdapter, Monotonic};
@@ -5,9 +5,21 @@
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
+// Protocol tracing: `log::trace!` with the `log` feature, compiled out otherwise.
+// Must be defined before the modules that use it.
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "log")]
+        log::trace!(target: "sdi12", $($arg)*);
+    };
+}
+
 pub mod common;
+pub mod implementations;
 pub mod recorder;
 pub mod sensor;
+#[cfg(feature = "test-util")]
+pub mod testing;
 
 // Re-export key types for convenience
 pub use common::Sdi12Addr;
@@ -0,0 +1,115 @@
+// src/recorder/sync_recorder/config.rs
+
+use crate::common::{timing, BaudRate};
+use core::time::Duration;
+
+/// Which command variant the recorder's workflow methods send.
+///
+/// Applies to `measure`, `measure_crc`, `begin_measure` and `read_continuous`;
+/// commands passed to `send_command` are always sent as given. The CRC is
+/// verified whenever the command actually sent requests one.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum CrcMode {
+    /// Always send the plain variant, even from `measure_crc`. For sensors that
+    /// predate CRC support (SDI-12 1.2 and earlier).
+    Never,
+    /// Send whichever variant the method names.
+    #[default]
+    WhenRequested,
+    /// Promote to the `*CRC` variant (`aMC!`, `aRC0!`, ...) wherever one exists.
+    /// Every sensor on the bus must support CRCs, or its responses will fail
+    /// verification.
+    Always,
+}
+
+/// What the recorder does with data whose CRC doesn't match.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum CrcFailurePolicy {
+    /// Fail with `Sdi12Error::CrcMismatch`.
+    #[default]
+    Reject,
+    /// Return the values anyway, marked with `DataInfo::crc_valid == Some(false)`.
+    /// For field data that is worth logging with a warning rather than losing.
+    /// Only data (`aDn!`, `aRn!`) is accepted this way; any other response with
+    /// a bad CRC still fails.
+    WarnAndAccept,
+}
+
+/// Tunable behaviour for a `SyncRecorder`.
+///
+/// The defaults follow the SDI-12 specification strictly; every option here
+/// relaxes or extends that behaviour for sensors that need it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RecorderConfig {
+    /// Verify a CRC on any data (`aDn!`, `aRn!`) or metadata parameter reply
+    /// that appears to carry one, not only on responses to `*CRC` commands.
+    /// Detection uses `crc::looks_like_crc_ascii`, and a detected CRC that
+    /// fails to verify is still reported as `Sdi12Error::CrcMismatch`. Other
+    /// replies, such as `aI!`'s text, never carry a CRC and aren't checked.
+    ///
+    /// Default: `false` (CRC is only checked when the command requested it).
+    pub always_verify_crc_if_present: bool,
+
+    /// Longest gap allowed between two bytes of one response line before the
+    /// line is abandoned as incomplete. The spec allows 1.66 ms of marking
+    /// between characters; slow or buggy sensors may need more.
+    ///
+    /// Default: `timing::INTER_CHARACTER_MARKING_MAX` + 5 ms.
+    pub inter_char_timeout: Duration,
+
+    /// Accept a bare `<LF>` as the end of a response line. Some non-compliant
+    /// sensors drop the `<CR>`; with this set their lines end at the `<LF>`
+    /// instead of running into the inter-character timeout.
+    ///
+    /// Default: `false` (lines must end in `<CR><LF>`).
+    pub accept_bare_lf: bool,
+
+    /// How long a break holds the line spacing, passed to the interface via
+    /// `Sdi12Serial::set_break_duration`. Some older sensors need 20 ms or more
+    /// to wake reliably. Values below `timing::BREAK_DURATION_MIN` are raised to it.
+    ///
+    /// Default: `timing::BREAK_DURATION_MIN` + 5 ms.
+    pub break_duration: Duration,
+
+    /// Whether workflow methods request CRCs; see `CrcMode`.
+    ///
+    /// Default: `CrcMode::WhenRequested`.
+    pub crc_mode: CrcMode,
+
+    /// How many values beyond the count announced in the timing response the
+    /// data commands may return before the measurement fails with
+    /// `Sdi12Error::UnexpectedResponse`. Some sensors append status values
+    /// they don't count; `usize::MAX` accepts any number.
+    ///
+    /// Default: `0` (exactly the announced count, or fewer).
+    pub extra_values_allowed: usize,
+
+    /// Whether data failing its CRC check is an error; see `CrcFailurePolicy`.
+    ///
+    /// Default: `CrcFailurePolicy::Reject`.
+    pub crc_failure_policy: CrcFailurePolicy,
+
+    /// Bit rate of the bus, passed to the interface via
+    /// `Sdi12Serial::set_baud_rate`. Read and write timeouts scale with it.
+    /// Anything but 1200 baud is non-standard; see `BaudRate`. Every command
+    /// fails with `Sdi12Error::UnsupportedBaudRate` if the interface can't
+    /// switch to it (`Sdi12Serial::supports_baud_rate`).
+    ///
+    /// Default: `BaudRate::STANDARD` (1200 baud).
+    pub baud_rate: BaudRate,
+}
+
+impl Default for RecorderConfig {
+    fn default() -> Self {
+        RecorderConfig {
+            always_verify_crc_if_present: false,
+            inter_char_timeout: timing::INTER_CHARACTER_MARKING_MAX + Duration::from_millis(5),
+            accept_bare_lf: false,
+            break_duration: timing::BREAK_DURATION_MIN + Duration::from_millis(5),
+            crc_mode: CrcMode::WhenRequested,
+            extra_values_allowed: 0,
+            crc_failure_policy: CrcFailurePolicy::Reject,
+            baud_rate: BaudRate::STANDARD,
+        }
+    }
+}
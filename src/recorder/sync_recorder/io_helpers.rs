@@ -1,10 +1,13 @@
 // src/recorder/sync_recorder/io_helpers.rs
 
-use super::SyncRecorder; // Access SyncRecorder definition
+use super::{SyncRecorder, TraceEvent, TraceSink}; // Access SyncRecorder definition
 use crate::common::{
+    address::Sdi12Addr,
+    command::Command,
     error::Sdi12Error,
     hal_traits::{Sdi12Instant, Sdi12Serial, Sdi12Timer},
-    timing, FrameFormat,
+    response::{ResponseLines, ResponseParseError, BINARY_PACKET_MAX_PAYLOAD, BINARY_PACKET_OVERHEAD},
+    timing, BaudRate, FrameFormat,
 };
 use core::fmt::Debug;
 use core::ops::Sub;
@@ -12,30 +15,32 @@ use core::time::Duration;
 use nb::Result as NbResult;
 
 // Implementation block for I/O related helpers
-impl<IF> SyncRecorder<IF>
+impl<IF, T> SyncRecorder<IF, T>
 where
     IF: Sdi12Serial + Sdi12Timer,
     IF::Error: Debug,
     IF::Instant: Sdi12Instant,
+    T: TraceSink,
 {
     /// Executes a non-blocking I/O operation (`f`) repeatedly until it
     /// stops returning `WouldBlock`, returning the final result or a timeout error.
-    pub(super) fn execute_blocking_io_with_timeout<FN, T>( // Make pub(super)
+    pub(super) fn execute_blocking_io_with_timeout<FN, R>( // Make pub(super)
         &mut self,
         timeout: Duration,
         mut f: FN,
-    ) -> Result<T, Sdi12Error<IF::Error>>
+    ) -> Result<R, Sdi12Error<IF::Error>>
     where
-        FN: FnMut(&mut IF) -> NbResult<T, IF::Error>,
+        FN: FnMut(&mut IF) -> NbResult<R, IF::Error>,
     {
-        let start_time = self.interface.now();
-        let deadline = start_time + timeout;
+        // Sum the elapsed steps rather than compare `now >= start + timeout`: near the
+        // end of a short-period timer that sum wraps around and times out at once.
+        let mut stopwatch = Stopwatch::start(self.interface.now());
 
         loop {
             match f(&mut self.interface) {
                 Ok(result) => return Ok(result),
                 Err(nb::Error::WouldBlock) => {
-                    if self.interface.now() >= deadline {
+                    if stopwatch.update(self.interface.now()) >= timeout {
                         return Err(Sdi12Error::Timeout);
                     }
                     // Optional delay - small delay might prevent busy-spinning 100% CPU
@@ -51,31 +56,65 @@ where
         let now = self.interface.now();
         let mut break_needed = true;
 
-        if let Some(last_time) = self.last_activity_time {
+        // A clock reading earlier than the last activity has wrapped, so the real gap
+        // is unknown and we break to be safe (a wrapping `Sub` would otherwise make
+        // it look short, a saturating one would make it zero).
+        if let Some(last_time) = self.last_activity_time.filter(|last| now >= *last) {
             let elapsed = now.sub(last_time);
-            if elapsed <= timing::PRE_COMMAND_BREAK_MARKING_THRESHOLD {
+            // Recent activity alone isn't enough if the interface saw the line leave marking.
+            if elapsed <= timing::PRE_COMMAND_BREAK_MARKING_THRESHOLD && self.interface.line_is_marking() {
                 break_needed = false;
             }
         }
 
         if break_needed {
-            let break_timeout = timing::BREAK_DURATION_MIN + Duration::from_millis(5);
-            self.execute_blocking_io_with_timeout(break_timeout, |iface| iface.send_break())?;
-            self.interface.delay_us(timing::POST_BREAK_MARKING_MIN.as_micros() as u32);
-            // Update time *after* break sequence completes successfully
-            self.last_activity_time = Some(self.interface.now());
+            self.send_break_sequence()?;
         }
 
         Ok(())
     }
 
+    /// Sends a break followed by the post-break marking, unconditionally.
+    pub(super) fn send_break_sequence(&mut self) -> Result<(), Sdi12Error<IF::Error>> {
+        let break_duration = self.config.break_duration.max(timing::BREAK_DURATION_MIN);
+        self.interface.set_break_duration(break_duration);
+        let break_timeout = break_duration + Duration::from_millis(5);
+        self.execute_blocking_io_with_timeout(break_timeout, |iface| iface.send_break())?;
+        trace!("break sent ({:?})", break_duration);
+        self.interface.delay_us(timing::POST_BREAK_MARKING_MIN.as_micros() as u32);
+        // Update time *after* break sequence completes successfully
+        self.last_activity_time = Some(self.interface.now());
+        self.trace.event(TraceEvent::BreakSent);
+        Ok(())
+    }
+
+    /// Switches the interface to `format` at the configured baud rate unless
+    /// both are known to be set already.
+    ///
+    /// A failed `set_baud_rate` or `set_config` leaves the format unknown, so
+    /// the next command tries again instead of being sent in whatever format
+    /// the UART was left in. A baud rate the interface doesn't support fails
+    /// with `UnsupportedBaudRate` before either is called.
+    pub(super) fn ensure_frame_format(&mut self, format: FrameFormat) -> Result<(), Sdi12Error<IF::Error>> {
+        let baud = self.config.baud_rate;
+        if self.known_frame_format == Some((format, baud)) {
+            return Ok(());
+        }
+        self.known_frame_format = None;
+        if !self.interface.supports_baud_rate(baud) {
+            return Err(Sdi12Error::UnsupportedBaudRate(baud));
+        }
+        self.interface.set_baud_rate(baud).map_err(Sdi12Error::Io)?;
+        self.interface.set_config(format).map_err(Sdi12Error::Io)?;
+        self.known_frame_format = Some((format, baud));
+        Ok(())
+    }
+
     /// Sends the already formatted command bytes over the serial interface.
     pub(super) fn send_command_bytes(&mut self, cmd_bytes: &[u8]) -> Result<(), Sdi12Error<IF::Error>> { // Make pub(super)
-        self.interface
-            .set_config(FrameFormat::Sdi12_7e1)
-            .map_err(Sdi12Error::Io)?;
+        self.ensure_frame_format(FrameFormat::Sdi12_7e1)?;
 
-        let write_duration = timing::BYTE_DURATION * cmd_bytes.len() as u32;
+        let write_duration = self.config.baud_rate.transmission_time(cmd_bytes.len());
         let write_timeout = write_duration + Duration::from_millis(20); // 20ms buffer
 
         for byte in cmd_bytes {
@@ -86,23 +125,43 @@ where
 
         let flush_timeout = Duration::from_millis(10);
         self.execute_blocking_io_with_timeout(flush_timeout, |iface| iface.flush())?;
+        trace!("tx {}", cmd_bytes.escape_ascii());
+        self.trace.event(TraceEvent::Tx(cmd_bytes));
 
         // NOTE: Do not update last_activity_time here. Update only after successful response.
         Ok(())
     }
 
      /// Reads a complete response line (up to <CR><LF>) into the buffer.
+     ///
+     /// `max_len` is the longest line (including `<CR><LF>`) accepted before giving up
+     /// with `Sdi12Error::ResponseTooLong`; see `Command::max_response_len`.
+     ///
+     /// Returns `Sdi12Error::BusContention` if the line can't be a single sensor's
+     /// response (see `check_line_integrity`).
      pub(super) fn read_response_line<'buf>( // Make pub(super)
         &mut self,
         buffer: &'buf mut [u8],
+        max_len: usize,
     ) -> Result<&'buf [u8], Sdi12Error<IF::Error>> {
-        // Calculate timeout: Response start time + time for max standard response length
-        let max_resp_len = 96; // Generous buffer
-        let read_allowance = timing::BYTE_DURATION * max_resp_len;
-        let read_timeout = timing::RESPONSE_START_TIME_MAX + read_allowance + Duration::from_millis(50);
+        let timeout = response_timeout_for_len(max_len, self.config.baud_rate);
+        self.read_response_line_with_timeout(buffer, max_len, timeout)
+    }
 
+     /// Like `read_response_line`, but waits at most `first_byte_timeout` for the
+     /// response to start instead of the length-based default.
+     pub(super) fn read_response_line_with_timeout<'buf>(
+        &mut self,
+        buffer: &'buf mut [u8],
+        max_len: usize,
+        first_byte_timeout: Duration,
+    ) -> Result<&'buf [u8], Sdi12Error<IF::Error>> {
         let mut bytes_read = 0;
         loop {
+            if bytes_read >= max_len {
+                // A runaway sensor: stop before it fills the caller's buffer
+                return Err(Sdi12Error::ResponseTooLong { max: max_len });
+            }
             if bytes_read >= buffer.len() {
                 return Err(Sdi12Error::BufferOverflow {
                     needed: bytes_read + 1,
@@ -112,39 +171,193 @@ where
 
             // Define a shorter timeout for subsequent bytes once the first byte arrived
             let current_timeout = if bytes_read == 0 {
-                read_timeout
+                first_byte_timeout
             } else {
-                 // Timeout based on inter-character spacing + buffer
-                timing::INTER_CHARACTER_MARKING_MAX + Duration::from_millis(5)
+                // Timeout based on inter-character spacing (configurable for slow sensors)
+                self.config.inter_char_timeout
             };
 
             match self.execute_blocking_io_with_timeout(current_timeout, |iface| iface.read_byte()) {
                 Ok(byte) => {
                     buffer[bytes_read] = byte;
                     bytes_read += 1;
+                    trace!("rx byte {:#04x}", byte);
 
-                    // Check for <CR><LF>
-                    if bytes_read >= 2
-                        && buffer[bytes_read - 2] == b'\r'
-                        && buffer[bytes_read - 1] == b'\n'
+                    // Check for <CR><LF> (or a bare <LF> if configured)
+                    if byte == b'\n'
+                        && ((bytes_read >= 2 && buffer[bytes_read - 2] == b'\r')
+                            || self.config.accept_bare_lf)
                     {
-                        return Ok(&buffer[..bytes_read]);
+                        let line = &buffer[..bytes_read];
+                        trace!("rx line {}", line.escape_ascii());
+                        self.trace.event(TraceEvent::Rx(line));
+                        check_line_integrity(line)?;
+                        return Ok(line);
                     }
                 }
                 Err(Sdi12Error::Timeout) => {
                     if bytes_read > 0 {
                         // Received some bytes but didn't get CRLF in time
-                        return Err(Sdi12Error::InvalidFormat);
+                        trace!("inter-character timeout after {}", buffer[..bytes_read].escape_ascii());
+                        self.trace.event(TraceEvent::Rx(&buffer[..bytes_read]));
+                        self.trace.event(TraceEvent::Timeout);
+                        return Err(Sdi12Error::InterCharTimeout);
                     } else {
                         // Timed out waiting for the first byte
-                        return Err(Sdi12Error::Timeout);
+                        trace!("no response within {:?}", current_timeout);
+                        self.trace.event(TraceEvent::Timeout);
+                        return Err(Sdi12Error::ResponseTimeout);
                     }
                 }
                 Err(e) => return Err(e),
             }
         }
     }
+
+    /// Reads one high-volume binary packet (reply to `aDBn!`) into the buffer
+    /// and returns its length. The packet size comes from its header, so there
+    /// is no line terminator to wait for; nothing is checked beyond that size.
+    ///
+    /// A packet longer than `buffer` is still read to its end, so the sensor
+    /// has finished talking, and then reported as `BufferOverflow`. `buffer`
+    /// must hold at least the 3 header bytes carrying the size.
+    pub(super) fn read_binary_packet(
+        &mut self,
+        buffer: &mut [u8],
+        first_byte_timeout: Duration,
+    ) -> Result<usize, Sdi12Error<IF::Error>> {
+        let mut bytes_read = 0;
+        let mut packet_len = BINARY_PACKET_OVERHEAD; // Until the header says otherwise
+        while bytes_read < packet_len {
+            let timeout = if bytes_read == 0 { first_byte_timeout } else { self.config.inter_char_timeout };
+            let byte = match self.execute_blocking_io_with_timeout(timeout, |iface| iface.read_byte()) {
+                Ok(byte) => byte,
+                Err(Sdi12Error::Timeout) if bytes_read == 0 => {
+                    self.trace.event(TraceEvent::Timeout);
+                    return Err(Sdi12Error::ResponseTimeout);
+                }
+                Err(Sdi12Error::Timeout) => {
+                    trace!("inter-character timeout after {} of {} binary bytes", bytes_read, packet_len);
+                    self.trace.event(TraceEvent::Rx(&buffer[..bytes_read.min(buffer.len())]));
+                    self.trace.event(TraceEvent::Timeout);
+                    return Err(Sdi12Error::InterCharTimeout);
+                }
+                Err(e) => return Err(e),
+            };
+            if let Some(slot) = buffer.get_mut(bytes_read) {
+                *slot = byte;
+            }
+            bytes_read += 1;
+            if bytes_read == 3 {
+                let payload_len = usize::from(u16::from_le_bytes([buffer[1], buffer[2]]));
+                if payload_len > BINARY_PACKET_MAX_PAYLOAD {
+                    return Err(Sdi12Error::from_parse_error(ResponseParseError::InconsistentBinaryPacketSize));
+                }
+                packet_len = payload_len + BINARY_PACKET_OVERHEAD;
+            }
+        }
+        trace!("rx binary packet of {} bytes", packet_len);
+        self.trace.event(TraceEvent::Rx(&buffer[..packet_len.min(buffer.len())]));
+        if packet_len > buffer.len() {
+            return Err(Sdi12Error::BufferOverflow { needed: packet_len, got: buffer.len() });
+        }
+        Ok(packet_len)
+    }
+
+    /// Reads up to `max_lines` consecutive response lines into the buffer.
+    /// Stops early (successfully) when no further line starts before the timeout,
+    /// as long as at least one line was read.
+    pub(super) fn read_response_lines<'buf>(
+        &mut self,
+        buffer: &'buf mut [u8],
+        max_lines: usize,
+    ) -> Result<ResponseLines<'buf>, Sdi12Error<IF::Error>> {
+        let mut used = 0;
+        let mut lines_read = 0;
+
+        while lines_read < max_lines {
+            match self.read_response_line(&mut buffer[used..], Command::MAX_RESPONSE_LEN) {
+                Ok(line) => {
+                    used += line.len();
+                    lines_read += 1;
+                }
+                // Quiet line after at least one response: the burst is over
+                Err(Sdi12Error::ResponseTimeout) if lines_read > 0 => break,
+                // Report overflow relative to the whole buffer, not the remaining tail
+                Err(Sdi12Error::BufferOverflow { needed, .. }) => {
+                    return Err(Sdi12Error::BufferOverflow {
+                        needed: used + needed,
+                        got: buffer.len(),
+                    });
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(ResponseLines::new(&buffer[..used]))
+    }
+}
+/// Time elapsed on the interface's clock, summed from readings taken more
+/// often than the clock wraps, so a short-period timer still measures waits
+/// longer than its period.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(super) struct Stopwatch<I> {
+    last: I,
+    elapsed: Duration,
+}
+
+impl<I: Sdi12Instant> Stopwatch<I> {
+    pub(super) fn start(now: I) -> Self {
+        Stopwatch { last: now, elapsed: Duration::ZERO }
+    }
+
+    /// Adds the time since the last reading and returns the total.
+    pub(super) fn update(&mut self, now: I) -> Duration {
+        // A reading earlier than the last one means the clock wrapped; that
+        // step is unknown (and may panic in `Sub`), so it counts as zero.
+        if now >= self.last {
+            self.elapsed += now.sub(self.last);
+        }
+        self.last = now;
+        self.elapsed
+    }
 }
+
+/// Default wait for a response line of up to `max_len` bytes to start: the
+/// spec's response start time plus the transmission time of the longest
+/// allowed line at `baud`, plus 50 ms of slack.
+pub(super) fn response_timeout_for_len(max_len: usize, baud: BaudRate) -> Duration {
+    let read_allowance = baud.transmission_time(max_len);
+    timing::RESPONSE_START_TIME_MAX + read_allowance + Duration::from_millis(50)
+}
+
+/// Rejects a `<CR><LF>`-terminated line that no single sensor would send.
+///
+/// When two sensors answer at once (typically during `?!` with several sensors
+/// on the bus) their bytes interleave into garbage that can still end in
+/// `<CR><LF>`. Such lines show up as an invalid address byte or as characters
+/// outside the printable ASCII range responses are limited to (plus `DEL`,
+/// which an ASCII CRC character can be).
+fn check_line_integrity<E: Debug>(line: &[u8]) -> Result<(), Sdi12Error<E>> {
+    let body = strip_line_terminator(line);
+    let Some((&addr_byte, payload)) = body.split_first() else {
+        return Ok(()); // Bare <CR><LF>: left for the payload checks to reject
+    };
+    if !Sdi12Addr::is_valid_address_char(addr_byte as char)
+        || !payload.iter().all(|b| (0x20..=0x7F).contains(b))
+    {
+        return Err(Sdi12Error::BusContention);
+    }
+    Ok(())
+}
+
+/// Removes a trailing `<CR><LF>`, or a bare `<LF>`, if present.
+pub(super) fn strip_line_terminator(line: &[u8]) -> &[u8] {
+    line.strip_suffix(b"\r\n")
+        .or_else(|| line.strip_suffix(b"\n"))
+        .unwrap_or(line)
+}
+
 // src/recorder/sync_recorder/io_helpers.rs
 // ... (main code) ...
 
@@ -153,8 +366,6 @@ where
 mod tests {
     use super::*;
     use crate::common::{
-        address::Sdi12Addr, // Keep needed imports for tests
-        command::Command,
         hal_traits::{Sdi12Serial, Sdi12Timer}, // Remove Sdi12Instant from here
         FrameFormat, Sdi12Error,
     };
@@ -188,8 +399,12 @@ mod tests {
         current_time_us: u64,
         read_queue: [Option<u8>; 96],
         read_pos: usize,
+        read_gap_us: u64,       // Simulated marking time before each byte after the first
+        next_byte_at_us: u64,
         write_log: [Option<u8>; 96],
         write_pos: usize,
+        line_marking: bool,
+        break_duration: Option<Duration>,
         #[cfg(feature = "std")]
         io_call_counts: std::collections::HashMap<&'static str, u32>,
         #[cfg(not(feature = "std"))]
@@ -203,8 +418,12 @@ mod tests {
                 current_time_us: 0,
                 read_queue: [None; 96],
                 read_pos: 0,
+                read_gap_us: 0,
+                next_byte_at_us: 0,
                 write_log: [None; 96],
                 write_pos: 0,
+                line_marking: true,
+                break_duration: None,
                  #[cfg(feature = "std")]
                  io_call_counts: std::collections::HashMap::new(),
                  #[cfg(not(feature = "std"))]
@@ -225,6 +444,7 @@ mod tests {
              *self.io_call_counts.get(name).unwrap_or(&0)
         }
          #[cfg(not(feature = "std"))]
+        #[allow(dead_code)]
         fn get_call_count(&self, _name: &'static str) -> u32 { 0 }
         fn stage_read_data(&mut self, data: &[u8]) {
              self.read_pos = 0;
@@ -246,8 +466,12 @@ mod tests {
          fn read_byte(&mut self) -> NbResult<u8, Self::Error> { /* ... */
             self.increment_call_count("read_byte");
             if self.read_pos < self.read_queue.len() {
+                if self.current_time_us < self.next_byte_at_us {
+                    return Err(nb::Error::WouldBlock);
+                }
                 if let Some(byte) = self.read_queue[self.read_pos] {
                     self.read_pos += 1;
+                    self.next_byte_at_us = self.current_time_us + self.read_gap_us;
                     Ok(byte)
                 } else {
                      Err(nb::Error::WouldBlock)
@@ -267,11 +491,11 @@ mod tests {
              }
          }
          fn flush(&mut self) -> NbResult<(), Self::Error> { self.increment_call_count("flush"); Ok(()) } // Uses NbResult
+         fn set_break_duration(&mut self, duration: Duration) { self.break_duration = Some(duration); }
          fn send_break(&mut self) -> NbResult<(), Self::Error> { self.increment_call_count("send_break"); self.break_sent = true; Ok(()) } // Uses NbResult
          fn set_config(&mut self, config: FrameFormat) -> Result<(), Self::Error> { self.increment_call_count("set_config"); self.config = config; Ok(()) }
+         fn line_is_marking(&mut self) -> bool { self.line_marking }
      }
-     // Helper
-     fn addr(c: char) -> Sdi12Addr { Sdi12Addr::new(c).unwrap() }
 
     // ... (All tests copied from previous mod.rs tests block) ...
     #[test]
@@ -340,7 +564,7 @@ mod tests {
         let mut recorder = SyncRecorder::new(mock_if);
         let mut buffer = [0u8; 32];
 
-        let result = recorder.read_response_line(&mut buffer);
+        let result = recorder.read_response_line(&mut buffer, Command::MAX_RESPONSE_LEN);
         assert!(result.is_ok());
         let line_slice = result.unwrap();
         let len = line_slice.len();
@@ -353,8 +577,8 @@ mod tests {
          let mock_if = MockInterface::new();
          let mut recorder = SyncRecorder::new(mock_if);
          let mut buffer = [0u8; 32];
-         let result = recorder.read_response_line(&mut buffer);
-         assert!(matches!(result, Err(Sdi12Error::Timeout)));
+         let result = recorder.read_response_line(&mut buffer, Command::MAX_RESPONSE_LEN);
+         assert!(matches!(result, Err(Sdi12Error::ResponseTimeout)));
     }
     #[test]
     fn test_read_response_line_timeout_partial_data() { /* ... as before ... */
@@ -362,8 +586,111 @@ mod tests {
          mock_if.stage_read_data(b"1+12.3");
          let mut recorder = SyncRecorder::new(mock_if);
          let mut buffer = [0u8; 32];
-         let result = recorder.read_response_line(&mut buffer);
-         assert!(matches!(result, Err(Sdi12Error::InvalidFormat)));
+         let result = recorder.read_response_line(&mut buffer, Command::MAX_RESPONSE_LEN);
+         assert!(matches!(result, Err(Sdi12Error::InterCharTimeout)));
+    }
+    #[test]
+    fn test_read_response_line_rejects_collision_garbage() {
+        // Interleaved "0+1.1" and "1+2.2": non-printable bytes mid-line
+        let mut mock_if = MockInterface::new();
+        mock_if.stage_read_data(b"0+\x91.\x02\x1f2.2\r\n");
+        let mut recorder = SyncRecorder::new(mock_if);
+        let mut buffer = [0u8; 32];
+        assert!(matches!(recorder.read_response_line(&mut buffer, Command::MAX_RESPONSE_LEN), Err(Sdi12Error::BusContention)));
+
+        // Garbled address byte
+        let mut mock_if = MockInterface::new();
+        mock_if.stage_read_data(b"\x80\r\n");
+        let mut recorder = SyncRecorder::new(mock_if);
+        assert!(matches!(recorder.read_response_line(&mut buffer, Command::MAX_RESPONSE_LEN), Err(Sdi12Error::BusContention)));
+
+        // Same during a multi-line query
+        let mut mock_if = MockInterface::new();
+        mock_if.stage_read_data(b"0\r\n#\r\n");
+        let mut recorder = SyncRecorder::new(mock_if);
+        assert!(matches!(recorder.read_response_lines(&mut buffer, 3), Err(Sdi12Error::BusContention)));
+
+        // A CRC of all ones encodes its last character as DEL, which is legitimate
+        let mut mock_if = MockInterface::new();
+        mock_if.stage_read_data(b"0+1\x7f\x7f\x7f\r\n");
+        let mut recorder = SyncRecorder::new(mock_if);
+        assert!(recorder.read_response_line(&mut buffer, Command::MAX_RESPONSE_LEN).is_ok());
+    }
+    #[test]
+    fn test_read_response_lines_stops_on_timeout() {
+        let mut mock_if = MockInterface::new();
+        mock_if.stage_read_data(b"0+1.1\r\n0+2.2\r\n");
+        let mut recorder = SyncRecorder::new(mock_if);
+        let mut buffer = [0u8; 32];
+
+        let mut lines = recorder.read_response_lines(&mut buffer, 5).unwrap();
+        assert_eq!(lines.next(), Some(&b"0+1.1\r\n"[..]));
+        assert_eq!(lines.next(), Some(&b"0+2.2\r\n"[..]));
+        assert_eq!(lines.next(), None);
+    }
+    #[test]
+    fn test_read_response_lines_max_lines_and_errors() {
+        let mut mock_if = MockInterface::new();
+        mock_if.stage_read_data(b"0+1.1\r\n0+2.2\r\n");
+        let mut recorder = SyncRecorder::new(mock_if);
+        let mut buffer = [0u8; 32];
+        assert_eq!(recorder.read_response_lines(&mut buffer, 1).unwrap().count(), 1);
+
+        // Nothing at all on the line is still a timeout
+        let mut recorder = SyncRecorder::new(MockInterface::new());
+        assert!(matches!(recorder.read_response_lines(&mut buffer, 3), Err(Sdi12Error::ResponseTimeout)));
+
+        // Second line doesn't fit
+        let mut mock_if = MockInterface::new();
+        mock_if.stage_read_data(b"0+1.1\r\n0+2.2\r\n");
+        let mut recorder = SyncRecorder::new(mock_if);
+        let mut small = [0u8; 10];
+        assert!(matches!(recorder.read_response_lines(&mut small, 3), Err(Sdi12Error::BufferOverflow { needed: 11, got: 10 })));
+    }
+    #[test]
+    fn test_read_response_line_inter_char_timeout_config() {
+        let slow_sensor = || {
+            let mut mock_if = MockInterface::new();
+            mock_if.stage_read_data(b"0+1.1\r\n");
+            mock_if.read_gap_us = 3_000;
+            mock_if
+        };
+        let mut buffer = [0u8; 16];
+
+        // A 3 ms gap is fine with the default allowance
+        let mut recorder = SyncRecorder::new(slow_sensor());
+        assert_eq!(recorder.read_response_line(&mut buffer, Command::MAX_RESPONSE_LEN).unwrap(), b"0+1.1\r\n");
+
+        // Too tight: the line is abandoned after the first byte
+        let mut recorder = SyncRecorder::new(slow_sensor());
+        recorder.config_mut().inter_char_timeout = Duration::from_millis(2);
+        assert!(matches!(recorder.read_response_line(&mut buffer, Command::MAX_RESPONSE_LEN), Err(Sdi12Error::InterCharTimeout)));
+
+        // Raised just above the gap: completes again
+        let mut recorder = SyncRecorder::new(slow_sensor());
+        recorder.config_mut().inter_char_timeout = Duration::from_millis(4);
+        assert_eq!(recorder.read_response_line(&mut buffer, Command::MAX_RESPONSE_LEN).unwrap(), b"0+1.1\r\n");
+    }
+    #[test]
+    fn test_read_response_line_bare_lf() {
+        let mut buffer = [0u8; 16];
+        let recorder_for = |data: &[u8], lenient: bool| {
+            let mut mock_if = MockInterface::new();
+            mock_if.stage_read_data(data);
+            let mut recorder = SyncRecorder::new(mock_if);
+            recorder.config_mut().accept_bare_lf = lenient;
+            recorder
+        };
+
+        // Strict: a bare <LF> doesn't end the line
+        let mut recorder = recorder_for(b"0\n", false);
+        assert!(matches!(recorder.read_response_line(&mut buffer, Command::MAX_RESPONSE_LEN), Err(Sdi12Error::InterCharTimeout)));
+
+        // Lenient: both terminators end it
+        let mut recorder = recorder_for(b"0\n", true);
+        assert_eq!(recorder.read_response_line(&mut buffer, Command::MAX_RESPONSE_LEN).unwrap(), b"0\n");
+        let mut recorder = recorder_for(b"0\r\n", true);
+        assert_eq!(recorder.read_response_line(&mut buffer, Command::MAX_RESPONSE_LEN).unwrap(), b"0\r\n");
     }
      #[test]
     fn test_read_response_line_buffer_overflow() { /* ... as before ... */
@@ -371,10 +698,39 @@ mod tests {
          mock_if.stage_read_data(b"1+12.345\r\n"); // 10 bytes
          let mut recorder = SyncRecorder::new(mock_if);
          let mut buffer = [0u8; 8]; // Buffer too small
-         let result = recorder.read_response_line(&mut buffer);
+         let result = recorder.read_response_line(&mut buffer, Command::MAX_RESPONSE_LEN);
          assert!(matches!(result, Err(Sdi12Error::BufferOverflow{needed: 9, got: 8})));
     }
     #[test]
+    fn test_read_response_line_too_long() {
+        // An address-only reply was expected but the sensor keeps talking
+        let mut mock_if = MockInterface::new();
+        mock_if.stage_read_data(b"0+1.23+4.56\r\n");
+        let mut recorder = SyncRecorder::new(mock_if);
+        let mut buffer = [0u8; 32];
+        let ack = Command::AcknowledgeActive { address: Sdi12Addr::new('0').unwrap() };
+        let result = recorder.read_response_line(&mut buffer, ack.max_response_len());
+        assert!(matches!(result, Err(Sdi12Error::ResponseTooLong { max: 3 })));
+
+        // Exactly at the limit is fine
+        let mut mock_if = MockInterface::new();
+        mock_if.stage_read_data(b"0\r\n");
+        let mut recorder = SyncRecorder::new(mock_if);
+        assert_eq!(recorder.read_response_line(&mut buffer, 3).unwrap(), b"0\r\n");
+    }
+    #[test]
+    fn test_read_response_line_huge_max_len() {
+        // The timeout derived from the limit saturates instead of overflowing or wrapping
+        assert_eq!(response_timeout_for_len(usize::MAX, BaudRate::STANDARD), response_timeout_for_len(u16::MAX as usize, BaudRate::STANDARD));
+        assert!(response_timeout_for_len(1 << 32, BaudRate::STANDARD) > response_timeout_for_len(Command::MAX_RESPONSE_LEN, BaudRate::STANDARD));
+
+        let mut mock_if = MockInterface::new();
+        mock_if.stage_read_data(b"0+1\r\n");
+        let mut recorder = SyncRecorder::new(mock_if);
+        let mut buffer = [0u8; 32];
+        assert_eq!(recorder.read_response_line(&mut buffer, usize::MAX).unwrap(), b"0+1\r\n");
+    }
+    #[test]
     fn test_send_command_bytes_success() { /* ... as before ... */
         let mock_if = MockInterface::new();
         let mut recorder = SyncRecorder::new(mock_if.clone());
@@ -391,6 +747,16 @@ mod tests {
         assert_eq!(recorder.interface.get_call_count("flush"), 1);
     }
     #[test]
+    fn test_send_command_bytes_unsupported_baud_rate() {
+        let mut recorder = SyncRecorder::new(MockInterface::new());
+        recorder.config_mut().baud_rate = BaudRate::new(9600).unwrap();
+        let result = recorder.send_command_bytes(b"1M!");
+
+        assert!(matches!(result, Err(Sdi12Error::UnsupportedBaudRate(b)) if b.bits_per_second() == 9600));
+        assert_eq!(recorder.interface.write_pos, 0);
+        assert_eq!(recorder.known_frame_format, None);
+    }
+    #[test]
     fn test_check_and_send_break_needed() { /* ... as before ... */
          let mut mock_if = MockInterface::new();
         mock_if.current_time_us = 200_000;
@@ -416,4 +782,98 @@ mod tests {
         assert_eq!(recorder.interface.current_time_us, 50_000);
         assert_eq!(recorder.last_activity_time, Some(MockInstant(10_000)));
     }
-}
\ No newline at end of file
+    #[test]
+    fn test_check_and_send_break_after_clock_wrap() {
+        // A 16-bit ms counter wrapped: last activity at tick 65_530, now reads tick 20.
+        // Only ~26 ms passed, but the recorder can't know that, so it must break.
+        let mut mock_if = MockInterface::new();
+        mock_if.current_time_us = 20_000;
+        let mut recorder = SyncRecorder::new(mock_if);
+        recorder.last_activity_time = Some(MockInstant(65_530_000));
+
+        assert!(recorder.check_and_send_break().is_ok());
+        assert!(recorder.interface.break_sent);
+    }
+    #[test]
+    fn test_check_and_send_break_duration_config() {
+        let mut recorder = SyncRecorder::new(MockInterface::new());
+        assert!(recorder.check_and_send_break().is_ok());
+        assert_eq!(recorder.interface.break_duration, Some(recorder.config().break_duration));
+
+        // Legacy sensors: a longer break is passed through
+        let mut recorder = SyncRecorder::new(MockInterface::new());
+        recorder.config_mut().break_duration = Duration::from_millis(25);
+        assert!(recorder.check_and_send_break().is_ok());
+        assert_eq!(recorder.interface.break_duration, Some(Duration::from_millis(25)));
+
+        // Never shorter than the spec minimum
+        let mut recorder = SyncRecorder::new(MockInterface::new());
+        recorder.config_mut().break_duration = Duration::from_millis(1);
+        assert!(recorder.check_and_send_break().is_ok());
+        assert_eq!(recorder.interface.break_duration, Some(crate::common::timing::BREAK_DURATION_MIN));
+    }
+    #[test]
+    fn test_reset_bus_breaks_and_forgets_activity() {
+        let mut mock_if = MockInterface::new();
+        mock_if.current_time_us = 50_000;
+        let mut recorder = SyncRecorder::new(mock_if);
+        recorder.last_activity_time = Some(MockInstant(40_000)); // Recent enough to skip a break
+
+        assert!(recorder.reset_bus().is_ok());
+        assert!(recorder.interface.break_sent);
+        assert!(recorder.last_activity_time.is_none());
+        let marking = crate::common::timing::POST_BREAK_MARKING_MIN + crate::common::timing::SENSOR_SLEEP_MARKING_TIME;
+        assert!(recorder.interface.current_time_us as u128 >= 50_000 + marking.as_micros());
+    }
+
+    #[test]
+    fn test_check_and_send_break_line_not_marking() {
+        let mut mock_if = MockInterface::new();
+        mock_if.current_time_us = 50_000;
+        mock_if.line_marking = false; // e.g. another master used the bus
+        let mut recorder = SyncRecorder::new(mock_if);
+        recorder.last_activity_time = Some(MockInstant(10_000));
+
+        assert!(recorder.check_and_send_break().is_ok());
+        assert!(recorder.interface.break_sent);
+    }
+
+    /// A 16-bit microsecond counter that wraps every ~65 ms, like a small hardware timer.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+    struct WrapInstant(u16);
+    impl core::ops::Add<Duration> for WrapInstant { type Output = Self; fn add(self, rhs: Duration) -> Self { WrapInstant(self.0.wrapping_add(rhs.as_micros() as u16)) } }
+    impl core::ops::Sub<WrapInstant> for WrapInstant { type Output = Duration; fn sub(self, rhs: WrapInstant) -> Duration { Duration::from_micros(self.0.wrapping_sub(rhs.0).into()) } }
+
+    /// Becomes readable once `ready_after_us` have passed.
+    struct WrapInterface { clock_us: u64, ready_after_us: u64 }
+    impl Sdi12Timer for WrapInterface {
+        type Instant = WrapInstant;
+        fn delay_us(&mut self, us: u32) { self.clock_us += us as u64; }
+        fn delay_ms(&mut self, ms: u32) { self.clock_us += ms as u64 * 1000; }
+        fn now(&self) -> WrapInstant { WrapInstant(self.clock_us as u16) }
+    }
+    impl Sdi12Serial for WrapInterface {
+        type Error = MockCommError;
+        fn read_byte(&mut self) -> NbResult<u8, Self::Error> { if self.clock_us >= self.ready_after_us { Ok(b'0') } else { Err(nb::Error::WouldBlock) } }
+        fn write_byte(&mut self, _byte: u8) -> NbResult<(), Self::Error> { Ok(()) }
+        fn flush(&mut self) -> NbResult<(), Self::Error> { Ok(()) }
+        fn send_break(&mut self) -> NbResult<(), Self::Error> { Ok(()) }
+        fn set_config(&mut self, _config: FrameFormat) -> Result<(), Self::Error> { Ok(()) }
+    }
+
+    #[test]
+    fn test_blocking_io_timeout_across_timer_wrap() {
+        // 5 ms before the counter wraps, waiting 10 ms for a byte due in 8 ms
+        let start = u64::from(u16::MAX) - 5_000;
+        let mut recorder = SyncRecorder::new(WrapInterface { clock_us: start, ready_after_us: start + 8_000 });
+        let timeout = Duration::from_millis(10);
+        assert_eq!(recorder.execute_blocking_io_with_timeout(timeout, |iface| iface.read_byte()), Ok(b'0'));
+
+        // Nothing arrives: the timeout still runs its full length across the wrap
+        let mut recorder = SyncRecorder::new(WrapInterface { clock_us: start, ready_after_us: u64::MAX });
+        let result = recorder.execute_blocking_io_with_timeout(timeout, |iface| iface.read_byte());
+        assert_eq!(result, Err(Sdi12Error::Timeout));
+        let waited = recorder.interface.clock_us - start;
+        assert!((10_000..10_200).contains(&waited), "waited {waited} us");
+    }
+}
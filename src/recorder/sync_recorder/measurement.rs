@@ -0,0 +1,280 @@
+// src/recorder/sync_recorder/measurement.rs
+
+use super::{SyncRecorder, TraceEvent, TraceSink};
+use crate::common::{
+    address::Sdi12Addr,
+    command::{Command, MeasurementIndex},
+    error::Sdi12Error,
+    hal_traits::{Sdi12Instant, Sdi12Serial, Sdi12Timer},
+    response::MeasurementTiming,
+};
+#[cfg(feature = "alloc")]
+use crate::common::response::DataInfo;
+use super::io_helpers::{strip_line_terminator, Stopwatch};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::time::Duration;
+
+/// A measurement that has been started but whose data hasn't been read yet.
+///
+/// Returned by `SyncRecorder::begin_measure`. Holds the sensor's timing
+/// response and when it arrived, so progress can be shown while other work
+/// runs; nothing on the bus happens until `finish`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Measurement<I> {
+    timing: MeasurementTiming,
+    started: I,
+    ready_after: Duration,
+    /// Time since `started`, summed per reading so a wrapping clock works.
+    stopwatch: Stopwatch<I>,
+    crc_expected: bool,
+    service_request: bool,
+}
+
+impl<I: Sdi12Instant> Measurement<I> {
+    /// The sensor's `atttn` response.
+    pub fn timing(&self) -> MeasurementTiming {
+        self.timing
+    }
+
+    /// The address of the measuring sensor.
+    pub fn address(&self) -> Sdi12Addr {
+        self.timing.address
+    }
+
+    /// Number of values the sensor announced.
+    pub fn values_expected(&self) -> usize {
+        self.timing.values_count.into()
+    }
+
+    /// When the timing response arrived, on the recorder's clock.
+    pub fn started(&self) -> I {
+        self.started
+    }
+
+    /// How long after `started` the sensor said its data would be ready: its
+    /// `ttt` seconds.
+    pub fn ready_after(&self) -> Duration {
+        self.ready_after
+    }
+
+    /// Whether the sensor may end the measurement early with a service request
+    /// (see `Command::allows_service_request`). False for concurrent measurements.
+    pub fn allows_service_request(&self) -> bool {
+        self.service_request
+    }
+
+    /// Time left until the data is ready, zero once `ready_after` has passed.
+    /// `now` is the recorder's clock, e.g. `recorder.interface().now()`.
+    ///
+    /// The time is summed from the readings passed here, so on a clock that
+    /// wraps call this at least once per wrap period; a reading that missed a
+    /// wrap undercounts, which only makes `finish` wait longer.
+    pub fn remaining(&mut self, now: I) -> Duration {
+        self.ready_after.saturating_sub(self.stopwatch.update(now))
+    }
+
+    /// `remaining`, rounded up to whole seconds.
+    pub fn seconds_remaining(&mut self, now: I) -> u16 {
+        let remaining = self.remaining(now);
+        let seconds = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+        seconds.min(u16::MAX.into()) as u16
+    }
+
+    /// Waits until the data is ready, then reads the values with `aD0!`,
+    /// `aD1!`, ... (see `SyncRecorder::collect_all_data`).
+    ///
+    /// For `aM!`-style measurements the wait listens for the sensor's service
+    /// request (`a<CR><LF>`, "data ready") and ends as soon as it arrives,
+    /// otherwise after `ready_after`. A concurrent measurement, where other
+    /// sensors may be talking, never reads the bus before the data commands and
+    /// always waits the full `ready_after`.
+    ///
+    /// Data of a measurement started with a CRC command is CRC-checked. A
+    /// measurement announcing no values returns an empty `DataInfo` at once.
+    #[cfg(feature = "alloc")]
+    pub fn finish<IF, T>(mut self, recorder: &mut SyncRecorder<IF, T>) -> Result<DataInfo, Sdi12Error<IF::Error>>
+    where
+        IF: Sdi12Serial + Sdi12Timer<Instant = I>,
+        IF::Error: Debug,
+        T: TraceSink,
+    {
+        if self.timing.values_count == 0 {
+            return Ok(DataInfo { address: self.timing.address, values: Vec::new(), crc: None, crc_valid: None });
+        }
+        if self.service_request {
+            let remaining = self.remaining(recorder.interface.now());
+            recorder.await_service_request(self.timing.address, remaining)?;
+        } else {
+            let remaining = self.remaining(recorder.interface.now());
+            recorder.wait(remaining);
+        }
+        recorder.collect_data(self.timing.address, self.values_expected(), self.crc_expected)
+    }
+
+    /// Abandons the measurement instead of finishing it; see
+    /// `SyncRecorder::abort_measurement`.
+    pub fn abort<IF, T>(self, recorder: &mut SyncRecorder<IF, T>) -> Result<(), Sdi12Error<IF::Error>>
+    where
+        IF: Sdi12Serial + Sdi12Timer<Instant = I>,
+        IF::Error: Debug,
+        T: TraceSink,
+    {
+        recorder.abort_measurement(self.timing.address)
+    }
+}
+
+impl<IF, T> SyncRecorder<IF, T>
+where
+    IF: Sdi12Serial + Sdi12Timer,
+    IF::Error: Debug,
+    IF::Instant: Sdi12Instant,
+    T: TraceSink,
+{
+    /// Sends `aM!` (or `aMn!`, or `aMC!` per `RecorderConfig::crc_mode`) and
+    /// returns a handle to the running measurement.
+    ///
+    /// This is `measure` split in two: inspect the handle while the sensor
+    /// works, then call `Measurement::finish` to collect the data.
+    pub fn begin_measure(
+        &mut self,
+        address: Sdi12Addr,
+        index: MeasurementIndex,
+    ) -> Result<Measurement<IF::Instant>, Sdi12Error<IF::Error>> {
+        self.begin_measurement(&Command::StartMeasurement { address, index })
+    }
+
+    /// Sends `aC!` (or `aCn!`, `aCC!`) and returns a handle to the running
+    /// concurrent measurement.
+    ///
+    /// The sensor sends no service request for a concurrent measurement, so
+    /// `Measurement::finish` always waits the full `ttt` seconds.
+    pub fn begin_concurrent_measure(
+        &mut self,
+        address: Sdi12Addr,
+        index: MeasurementIndex,
+    ) -> Result<Measurement<IF::Instant>, Sdi12Error<IF::Error>> {
+        self.begin_measurement(&Command::StartConcurrentMeasurement { address, index })
+    }
+
+    /// Aborts a running measurement at `address` and checks the sensor is
+    /// listening again.
+    ///
+    /// Sends a break, which makes the sensor abandon any `aM!`, `aC!`, `aV!` or
+    /// `aH!` measurement in progress, then `a!`. Whatever the aborted
+    /// measurement would have produced is discarded: its data registers are
+    /// undefined afterwards and must not be read. Use this to cut a long `ttt`
+    /// short rather than waiting it out.
+    pub fn abort_measurement(&mut self, address: Sdi12Addr) -> Result<(), Sdi12Error<IF::Error>> {
+        self.send_break_sequence()?;
+        trace!("measurement at {} aborted", address);
+        // The break just sent keeps the line active, so `a!` follows without another one.
+        self.acknowledge(address)
+    }
+
+    /// Checks, without sending anything, whether a sensor has sent a service
+    /// request (`a<CR><LF>`, "data ready") and returns its address.
+    ///
+    /// Meant for interrupt-driven interfaces whose receive buffer fills between
+    /// transactions: an event loop can poll this instead of blocking in
+    /// `Measurement::finish`. Returns `None` at once if no byte is pending.
+    /// Otherwise the pending line is consumed; it only waits for the rest of
+    /// that line, and a line that isn't a service request (or an I/O error
+    /// while reading it) also gives `None`.
+    pub fn poll_service_request(&mut self) -> Option<Sdi12Addr> {
+        let first = self.interface.read_byte().ok()?;
+        let mut buffer = [0u8; Command::MAX_RESPONSE_LEN];
+        buffer[0] = first;
+        let mut len = 1;
+        while buffer[len - 1] != b'\n' {
+            if len == buffer.len() {
+                return None;
+            }
+            let timeout = self.config.inter_char_timeout;
+            buffer[len] = self.execute_blocking_io_with_timeout(timeout, |iface| iface.read_byte()).ok()?;
+            len += 1;
+        }
+        let line = &buffer[..len];
+        self.trace.event(TraceEvent::Rx(line));
+        if !line.ends_with(b"\r\n") && !self.config.accept_bare_lf {
+            return None;
+        }
+        let &[address] = strip_line_terminator(line) else {
+            return None;
+        };
+        let address = Sdi12Addr::new(address as char).ok()?;
+        trace!("service request from {}", address);
+        self.last_activity_time = Some(self.interface.now());
+        Some(address)
+    }
+
+    /// Listens for `address`'s service request for up to `budget`. Returns
+    /// whether it arrived; other lines and garbled bytes on the bus are skipped.
+    ///
+    /// Reads byte by byte so the time spent is summed in short steps (see
+    /// `Stopwatch`): the budget is the measurement's `ttt`, seconds that a
+    /// short-period timer wraps through many times.
+    #[cfg(feature = "alloc")]
+    fn await_service_request(
+        &mut self,
+        address: Sdi12Addr,
+        budget: Duration,
+    ) -> Result<bool, Sdi12Error<IF::Error>> {
+        let mut buffer = [0u8; Command::MAX_RESPONSE_LEN];
+        let mut len = 0;
+        let mut stopwatch = Stopwatch::start(self.interface.now());
+        let mut last_byte_at = Duration::ZERO;
+        loop {
+            let elapsed = stopwatch.update(self.interface.now());
+            if elapsed >= budget {
+                return Ok(false);
+            }
+            let byte = match self.interface.read_byte() {
+                Ok(byte) => byte,
+                Err(nb::Error::WouldBlock) => {
+                    self.interface.delay_us(100);
+                    continue;
+                }
+                Err(nb::Error::Other(e)) => return Err(Sdi12Error::Io(e)),
+            };
+            // A pause longer than the inter-character timeout, or a line longer than
+            // any response, ends whatever garbage came before.
+            if len == buffer.len() || (len > 0 && elapsed - last_byte_at > self.config.inter_char_timeout) {
+                len = 0;
+            }
+            last_byte_at = elapsed;
+            buffer[len] = byte;
+            len += 1;
+            if byte != b'\n' {
+                continue;
+            }
+            let line = &buffer[..core::mem::take(&mut len)];
+            self.trace.event(TraceEvent::Rx(line));
+            if (line.ends_with(b"\r\n") || self.config.accept_bare_lf)
+                && strip_line_terminator(line) == [address.as_char() as u8]
+            {
+                trace!("service request from {}", address);
+                self.last_activity_time = Some(self.interface.now());
+                return Ok(true);
+            }
+        }
+    }
+
+    pub(super) fn begin_measurement(
+        &mut self,
+        command: &Command,
+    ) -> Result<Measurement<IF::Instant>, Sdi12Error<IF::Error>> {
+        let command = self.apply_crc_mode(command.clone());
+        let timing = self.start_measurement(&command)?;
+        let started = self.interface.now();
+        Ok(Measurement {
+            timing,
+            started,
+            ready_after: Duration::from_secs(timing.time_seconds.into()),
+            stopwatch: Stopwatch::start(started),
+            crc_expected: command.is_crc_variant(),
+            service_request: command.allows_service_request(),
+        })
+    }
+}
@@ -1,23 +1,45 @@
 // src/recorder/sync_recorder/mod.rs
 
 // Declare the implementation detail modules
+mod config;
 mod io_helpers;
+mod measurement;
 mod protocol_helpers;
+mod response_buffer;
+mod trace;
 mod transaction;
 
 // Necessary imports for struct definition and public methods
 use crate::common::{
     address::Sdi12Addr,
-    command::Command,
+    command::{Command, DataIndex, IdentifyMeasurementCommand},
     error::Sdi12Error,
     hal_traits::{Sdi12Instant, Sdi12Serial, Sdi12Timer},
     // response::PayloadSlice, // Not needed directly in this file anymore
+    response::{MeasurementTiming, ResponseLines},
+    timing, BaudRate, FrameFormat,
 };
+#[cfg(feature = "alloc")]
+use crate::common::{
+    command::{ContinuousIndex, IdentifyParameterIndex, MeasurementBase, MeasurementIndex},
+    response::{DataInfo, MetadataInfo, PayloadSlice, Response},
+    types::Sdi12Value,
+};
+#[cfg(feature = "alloc")]
+use crate::recorder::transport::{collect_data_registers, Transport};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 use core::fmt::Debug;
-// use core::time::Duration;
+#[cfg(feature = "alloc")]
+use core::time::Duration;
+
+pub use config::{CrcFailurePolicy, CrcMode, RecorderConfig};
+pub use measurement::Measurement;
+pub use protocol_helpers::validate_response;
+pub use response_buffer::{ResponseBuffer, DEFAULT_RESPONSE_BUFFER_LEN};
+pub use trace::{NoTrace, TraceEvent, TraceSink};
 
-#[derive(Debug)]
-pub struct SyncRecorder<IF>
+pub struct SyncRecorder<IF, T = NoTrace>
 where
     IF: Sdi12Serial + Sdi12Timer,
     IF::Error: Debug,
@@ -25,9 +47,33 @@ where
 {
     interface: IF,
     last_activity_time: Option<IF::Instant>,
+    /// The frame format and baud rate last applied with `set_config` and
+    /// `set_baud_rate`; `None` until the first command, after either failed
+    /// and whenever the interface may have been reconfigured behind the
+    /// recorder's back.
+    known_frame_format: Option<(FrameFormat, BaudRate)>,
+    config: RecorderConfig,
+    trace: T,
+}
+
+impl<IF, T> Debug for SyncRecorder<IF, T>
+where
+    IF: Sdi12Serial + Sdi12Timer + Debug,
+    IF::Error: Debug,
+    IF::Instant: Sdi12Instant,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // The trace sink is usually a closure, which has no Debug.
+        f.debug_struct("SyncRecorder")
+            .field("interface", &self.interface)
+            .field("last_activity_time", &self.last_activity_time)
+            .field("known_frame_format", &self.known_frame_format)
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
 }
 
-// Implementation block for constructor and public methods
+// Constructors: a new recorder doesn't trace until `with_trace`.
 impl<IF> SyncRecorder<IF>
 where
     IF: Sdi12Serial + Sdi12Timer,
@@ -35,12 +81,95 @@ where
     IF::Instant: Sdi12Instant,
 {
     pub fn new(interface: IF) -> Self {
+        Self::with_config(interface, RecorderConfig::default())
+    }
+
+    /// Creates a recorder with non-default behaviour.
+    pub fn with_config(interface: IF, config: RecorderConfig) -> Self {
         SyncRecorder {
             interface,
             last_activity_time: None,
+            known_frame_format: None,
+            config,
+            trace: NoTrace,
+        }
+    }
+}
+
+// Implementation block for public methods
+impl<IF, T> SyncRecorder<IF, T>
+where
+    IF: Sdi12Serial + Sdi12Timer,
+    IF::Error: Debug,
+    IF::Instant: Sdi12Instant,
+    T: TraceSink,
+{
+    /// Returns the active configuration.
+    pub fn config(&self) -> &RecorderConfig {
+        &self.config
+    }
+
+    /// Returns the configuration for in-place changes.
+    pub fn config_mut(&mut self) -> &mut RecorderConfig {
+        &mut self.config
+    }
+
+    /// Returns the owned HAL interface.
+    pub fn interface(&self) -> &IF {
+        &self.interface
+    }
+
+    /// Returns the owned HAL interface, e.g. to reconfigure a pin or read a status
+    /// register. Bytes sent or read through it bypass the recorder's bookkeeping;
+    /// if the bus may have been disturbed, prefer `replace_interface` so the next
+    /// command starts with a break. The next command re-applies the 7E1 frame
+    /// format in case it was changed here.
+    pub fn interface_mut(&mut self) -> &mut IF {
+        self.known_frame_format = None;
+        &mut self.interface
+    }
+
+    /// Swaps in another interface and returns the previous one.
+    ///
+    /// This is the way to drive several buses from one recorder (and one
+    /// `RecorderConfig`): keep the idle interfaces outside and swap the one you
+    /// want to talk to in before each exchange. The recorder forgets its last
+    /// activity time, so the first command on the new bus always sends a break.
+    /// Alternatively, a recorder is small enough to simply keep one per bus.
+    pub fn replace_interface(&mut self, interface: IF) -> IF {
+        self.last_activity_time = None;
+        self.known_frame_format = None;
+        core::mem::replace(&mut self.interface, interface)
+    }
+
+    /// Passes every break, command, response and retry to `sink`, for
+    /// capturing raw bus traffic without the `log` feature (e.g. to an SD
+    /// card). Any `FnMut(TraceEvent<'_>)` closure is a sink; it may borrow
+    /// the buffer it writes to, which stays borrowed while the recorder lives.
+    ///
+    /// `sink` runs inside the transaction, between bus operations, so it
+    /// should return quickly. Replaces the sink set before; pass `NoTrace`
+    /// to stop tracing.
+    pub fn with_trace<S: TraceSink>(self, sink: S) -> SyncRecorder<IF, S> {
+        SyncRecorder {
+            interface: self.interface,
+            last_activity_time: self.last_activity_time,
+            known_frame_format: self.known_frame_format,
+            config: self.config,
+            trace: sink,
         }
     }
 
+    /// Returns the trace sink, e.g. to inspect what it collected.
+    pub fn trace_sink_mut(&mut self) -> &mut T {
+        &mut self.trace
+    }
+
+    /// Consumes the recorder and returns the HAL interface.
+    pub fn into_interface(self) -> IF {
+        self.interface
+    }
+
     // --- Public Blocking Methods ---
 
     pub fn acknowledge(&mut self, address: Sdi12Addr) -> Result<(), Sdi12Error<IF::Error>> {
@@ -60,36 +189,460 @@ where
     /// address/CRC validation, and returns the start/end indices of the validated payload
     /// within the provided read buffer on success.
     ///
+    /// Each attempt waits `default_response_timeout(command)` for the response to
+    /// start; use `execute_transaction_with_timeout` to override it.
+    ///
     /// # Arguments
     /// * `command`: The `sdi12::common::Command` to send.
     /// * `read_buffer`: A mutable byte slice to store the sensor's response line.
-    ///                  A size of ~96 bytes is recommended for standard commands.
+    ///   A size of ~96 bytes is recommended for standard commands.
     ///
     /// # Returns
     /// * `Ok((usize, usize))` containing the start and end indices of the payload within `read_buffer`.
-    /// * `Err(Sdi12Error)` on communication error, timeout, or invalid response framing/CRC.
-    pub fn send_command<'buf>( // Add lifetime marker for read_buffer
+    /// * `Err(Sdi12Error::RetriesExhausted)` if no attempt got a complete response.
+    /// * `Err(Sdi12Error)` on communication error or invalid response framing/CRC.
+    pub fn send_command(
         &mut self,
         command: &Command,
-        read_buffer: &'buf mut [u8],
+        read_buffer: &mut [u8],
     ) -> Result<(usize, usize), Sdi12Error<IF::Error>> {
         // Directly use the core transaction logic defined in transaction.rs
         self.execute_transaction(command, read_buffer)
     }
 
+    /// Sends `bytes` exactly as given and returns the raw response line.
+    ///
+    /// An escape hatch for vendor-specific (`aX...!`) commands and debugging.
+    /// The break/marking rules and the 7E1 frame format are applied as for
+    /// `send_command`, but nothing is formatted or retried, and the response is
+    /// only checked for `<CR><LF>` framing and printable characters: the returned
+    /// line (address, payload, CRC and the trailing `<CR><LF>`) is exactly what
+    /// the sensor sent.
+    ///
+    /// # Returns
+    /// * `Ok(&[u8])` borrowing the line from `read_buffer`.
+    /// * `Err(Sdi12Error::ResponseTimeout)` if no response started in time.
+    /// * `Err(Sdi12Error::BufferOverflow)` if the line doesn't fit in `read_buffer`.
+    /// * `Err(Sdi12Error::BusContention)` if the line is garbled.
+    pub fn send_raw<'buf>(
+        &mut self,
+        bytes: &[u8],
+        read_buffer: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Sdi12Error<IF::Error>> {
+        self.check_and_send_break()?;
+        self.send_command_bytes(bytes)?;
+        let line = self.read_response_line(read_buffer, Command::MAX_RESPONSE_LEN)?;
+        self.last_activity_time = Some(self.interface.now());
+        Ok(line)
+    }
+
+    /// Returns the bus to a known state after a collision, timeout or other error.
+    ///
+    /// Sends a break, then holds the line marking for
+    /// `timing::SENSOR_SLEEP_MARKING_TIME` (100 ms) so every sensor drops back to
+    /// standby, and forgets the last activity time so the next command starts
+    /// with a fresh break.
+    pub fn reset_bus(&mut self) -> Result<(), Sdi12Error<IF::Error>> {
+        self.send_break_sequence()?;
+        self.interface.delay_ms(timing::SENSOR_SLEEP_MARKING_TIME.as_millis() as u32);
+        self.last_activity_time = None;
+        Ok(())
+    }
+
+    /// Reads several back-to-back response lines without sending a command.
+    ///
+    /// Intended for sensors in continuous mode that emit data lines on their own.
+    /// Lines are read into `read_buffer` one after another until `max_lines` have
+    /// arrived or no new line starts before the response timeout.
+    ///
+    /// # Returns
+    /// * `Ok(ResponseLines)` iterating over each raw line (including `<CR><LF>`).
+    /// * `Err(Sdi12Error::ResponseTimeout)` if not even one line arrived.
+    /// * `Err(Sdi12Error::BufferOverflow)` if the lines don't fit in `read_buffer`.
+    pub fn read_lines<'buf>(
+        &mut self,
+        read_buffer: &'buf mut [u8],
+        max_lines: usize,
+    ) -> Result<ResponseLines<'buf>, Sdi12Error<IF::Error>> {
+        let lines = self.read_response_lines(read_buffer, max_lines)?;
+        self.last_activity_time = Some(self.interface.now());
+        Ok(lines)
+    }
+
+    /// Reads data registers `aD0!`, `aD1!`, ... until `expected_count` values
+    /// have arrived.
+    ///
+    /// Sensors are free to spread their values over the registers as they like,
+    /// so this keeps asking until the count is reached, a register answers with
+    /// no values (the sensor has nothing more), or `aD999!` has been read.
+    /// The result may therefore hold fewer values than expected. More than
+    /// expected (plus `RecorderConfig::extra_values_allowed`) is a protocol
+    /// violation and fails with `Sdi12Error::UnexpectedResponse`.
+    ///
+    /// `expected_count` is normally the `n` from the preceding `aM!`/`aC!` timing
+    /// response. Responses are not CRC-checked unless
+    /// `RecorderConfig::always_verify_crc_if_present` is set; after `aMC!`/`aCC!`
+    /// use `measure_crc` instead. A verified CRC is kept in `DataInfo::crc`.
+    #[cfg(feature = "alloc")]
+    pub fn collect_all_data(
+        &mut self,
+        address: Sdi12Addr,
+        expected_count: usize,
+    ) -> Result<DataInfo, Sdi12Error<IF::Error>> {
+        self.collect_data(address, expected_count, false)
+    }
+
+    /// Sends a command answered with `atttn` (`aM!`, `aMC!`, `aC!`, `aCC!`, `aV!`,
+    /// `aHA!`, `aHB!` and their indexed forms) and returns the parsed timing.
+    ///
+    /// The sensor's data is not read; follow up with `collect_all_data` once
+    /// `time_seconds` have passed, or use `measure`.
+    ///
+    /// # Returns
+    /// * `Ok(MeasurementTiming)` with the sensor's time estimate and value count.
+    /// * `Err(Sdi12Error::InvalidFormat)` if `command` isn't a measurement-style command.
+    /// * `Err(Sdi12Error::ParseError)` if the reply isn't a timing response with
+    ///   the count width `command` calls for (`Command::timing_count_digits`).
+    pub fn start_measurement(&mut self, command: &Command) -> Result<MeasurementTiming, Sdi12Error<IF::Error>> {
+        if !command.expects_timing_response() {
+            return Err(Sdi12Error::InvalidFormat);
+        }
+        let mut read_buffer = [0u8; Command::MAX_RESPONSE_LEN];
+        let timeout = self.default_response_timeout(command);
+        // Timing replies never carry a CRC, even to `aMC!`; only the data does.
+        let (start, end) = self.execute_transaction_expecting(command, &mut read_buffer, timeout, false)?;
+        MeasurementTiming::from_payload_for(command, &read_buffer[start..end])
+            .map_err(Sdi12Error::from_parse_error)
+    }
+
+    /// Asks how a measurement would go without starting it: sends `aIM!`
+    /// (or `aIMC!`, `aIC3!`, ... per `command`) and returns the `atttn` the
+    /// sensor would answer the measurement command with.
+    ///
+    /// The sensor doesn't measure, so there is nothing to wait for and no data
+    /// is read. The address is the one in `command`.
+    pub fn identify_measurement(
+        &mut self,
+        command: IdentifyMeasurementCommand,
+    ) -> Result<MeasurementTiming, Sdi12Error<IF::Error>> {
+        self.start_measurement(&Command::IdentifyMeasurement(command))
+    }
+
+    /// Runs a complete `aM!` measurement: starts it, waits the time the sensor
+    /// asked for, then reads the values with `aD0!`, `aD1!`, ...
+    ///
+    /// The wait ends early when the sensor sends its service request, at the
+    /// latest after the `ttt` seconds of the timing response. Use
+    /// `begin_measure` to watch the measurement's progress in between.
+    /// A sensor announcing no values (`a0000`) yields an empty `DataInfo` at
+    /// once, without waiting or sending `aD0!`.
+    ///
+    /// With `CrcMode::Always` this sends `aMC!` and checks the data CRCs.
+    #[cfg(feature = "alloc")]
+    pub fn measure(
+        &mut self,
+        address: Sdi12Addr,
+        index: MeasurementIndex,
+    ) -> Result<DataInfo, Sdi12Error<IF::Error>> {
+        self.run_measurement(&Command::StartMeasurement { address, index })
+    }
+
+    /// Like `measure`, but sends `aMC!` so every data response is CRC-checked.
+    ///
+    /// `CrcMode::Never` downgrades this to `aM!`.
+    #[cfg(feature = "alloc")]
+    pub fn measure_crc(
+        &mut self,
+        address: Sdi12Addr,
+        index: MeasurementIndex,
+    ) -> Result<DataInfo, Sdi12Error<IF::Error>> {
+        self.run_measurement(&Command::StartMeasurementCRC { address, index })
+    }
+
+    /// Sends `aC!` (or `aCn!`), waits `ttt` seconds, and reads the values.
+    ///
+    /// Like `measure`, but other sensors may be started while this one works;
+    /// see `begin_concurrent_measure` to do so.
+    #[cfg(feature = "alloc")]
+    pub fn measure_concurrent(
+        &mut self,
+        address: Sdi12Addr,
+        index: MeasurementIndex,
+    ) -> Result<DataInfo, Sdi12Error<IF::Error>> {
+        self.run_measurement(&Command::StartConcurrentMeasurement { address, index })
+    }
+
+    /// Sends `aV!`, waits `ttt` seconds, and reads the verification results
+    /// with `aD0!`, `aD1!`, ...
+    ///
+    /// The values are sensor-specific diagnostics (supply voltage, self-test
+    /// codes, ...) whose meaning is in the sensor's manual; a datalogger can
+    /// run this as a health check. Like `measure`, the wait ends early on a
+    /// service request.
+    #[cfg(feature = "alloc")]
+    pub fn verify_and_collect(&mut self, address: Sdi12Addr) -> Result<DataInfo, Sdi12Error<IF::Error>> {
+        self.run_measurement(&Command::StartVerification { address })
+    }
+
+    /// Runs `aM!`, `aM1!`, ... `aM9!` in turn and returns the data of each
+    /// measurement group, stopping at the first one that announces no values.
+    ///
+    /// Base SDI-12 has no way to ask which of `aM1!`..`aM9!` a sensor
+    /// supports, so this is a best-effort probe: it relies on the sensor
+    /// answering an unsupported group with `a0000`, as the spec asks. A sensor
+    /// that stays silent instead makes this fail with the timeout; use
+    /// `measure` per group when the supported set is known from the manual.
+    /// Groups are measured one after another, each with its full wait.
+    #[cfg(feature = "alloc")]
+    pub fn measure_all(&mut self, address: Sdi12Addr) -> Result<Vec<DataInfo>, Sdi12Error<IF::Error>> {
+        let mut groups = Vec::new();
+        let indices = core::iter::once(MeasurementIndex::Base).chain((1..=9).map(MeasurementIndex::Indexed));
+        for index in indices {
+            let data = self.measure(address, index)?;
+            if data.values.is_empty() {
+                break;
+            }
+            groups.push(data);
+        }
+        Ok(groups)
+    }
+
+    /// Measures several sensors concurrently and returns each one's data.
+    ///
+    /// Sends `aC!` (or `aCC!` per `RecorderConfig::crc_mode`) to every address
+    /// back to back, so all sensors measure at once, then reads each sensor's
+    /// values with `aD0!`, ... as soon as its own `ttt` has passed, earliest
+    /// first. The whole call takes about the longest `ttt` instead of their sum.
+    ///
+    /// Each address gets its own result, in the order of `addresses`: a sensor
+    /// that doesn't answer its `aC!` or whose data can't be read (timeout, CRC
+    /// or format error) gets that error rather than failing the others. Only an
+    /// I/O error of the interface aborts the call.
+    #[cfg(feature = "alloc")]
+    pub fn measure_concurrent_all(
+        &mut self,
+        addresses: &[Sdi12Addr],
+    ) -> Result<ConcurrentResults<IF::Error>, Sdi12Error<IF::Error>> {
+        let mut results = Vec::with_capacity(addresses.len());
+        let mut running = Vec::with_capacity(addresses.len());
+        for (position, &address) in addresses.iter().enumerate() {
+            match self.begin_concurrent_measure(address, MeasurementIndex::Base) {
+                Ok(measurement) => running.push((position, measurement)),
+                Err(Sdi12Error::Io(e)) => return Err(Sdi12Error::Io(e)),
+                Err(e) => {
+                    trace!("no measurement started at {}: {:?}", address, e);
+                    results.push((position, (address, Err(e))));
+                }
+            }
+        }
+
+        // Started back to back, so the shortest `ttt` is ready first.
+        running.sort_by_key(|(_, measurement)| measurement.ready_after());
+        for (position, measurement) in running {
+            let address = measurement.address();
+            match measurement.finish(self) {
+                Err(Sdi12Error::Io(e)) => return Err(Sdi12Error::Io(e)),
+                result => results.push((position, (address, result))),
+            }
+        }
+        results.sort_by_key(|(position, _)| *position);
+        Ok(results.into_iter().map(|(_, result)| result).collect())
+    }
+
+    /// Sends `aHA!` and returns the sensor's `atttnnn` timing.
+    ///
+    /// High-volume ASCII measurements may announce up to 999 values; once
+    /// `time_seconds` have passed, read them with `collect_high_volume_ascii`.
+    pub fn start_high_volume_ascii(&mut self, address: Sdi12Addr) -> Result<MeasurementTiming, Sdi12Error<IF::Error>> {
+        self.start_measurement(&Command::StartHighVolumeASCII { address })
+    }
+
+    /// Reads the data of a high-volume ASCII measurement with `aD0!`, `aD1!`, ...
+    ///
+    /// Works like `collect_all_data`, but every response is CRC-checked, since
+    /// data following `aHA!` always carries one. Each response may hold up to
+    /// 75 characters of values, so a 999-value measurement spans many registers.
+    #[cfg(feature = "alloc")]
+    pub fn collect_high_volume_ascii(
+        &mut self,
+        address: Sdi12Addr,
+        expected_count: usize,
+    ) -> Result<DataInfo, Sdi12Error<IF::Error>> {
+        self.collect_data(address, expected_count, true)
+    }
+
+    /// Sends `aDBn!` and reads the sensor's high-volume binary packet into
+    /// `read_buffer`.
+    ///
+    /// Returns the whole packet (address, size, data type, payload and CRC)
+    /// after checking its size, address and CRC; decode it with
+    /// `parse_binary_packet`. The buffer is the caller's, so no-alloc users
+    /// decide how much memory binary data gets: a payload may be up to
+    /// `BINARY_PACKET_MAX_PAYLOAD` (1000) bytes, and a buffer of
+    /// `BINARY_PACKET_MAX_PAYLOAD + BINARY_PACKET_OVERHEAD` holds any packet.
+    ///
+    /// The packet is read in 8N1 and the interface returned to 7E1 afterwards.
+    /// Missing or truncated packets are retried like `send_command`.
+    ///
+    /// # Returns
+    /// * `Ok(&[u8])` borrowing the packet from `read_buffer`.
+    /// * `Err(Sdi12Error::BufferOverflow)` if `read_buffer` is shorter than the
+    ///   packet (`packet_size + 6` bytes). The packet is still read to its end,
+    ///   so the bus is quiet again.
+    /// * `Err(Sdi12Error::CrcMismatch)` if the packet's CRC doesn't match.
+    pub fn send_binary_data<'buf>(
+        &mut self,
+        address: Sdi12Addr,
+        index: DataIndex,
+        read_buffer: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Sdi12Error<IF::Error>> {
+        let len = self.run_binary_transaction(&Command::SendBinaryData { address, index }, read_buffer)?;
+        Ok(&read_buffer[..len])
+    }
+
+    /// Reads `aR0!`..`aR9!` (or `aRC0!`.. per `RecorderConfig::crc_mode`) from a
+    /// sensor in continuous mode and returns its values.
+    #[cfg(feature = "alloc")]
+    pub fn read_continuous(
+        &mut self,
+        address: Sdi12Addr,
+        index: ContinuousIndex,
+    ) -> Result<DataInfo, Sdi12Error<IF::Error>> {
+        let command = self.apply_crc_mode(Command::ReadContinuous { address, index });
+        let mut read_buffer = [0u8; Command::MAX_RESPONSE_LEN];
+        let crc_expected = command.is_crc_variant();
+        let (start, end, crc) = self.execute_data_transaction(&command, &mut read_buffer, crc_expected)?;
+        let mut values = Vec::new();
+        push_values(&mut values, &read_buffer[start..end])?;
+        Ok(DataInfo { address, values, crc: crc.map(|crc| crc.value), crc_valid: crc.map(|crc| crc.valid) })
+    }
+
+    /// Sends a parameter metadata query (`aIM_001!`, `aIMC_001!`, ...) and
+    /// returns the parsed `,field,field;` reply.
+    ///
+    /// The reply's CRC is checked for the CRC variants of `base` (`IMC`, `ICC`,
+    /// `IRC`) and kept in `MetadataInfo::crc`.
+    #[cfg(feature = "alloc")]
+    pub fn identify_parameter(
+        &mut self,
+        address: Sdi12Addr,
+        base: MeasurementBase,
+        param_index: IdentifyParameterIndex,
+    ) -> Result<MetadataInfo, Sdi12Error<IF::Error>> {
+        let command = Command::identify_parameter(address, base, param_index);
+        let mut buffer: ResponseBuffer = ResponseBuffer::new();
+        match buffer.response(self, &command)? {
+            Response::Metadata(metadata) => Ok(metadata),
+            _ => Err(Sdi12Error::UnexpectedResponse),
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    fn run_measurement(&mut self, command: &Command) -> Result<DataInfo, Sdi12Error<IF::Error>> {
+        self.begin_measurement(command)?.finish(self)
+    }
+
+    /// Switches `command` to the variant `RecorderConfig::crc_mode` asks for.
+    fn apply_crc_mode(&self, command: Command) -> Command {
+        match self.config.crc_mode {
+            CrcMode::Never => command.without_crc(),
+            CrcMode::WhenRequested => command,
+            CrcMode::Always => command.with_crc(),
+        }
+    }
+
+    /// Blocks for `duration`, in steps short enough for `delay_ms`'s `u32`.
+    #[cfg(feature = "alloc")]
+    fn wait(&mut self, duration: Duration) {
+        let mut remaining_ms = duration.as_millis();
+        while remaining_ms > 0 {
+            let step = remaining_ms.min(u32::MAX as u128) as u32;
+            self.interface.delay_ms(step);
+            remaining_ms -= step as u128;
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    fn collect_data(
+        &mut self,
+        address: Sdi12Addr,
+        expected_count: usize,
+        crc_expected: bool,
+    ) -> Result<DataInfo, Sdi12Error<IF::Error>> {
+        let extra_allowed = self.config.extra_values_allowed;
+        let mut registers = DataRegisters { recorder: self, crc_expected, crc: None, crc_valid: None };
+        let values = collect_data_registers(&mut registers, address, expected_count, extra_allowed)?;
+        let DataRegisters { crc, crc_valid, .. } = registers;
+        Ok(DataInfo { address, values, crc, crc_valid })
+    }
+
     // TODO: Implement other specific public methods like send_identification etc.
 
 } // End impl SyncRecorder
 
+/// The recorder as a `Transport` for `aDn!` registers: data is read with the
+/// measurement's CRC setting and the CRCs seen are kept for `DataInfo`.
+#[cfg(feature = "alloc")]
+struct DataRegisters<'r, IF, T>
+where
+    IF: Sdi12Serial + Sdi12Timer,
+    IF::Error: Debug,
+    IF::Instant: Sdi12Instant,
+{
+    recorder: &'r mut SyncRecorder<IF, T>,
+    crc_expected: bool,
+    /// CRC of the last register read.
+    crc: Option<u16>,
+    /// Whether every register's CRC matched; one failed register marks the whole measurement.
+    crc_valid: Option<bool>,
+}
+
+#[cfg(feature = "alloc")]
+impl<IF, T> Transport for DataRegisters<'_, IF, T>
+where
+    IF: Sdi12Serial + Sdi12Timer,
+    IF::Error: Debug,
+    IF::Instant: Sdi12Instant,
+    T: TraceSink,
+{
+    type Error = IF::Error;
+
+    fn transaction<'buf>(
+        &mut self,
+        command: &Command,
+        buffer: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Sdi12Error<IF::Error>> {
+        let (start, end, register_crc) = self.recorder.execute_data_transaction(command, buffer, self.crc_expected)?;
+        if start < end {
+            self.crc = register_crc.map(|crc| crc.value);
+            self.crc_valid = match (self.crc_valid, register_crc.map(|crc| crc.valid)) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (previous, current) => current.or(previous),
+            };
+        }
+        Ok(&buffer[start..end])
+    }
+}
+
+/// Each sensor's outcome in `measure_concurrent_all`.
+#[cfg(feature = "alloc")]
+type ConcurrentResults<E> = Vec<(Sdi12Addr, Result<DataInfo, Sdi12Error<E>>)>;
+
+/// Parses a `<values>` payload and appends the values to `values`.
+#[cfg(feature = "alloc")]
+fn push_values<E: Debug>(values: &mut Vec<Sdi12Value>, payload: &[u8]) -> Result<(), Sdi12Error<E>> {
+    values.extend(PayloadSlice(payload).parse_values().map_err(Sdi12Error::from_parse_error)?);
+    Ok(())
+}
+
 // --- Unit Tests ---
 #[cfg(test)]
 mod tests {
    // Minimal tests for construction remain. Tests for acknowledge/send_command
    // behavior are better placed with execute_transaction tests as they rely heavily on it.
     use super::*;
-    use crate::common::address::Sdi12Addr; // Keep for potential future tests
-    use crate::common::hal_traits::{Sdi12Instant, Sdi12Serial, Sdi12Timer};
-    use crate::common::{FrameFormat, Sdi12Error};
+    use crate::common::hal_traits::{Sdi12Serial, Sdi12Timer};
+    use crate::common::FrameFormat;
     use core::time::Duration;
     use nb::Result as NbResult;
     use nb;
@@ -100,15 +653,30 @@ mod tests {
     impl core::ops::Sub<MockInstant> for MockInstant { type Output = Duration; fn sub(self, rhs: MockInstant) -> Duration { Duration::from_micros(self.0.saturating_sub(rhs.0)) } }
     #[derive(Debug, Copy, Clone, PartialEq, Eq)]
     struct MockCommError;
-    #[derive(Clone)]
-    struct MockInterface;
+    #[derive(Clone, Debug, PartialEq)]
+    struct MockInterface(u8); // Bus id
     impl Sdi12Timer for MockInterface { type Instant = MockInstant; fn delay_us(&mut self, _us: u32) {} fn delay_ms(&mut self, _ms: u32) {} fn now(&self) -> Self::Instant { MockInstant(0) } }
     impl Sdi12Serial for MockInterface { type Error = MockCommError; fn read_byte(&mut self) -> NbResult<u8, Self::Error> { Err(nb::Error::WouldBlock) } fn write_byte(&mut self, _byte: u8) -> NbResult<(), Self::Error> { Ok(()) } fn flush(&mut self) -> NbResult<(), Self::Error> { Ok(()) } fn send_break(&mut self) -> NbResult<(), Self::Error> { Ok(()) } fn set_config(&mut self, _config: FrameFormat) -> Result<(), Self::Error> { Ok(()) } }
 
     #[test]
     fn test_recorder_construction_in_mod() {
-        let mock_interface = MockInterface;
+        let mock_interface = MockInterface(0);
         let recorder = SyncRecorder::new(mock_interface);
         assert!(recorder.last_activity_time.is_none());
+        assert_eq!(*recorder.config(), RecorderConfig::default());
+        assert!(!recorder.config().always_verify_crc_if_present);
+    }
+
+    #[test]
+    fn test_interface_access_and_swap() {
+        let mut recorder = SyncRecorder::new(MockInterface(1));
+        recorder.interface_mut().0 = 2;
+        assert_eq!(recorder.interface(), &MockInterface(2));
+
+        recorder.last_activity_time = Some(MockInstant(5));
+        let previous = recorder.replace_interface(MockInterface(3));
+        assert_eq!(previous, MockInterface(2));
+        assert!(recorder.last_activity_time.is_none()); // Next command on bus 3 breaks first
+        assert_eq!(recorder.into_interface(), MockInterface(3));
     }
 }
\ No newline at end of file
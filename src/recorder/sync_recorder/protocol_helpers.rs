@@ -1,99 +1,183 @@
 // src/recorder/sync_recorder/protocol_helpers.rs
 
-use super::SyncRecorder;
+use super::{RecorderConfig, SyncRecorder, TraceSink};
 use crate::common::{
     address::Sdi12Addr,
-    command::{Command, IdentifyMeasurementParameterCommand}, // Import Command and sub-enums
+    command::Command,
     error::Sdi12Error,
     hal_traits::{Sdi12Instant, Sdi12Serial, Sdi12Timer},
-    response::PayloadSlice, // Only needed for test helper function now
+    response::{CrcHandling, ResponseParseError},
 };
 use core::fmt::Debug;
 
-impl<IF> SyncRecorder<IF>
+impl<IF, T> SyncRecorder<IF, T>
 where
     IF: Sdi12Serial + Sdi12Timer,
     IF::Error: Debug,
     IF::Instant: Sdi12Instant,
+    T: TraceSink,
 {
     /// Parses the raw response line, checking address, CRC (if needed),
-    /// and returns the start/end indices of the payload within the original line buffer.
+    /// and returns the start/end indices of the payload within the original line buffer,
+    /// plus the CRC that was checked and stripped (`None` if there was none).
+    ///
+    /// `crc_expected` is usually `command_requests_crc(original_cmd)`, but the
+    /// caller may know better, e.g. for `aD0!` after `aMC!`. With
+    /// `accept_crc_mismatch` a bad CRC is stripped and reported with
+    /// `CheckedCrc::valid` unset instead of failing.
+    pub(super) fn check_response_payload(
+        &mut self,
+        response_line: &[u8],
+        original_cmd: &Command,
+        crc_expected: bool,
+        accept_crc_mismatch: bool,
+    ) -> Result<(usize, usize, Option<CheckedCrc>), Sdi12Error<IF::Error>> {
+        payload_bounds(response_line, original_cmd, crc_expected, &self.config, accept_crc_mismatch)
+    }
+
+    /// `check_response_payload` rejecting CRC mismatches, payload bounds only.
+    #[cfg(test)]
     pub(super) fn process_response_payload(
         &mut self,
         response_line: &[u8],
         original_cmd: &Command,
-    ) -> Result<(usize, usize), Sdi12Error<IF::Error>> { // Return (start, end) indices
+        crc_expected: bool,
+    ) -> Result<(usize, usize), Sdi12Error<IF::Error>> {
+        self.check_response_payload(response_line, original_cmd, crc_expected, false).map(|(start, end, _)| (start, end))
+    }
+}
 
-        if response_line.len() < 3 { // Must have at least address + CR + LF
-            return Err(Sdi12Error::InvalidFormat);
-        }
+/// A CRC found at the end of a response line and stripped from its payload.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(super) struct CheckedCrc {
+    /// The CRC as received.
+    pub(super) value: u16,
+    /// Whether it matched the line; only `false` when mismatches are accepted.
+    pub(super) valid: bool,
+}
 
-        // 1. Check and strip <CR><LF>
-        let crlf_len = 2;
-        let data_end_idx = response_line.len() - crlf_len;
-        if response_line[data_end_idx..] != [b'\r', b'\n'] {
-            return Err(Sdi12Error::InvalidFormat); // Missing or incorrect terminator
-        }
-        let response_without_crlf = &response_line[..data_end_idx];
+/// Checks a complete response line (including `<CR><LF>`) to `cmd` the way the
+/// recorder does: line ending, address and the CRC where the reply carries one.
+///
+/// Which replies carry a CRC is decided as in `parse_response_for`: `aRCn!`
+/// and the CRC metadata queries must end in one, timing replies (`aMC!`
+/// included) must not, and `aDn!` data is checked if it ends in one, since
+/// the command alone doesn't say whether the measurement asked for a CRC.
+///
+/// Needs no recorder or interface, so captured byte streams can be checked
+/// offline. The checks are otherwise the spec-strict ones of
+/// `RecorderConfig::default()`.
+pub fn validate_response(cmd: &Command, raw: &[u8]) -> Result<(), Sdi12Error<()>> {
+    let crc = CrcHandling::for_command(cmd);
+    let config = RecorderConfig { always_verify_crc_if_present: crc == CrcHandling::Detect, ..RecorderConfig::default() };
+    payload_bounds(raw, cmd, crc == CrcHandling::Expected, &config, false).map(|_| ())
+}
 
-        if response_without_crlf.is_empty() { // Needs at least address
-             return Err(Sdi12Error::InvalidFormat);
-        }
+/// The body of `check_response_payload`, shared with `validate_response`.
+fn payload_bounds<E: Debug>(
+    response_line: &[u8],
+    original_cmd: &Command,
+    crc_expected: bool,
+    config: &RecorderConfig,
+    accept_crc_mismatch: bool,
+) -> Result<(usize, usize, Option<CheckedCrc>), Sdi12Error<E>> {
+    if response_line.is_empty() {
+        return Err(Sdi12Error::from_parse_error(ResponseParseError::EmptyInput));
+    }
 
-        // 2. Check address
-        let received_addr_char = response_without_crlf[0] as char;
-        let expected_addr = match original_cmd {
-             Command::AddressQuery => None, // Special case, accept any valid address
-             _ => Some(original_cmd.address()),
-        };
+    // 1. Check and strip <CR><LF> (or a bare <LF> if configured)
+    let response_without_crlf = response_line
+        .strip_suffix(b"\r\n")
+        .or_else(|| response_line.strip_suffix(b"\n").filter(|_| config.accept_bare_lf))
+        .ok_or(Sdi12Error::from_parse_error(ResponseParseError::MissingCrLf))?;
 
-        let received_addr = Sdi12Addr::new(received_addr_char)
-            .map_err(|_| Sdi12Error::InvalidAddress(received_addr_char))?; // Map error
+    if response_without_crlf.is_empty() { // Needs at least address
+         return Err(Sdi12Error::from_parse_error(ResponseParseError::TooShort));
+    }
+
+    // 2. Check address
+    let received_addr_char = response_without_crlf[0] as char;
+    let expected_addr = match original_cmd {
+         Command::AddressQuery => None, // Special case, accept any valid address
+         _ => Some(original_cmd.address()),
+    };
+
+    let received_addr = Sdi12Addr::new(received_addr_char)
+        .map_err(|_| Sdi12Error::InvalidAddress(received_addr_char))?; // Map error
+
+    if let Some(expected) = expected_addr {
+         if received_addr != expected {
+            return Err(Sdi12Error::UnexpectedResponse);
+         }
+    }
+
+    // 3. Determine payload boundaries and process CRC if needed
+    let payload_start_index = 1; // Payload starts after the address byte
+    let mut payload_end_index = response_without_crlf.len(); // End is before CRLF initially
 
-        if let Some(expected) = expected_addr {
-             if received_addr != expected {
-                return Err(Sdi12Error::UnexpectedResponse);
-             }
+    // Optionally verify a CRC the sensor appended on its own, on replies that can
+    // carry one. Text such as `aI!`'s may end in three letters that only look like it.
+    let crc_present = crc_expected
+        || (config.always_verify_crc_if_present
+            && may_carry_crc(original_cmd)
+            && crate::common::crc::looks_like_crc_ascii(response_without_crlf));
+
+    let mut checked_crc = None;
+    if crc_present {
+         // TODO: Handle binary CRC case differently if needed
+        let crc_len = 3; // Assuming ASCII CRC
+        if response_without_crlf.len() < payload_start_index + crc_len { // Need address + CRC
+            return Err(Sdi12Error::from_parse_error(ResponseParseError::TooShort));
+        }
+        // CRC verification uses the slice *including* address but *excluding* CRLF
+        match crate::common::crc::verify_response_crc_ascii(response_without_crlf) {
+            Ok(()) => {
+                let crc_bytes = &response_without_crlf[response_without_crlf.len() - crc_len..];
+                let value = crate::common::crc::try_decode_crc_ascii(crc_bytes)
+                    .ok_or(Sdi12Error::from_parse_error(ResponseParseError::TooShort))?;
+                checked_crc = Some(CheckedCrc { value, valid: true });
+            }
+            Err(Sdi12Error::CrcMismatch { expected, .. }) if accept_crc_mismatch => {
+                trace!("CRC mismatch accepted in {}", response_without_crlf.escape_ascii());
+                checked_crc = Some(CheckedCrc { value: expected, valid: false });
+            }
+            Err(e @ Sdi12Error::CrcMismatch { .. }) => return Err(e), // Pass through CRC error
+            // Only other failure is a short buffer
+            Err(_) => return Err(Sdi12Error::from_parse_error(ResponseParseError::TooShort)),
         }
+         // Adjust payload end index to be before the CRC
+         payload_end_index = response_without_crlf.len() - crc_len;
+    }
 
-        // 3. Determine payload boundaries and process CRC if needed
-        let payload_start_index = 1; // Payload starts after the address byte
-        let mut payload_end_index = response_without_crlf.len(); // End is before CRLF initially
+    // Return the calculated indices relative to the start of the original response_line buffer
+    Ok((payload_start_index, payload_end_index, checked_crc))
+}
 
-        let crc_expected = matches!(
-            original_cmd,
+/// Whether the reply to `cmd` can end in a CRC at all: data (`aDn!`, `aRn!`)
+/// and the metadata parameter queries. Timing, identification and extended
+/// replies never do.
+fn may_carry_crc(cmd: &Command) -> bool {
+    cmd.expects_data_response() || matches!(cmd, Command::IdentifyMeasurementParameter(_))
+}
+
+/// Whether responses to `cmd` are expected to end in a CRC.
+///
+/// True for `aRCn!` and the `IMC`/`ICC`/`IRC` parameter queries, whose replies
+/// carry one, and for the measurement commands (`aMC!`, `aCC!`, `aHA!`, `aHB!`)
+/// whose *data* does; their timing reply has none, so `start_measurement`
+/// reads it without. Other metadata queries, high-volume ones included,
+/// answer without a CRC.
+pub(super) fn command_requests_crc(cmd: &Command) -> bool {
+    match cmd {
+        Command::IdentifyMeasurementParameter(parameter) => parameter.is_crc_variant(),
+        _ => matches!(
+            cmd,
             Command::StartMeasurementCRC { .. }
                 | Command::StartConcurrentMeasurementCRC { .. }
                 | Command::ReadContinuousCRC { .. }
                 | Command::StartHighVolumeASCII { .. }
                 | Command::StartHighVolumeBinary { .. }
-                | Command::IdentifyMeasurementParameter(
-                    IdentifyMeasurementParameterCommand::MeasurementCRC { .. }
-                    | IdentifyMeasurementParameterCommand::ConcurrentMeasurementCRC { .. }
-                    | IdentifyMeasurementParameterCommand::ReadContinuousCRC { .. }
-                    | IdentifyMeasurementParameterCommand::HighVolumeASCII { .. }
-                    | IdentifyMeasurementParameterCommand::HighVolumeBinary { .. }
-                    )
-        );
-
-        if crc_expected {
-             // TODO: Handle binary CRC case differently if needed
-            let crc_len = 3; // Assuming ASCII CRC
-            if response_without_crlf.len() < payload_start_index + crc_len { // Need address + CRC
-                return Err(Sdi12Error::InvalidFormat);
-            }
-            // CRC verification uses the slice *including* address but *excluding* CRLF
-            crate::common::crc::verify_response_crc_ascii(response_without_crlf)
-                 .map_err(|e| match e {
-                     Sdi12Error::CrcMismatch{..} => e, // Pass through CRC error
-                     _ => Sdi12Error::InvalidFormat,    // Other verification errors become InvalidFormat
-                 })?;
-             // Adjust payload end index to be before the CRC
-             payload_end_index = response_without_crlf.len() - crc_len;
-        }
-
-        // Return the calculated indices relative to the start of the original response_line buffer
-        Ok((payload_start_index, payload_end_index))
+        ),
     }
 }
 
@@ -135,7 +219,7 @@ mod tests {
         let mut recorder = SyncRecorder::new(mock_if);
         let line = b"0\r\n";
         let cmd = Command::AcknowledgeActive{ address: addr('0') };
-        let result = recorder.process_response_payload(line, &cmd);
+        let result = recorder.process_response_payload(line, &cmd, command_requests_crc(&cmd));
         assert!(result.is_ok());
         let (start, end) = result.unwrap();
         assert_eq!((start, end), (1, 1)); // Indices for empty payload
@@ -147,7 +231,7 @@ mod tests {
         let mut recorder = SyncRecorder::new(mock_if);
         let line = b"1+12.3-45\r\n"; // 11 bytes total
         let cmd = Command::SendData{ address: addr('1'), index: DataIndex::new(0).unwrap() };
-        let result = recorder.process_response_payload(line, &cmd);
+        let result = recorder.process_response_payload(line, &cmd, command_requests_crc(&cmd));
          assert!(result.is_ok());
         let (start, end) = result.unwrap();
         assert_eq!(start, 1);
@@ -160,7 +244,7 @@ mod tests {
         let mut recorder = SyncRecorder::new(mock_if);
         let line = b"0+3.14OqZ\r\n"; // 10 bytes total
         let cmd = Command::StartMeasurementCRC{ address: addr('0'), index: MeasurementIndex::Base };
-        let result = recorder.process_response_payload(line, &cmd);
+        let result = recorder.process_response_payload(line, &cmd, command_requests_crc(&cmd));
         assert!(result.is_ok());
         let (start, end) = result.unwrap();
         assert_eq!(start, 1);
@@ -173,8 +257,40 @@ mod tests {
         let mut recorder = SyncRecorder::new(mock_if);
         let line = b"0+3.14OqX\r\n"; // Bad CRC
         let cmd = Command::StartMeasurementCRC{ address: addr('0'), index: MeasurementIndex::Base };
-        let result = recorder.process_response_payload(line, &cmd);
+        let result = recorder.process_response_payload(line, &cmd, command_requests_crc(&cmd));
         assert!(matches!(result, Err(Sdi12Error::CrcMismatch { .. })));
+    }
+    #[test]
+    fn test_process_response_payload_unrequested_crc() {
+        let line = b"0+3.14OqZ\r\n";
+        let cmd = Command::SendData{ address: addr('0'), index: DataIndex::new(0).unwrap() };
+
+        // Spec-strict default leaves the CRC in the payload
+        let mut recorder = SyncRecorder::new(MockInterface);
+        let (start, end) = recorder.process_response_payload(line, &cmd, command_requests_crc(&cmd)).unwrap();
+        assert_eq!(slice_from_indices(line, start, end).as_bytes(), b"+3.14OqZ");
+
+        // Opportunistic verification strips a valid CRC...
+        recorder.config_mut().always_verify_crc_if_present = true;
+        let (start, end) = recorder.process_response_payload(line, &cmd, command_requests_crc(&cmd)).unwrap();
+        assert_eq!(slice_from_indices(line, start, end).as_bytes(), b"+3.14");
+
+        // ...still rejects a bad one...
+        assert!(matches!(recorder.process_response_payload(b"0+3.14OqX\r\n", &cmd, command_requests_crc(&cmd)), Err(Sdi12Error::CrcMismatch { .. })));
+
+        // ...and ignores responses without one.
+        let (start, end) = recorder.process_response_payload(b"0+3.14\r\n", &cmd, command_requests_crc(&cmd)).unwrap();
+        assert_eq!((start, end), (1, 6));
+    }
+    #[test]
+    fn test_detected_crc_only_on_replies_that_carry_one() {
+        // An identification whose serial number ends in three letters after a '-'
+        let line = b"013VENDOR  MODEL 1.0SN-ABC\r\n";
+        let cmd = Command::SendIdentification { address: addr('0') };
+        let mut recorder = SyncRecorder::new(MockInterface);
+        recorder.config_mut().always_verify_crc_if_present = true;
+        let (start, end) = recorder.process_response_payload(line, &cmd, command_requests_crc(&cmd)).unwrap();
+        assert_eq!(slice_from_indices(line, start, end).as_bytes(), b"13VENDOR  MODEL 1.0SN-ABC");
     }
      #[test]
     fn test_process_response_payload_wrong_address() {
@@ -182,7 +298,7 @@ mod tests {
         let mut recorder = SyncRecorder::new(mock_if);
         let line = b"1+12.3\r\n";
         let cmd = Command::SendData{ address: addr('0'), index: DataIndex::new(0).unwrap() }; // Sent to 0
-        let result = recorder.process_response_payload(line, &cmd);
+        let result = recorder.process_response_payload(line, &cmd, command_requests_crc(&cmd));
         assert!(matches!(result, Err(Sdi12Error::UnexpectedResponse)));
     }
     #[test]
@@ -191,23 +307,70 @@ mod tests {
         let mut recorder = SyncRecorder::new(mock_if);
         let line = b"5\r\n"; // Response from sensor 5
         let cmd = Command::AddressQuery; // Query command
-        let result = recorder.process_response_payload(line, &cmd);
+        let result = recorder.process_response_payload(line, &cmd, command_requests_crc(&cmd));
         assert!(result.is_ok());
         let (start, end) = result.unwrap();
         assert_eq!((start, end), (1, 1)); // Empty payload
         assert_eq!(slice_from_indices(line, start, end).as_bytes(), b"");
+    }
+    #[test]
+    fn test_validate_response_offline() {
+        let data = Command::SendData{ address: addr('0'), index: DataIndex::new(0).unwrap() };
+        let measure_crc = Command::StartMeasurementCRC{ address: addr('0'), index: MeasurementIndex::Base };
+        assert_eq!(validate_response(&data, b"0+1.5\r\n"), Ok(()));
+        assert_eq!(validate_response(&data, b"0+3.14OqZ\r\n"), Ok(()));
+        assert!(matches!(validate_response(&data, b"0+3.14OqX\r\n"), Err(Sdi12Error::CrcMismatch { .. })));
+        // Timing replies never carry a CRC, whatever the command asked for the data
+        assert_eq!(validate_response(&measure_crc, b"00012\r\n"), Ok(()));
+        assert_eq!(validate_response(&Command::StartHighVolumeASCII { address: addr('0') }, b"0000102\r\n"), Ok(()));
+        assert_eq!(validate_response(&data, b"1+1.5\r\n"), Err(Sdi12Error::UnexpectedResponse));
+        assert_eq!(validate_response(&data, b"0+1.5\n"), Err(Sdi12Error::ParseError(ResponseParseError::MissingCrLf)));
+    }
+    #[test]
+    fn test_metadata_crc_follows_command() {
+        use crate::common::command::{IdentifyParameterIndex, MeasurementBase};
+        let param = IdentifyParameterIndex::new(1).unwrap();
+        let query = |base| Command::identify_parameter(addr('0'), base, param);
+        let line = b"0,TA,C;\r\n";
+
+        for base in [MeasurementBase::Measurement(MeasurementIndex::Base), MeasurementBase::HighVolumeASCII, MeasurementBase::HighVolumeBinary] {
+            assert!(!command_requests_crc(&query(base)), "{base:?}");
+            assert_eq!(validate_response(&query(base), line), Ok(()));
+        }
+        for base in [MeasurementBase::MeasurementCRC(MeasurementIndex::Base), MeasurementBase::ConcurrentCRC(MeasurementIndex::Base)] {
+            assert!(command_requests_crc(&query(base)), "{base:?}");
+            // Without its CRC the ",C;" tail fails the check
+            assert!(validate_response(&query(base), line).is_err());
+        }
+    }
+    #[test]
+    fn test_process_response_payload_bare_lf() {
+        let mut recorder = SyncRecorder::new(MockInterface);
+        let cmd = Command::SendData{ address: addr('0'), index: DataIndex::new(0).unwrap() };
+        assert_eq!(recorder.process_response_payload(b"0+1\n", &cmd, false), Err(Sdi12Error::ParseError(ResponseParseError::MissingCrLf)));
+
+        recorder.config_mut().accept_bare_lf = true;
+        assert_eq!(recorder.process_response_payload(b"0+1\n", &cmd, false), Ok((1, 3)));
+        assert_eq!(recorder.process_response_payload(b"0+1\r\n", &cmd, false), Ok((1, 3)));
+        assert_eq!(recorder.process_response_payload(b"0\n", &cmd, false), Ok((1, 1)));
     }
      #[test]
     fn test_process_response_payload_invalid_format() {
         let mock_if = MockInterface;
         let mut recorder = SyncRecorder::new(mock_if);
         let cmd = Command::AcknowledgeActive{ address: addr('0') };
+        let parse_err = |e| Err(Sdi12Error::ParseError(e));
+
+        assert_eq!(recorder.process_response_payload(b"", &cmd, command_requests_crc(&cmd)), parse_err(ResponseParseError::EmptyInput));
+        assert_eq!(recorder.process_response_payload(b"0", &cmd, command_requests_crc(&cmd)), parse_err(ResponseParseError::MissingCrLf));
+        assert_eq!(recorder.process_response_payload(b"0\r", &cmd, command_requests_crc(&cmd)), parse_err(ResponseParseError::MissingCrLf));
+        assert_eq!(recorder.process_response_payload(b"\r\n", &cmd, command_requests_crc(&cmd)), parse_err(ResponseParseError::TooShort)); // No address
 
-        assert!(matches!(recorder.process_response_payload(b"0", &cmd), Err(Sdi12Error::InvalidFormat))); // Too short
-        assert!(matches!(recorder.process_response_payload(b"0\r", &cmd), Err(Sdi12Error::InvalidFormat))); // Too short
-        assert!(matches!(recorder.process_response_payload(b"\r\n", &cmd), Err(Sdi12Error::InvalidFormat))); // No address
+        // A CRC command whose response is too short to hold the CRC
+        let crc_cmd = Command::StartMeasurementCRC { address: addr('0'), index: MeasurementIndex::Base };
+        assert_eq!(recorder.process_response_payload(b"0Oq\r\n", &crc_cmd, true), parse_err(ResponseParseError::TooShort));
         // Check valid empty payload case
-        let (start, end) = recorder.process_response_payload(b"0\r\n", &cmd).unwrap();
+        let (start, end) = recorder.process_response_payload(b"0\r\n", &cmd, command_requests_crc(&cmd)).unwrap();
         assert_eq!((start, end), (1, 1));
     }
 }
\ No newline at end of file
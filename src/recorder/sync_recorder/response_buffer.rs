@@ -0,0 +1,170 @@
+// src/recorder/sync_recorder/response_buffer.rs
+
+use super::{SyncRecorder, TraceSink};
+use crate::common::{
+    command::Command,
+    error::Sdi12Error,
+    hal_traits::{Sdi12Instant, Sdi12Serial, Sdi12Timer},
+    response::{parse_response_for_with_options, ParseOptions, PayloadSlice, Response},
+};
+use crate::recorder::Transport;
+use core::fmt::Debug;
+
+/// Default `ResponseBuffer` size: room for any standard response line.
+pub const DEFAULT_RESPONSE_BUFFER_LEN: usize = Command::MAX_RESPONSE_LEN;
+
+/// A read buffer that runs transactions and lends out views of the response.
+///
+/// Bundles the `[u8; N]` every transaction needs with the parsing step, so the
+/// result borrows from the buffer and no `'buf` lifetime has to be threaded by
+/// hand. One buffer can be reused for any number of transactions; each result
+/// lives until the next call.
+///
+/// ```ignore
+/// let mut buffer: ResponseBuffer = ResponseBuffer::new();
+/// let payload = buffer.transaction(&mut recorder, &command)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResponseBuffer<const N: usize = DEFAULT_RESPONSE_BUFFER_LEN> {
+    bytes: [u8; N],
+}
+
+impl<const N: usize> ResponseBuffer<N> {
+    pub const fn new() -> Self {
+        ResponseBuffer { bytes: [0; N] }
+    }
+
+    /// Runs `command` over `transport` and returns the validated payload.
+    pub fn transaction<T: Transport>(
+        &mut self,
+        transport: &mut T,
+        command: &Command,
+    ) -> Result<PayloadSlice<'_>, Sdi12Error<T::Error>> {
+        transport.transaction(command, &mut self.bytes).map(PayloadSlice)
+    }
+
+    /// Runs `command` and parses the whole response line into a `Response`.
+    ///
+    /// The response shape follows the command (see `parse_response_for`), and a
+    /// CRC is stripped exactly when the transaction verified one.
+    pub fn response<IF, T>(
+        &mut self,
+        recorder: &mut SyncRecorder<IF, T>,
+        command: &Command,
+    ) -> Result<Response<'_>, Sdi12Error<IF::Error>>
+    where
+        IF: Sdi12Serial + Sdi12Timer,
+        IF::Error: Debug,
+        IF::Instant: Sdi12Instant,
+        T: TraceSink,
+    {
+        let (_, end, crc) = recorder.execute_checked_transaction(command, &mut self.bytes)?;
+        // The validated line starts the buffer; its terminator follows the payload (and any CRC).
+        let line_len = self.bytes[end..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(self.bytes.len(), |i| end + i + 1);
+        let line = &self.bytes[..line_len];
+
+        let options = ParseOptions {
+            crc_expected: Some(crc.is_some()),
+            accept_bare_lf: recorder.config.accept_bare_lf,
+            ..ParseOptions::default()
+        };
+        parse_response_for_with_options(command, line, options).map_err(Sdi12Error::from_parse_error)
+    }
+}
+
+impl<const N: usize> Default for ResponseBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{
+        address::Sdi12Addr,
+        command::MeasurementIndex,
+        response::MeasurementTiming,
+        FrameFormat,
+    };
+    use core::time::Duration;
+    use nb::Result as NbResult;
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+    struct MockInstant(u64);
+    impl core::ops::Add<Duration> for MockInstant { type Output = Self; fn add(self, rhs: Duration) -> Self { MockInstant(self.0 + rhs.as_micros() as u64) } }
+    impl core::ops::Sub<MockInstant> for MockInstant { type Output = Duration; fn sub(self, rhs: MockInstant) -> Duration { Duration::from_micros(self.0.saturating_sub(rhs.0)) } }
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    struct MockCommError;
+
+    /// Replies with `reply` once the command's `!` was written.
+    struct MockInterface { reply: &'static [u8], pos: Option<usize>, clock_us: u64 }
+    impl Sdi12Timer for MockInterface {
+        type Instant = MockInstant;
+        fn delay_us(&mut self, us: u32) { self.clock_us += us as u64; }
+        fn delay_ms(&mut self, ms: u32) { self.clock_us += ms as u64 * 1000; }
+        fn now(&self) -> Self::Instant { MockInstant(self.clock_us) }
+    }
+    impl Sdi12Serial for MockInterface {
+        type Error = MockCommError;
+        fn read_byte(&mut self) -> NbResult<u8, Self::Error> {
+            let pos = self.pos.filter(|&p| p < self.reply.len()).ok_or(nb::Error::WouldBlock)?;
+            self.pos = Some(pos + 1);
+            Ok(self.reply[pos])
+        }
+        fn write_byte(&mut self, byte: u8) -> NbResult<(), Self::Error> { if byte == b'!' { self.pos = Some(0); } Ok(()) }
+        fn flush(&mut self) -> NbResult<(), Self::Error> { Ok(()) }
+        fn send_break(&mut self) -> NbResult<(), Self::Error> { Ok(()) }
+        fn set_config(&mut self, _config: FrameFormat) -> Result<(), Self::Error> { Ok(()) }
+    }
+
+    fn recorder(reply: &'static [u8]) -> SyncRecorder<MockInterface> {
+        SyncRecorder::new(MockInterface { reply, pos: None, clock_us: 0 })
+    }
+
+    #[test]
+    fn test_response_buffer_views() {
+        let address = Sdi12Addr::new('0').unwrap();
+        let measure = Command::StartMeasurement { address, index: MeasurementIndex::Base };
+        let mut buffer: ResponseBuffer = ResponseBuffer::new();
+
+        let mut rec = recorder(b"00052\r\n");
+        assert_eq!(buffer.transaction(&mut rec, &measure).unwrap().as_bytes(), b"0052");
+
+        let mut rec = recorder(b"00052\r\n");
+        assert_eq!(
+            buffer.response(&mut rec, &measure),
+            Ok(Response::Timing(MeasurementTiming { address, time_seconds: 5, values_count: 2 }))
+        );
+
+        let mut rec = recorder(b"0\r\n");
+        assert_eq!(buffer.response(&mut rec, &Command::AcknowledgeActive { address }), Ok(Response::Acknowledge { address }));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_response_strips_only_checked_crc() {
+        use crate::common::command::{ContinuousIndex, DataIndex};
+        use crate::common::crc::calculate_crc16;
+        let address = Sdi12Addr::new('0').unwrap();
+        let mut buffer: ResponseBuffer = ResponseBuffer::new();
+
+        let read_crc = Command::ReadContinuousCRC { address, index: ContinuousIndex::new(0).unwrap() };
+        let mut rec = recorder(b"0+3.14OqZ\r\n");
+        let response = buffer.response(&mut rec, &read_crc).unwrap();
+        assert_eq!(response.as_data().map(|data| data.crc), Some(Some(calculate_crc16(b"0+3.14"))));
+
+        // An unchecked CRC-looking tail is payload, which then fails to parse as values
+        let data = Command::SendData { address, index: DataIndex::new(0).unwrap() };
+        let mut rec = recorder(b"0+3.14OqZ\r\n");
+        assert!(buffer.response(&mut rec, &data).is_err());
+        let mut rec = recorder(b"0+3.14OqZ\r\n");
+        rec.config_mut().always_verify_crc_if_present = true;
+        let response = buffer.response(&mut rec, &data).unwrap();
+        assert_eq!(response.as_data().map(|data| data.values.len()), Some(1));
+    }
+}
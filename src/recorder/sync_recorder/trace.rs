@@ -0,0 +1,49 @@
+// src/recorder/sync_recorder/trace.rs
+
+/// One step of the recorder's conversation with the bus, passed to the
+/// sink set with `SyncRecorder::with_trace`.
+///
+/// The slices borrow the recorder's buffers and are only valid for the call;
+/// copy them out to keep them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TraceEvent<'a> {
+    /// A break (and the marking after it) was sent.
+    BreakSent,
+    /// Bytes written to the bus: a command, `!` included.
+    Tx(&'a [u8]),
+    /// Bytes read from the bus: a response line with its `<CR><LF>`, a binary
+    /// packet, or the partial line received before a `Timeout`.
+    Rx(&'a [u8]),
+    /// The last command is about to be sent again; this is the number of the
+    /// attempt (2 for the first retry).
+    Retry(usize),
+    /// A response didn't start, or stopped before its end, in time.
+    Timeout,
+}
+
+/// Receives the `TraceEvent`s of a `SyncRecorder`.
+///
+/// Implemented for every `FnMut(TraceEvent<'_>)` closure, so a closure that
+/// borrows a log buffer works with or without `alloc`.
+pub trait TraceSink {
+    fn event(&mut self, event: TraceEvent<'_>);
+}
+
+impl<F> TraceSink for F
+where
+    F: FnMut(TraceEvent<'_>),
+{
+    fn event(&mut self, event: TraceEvent<'_>) {
+        self(event)
+    }
+}
+
+/// The default sink of a `SyncRecorder`: drops every event, and compiles
+/// away.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct NoTrace;
+
+impl TraceSink for NoTrace {
+    #[inline]
+    fn event(&mut self, _event: TraceEvent<'_>) {}
+}
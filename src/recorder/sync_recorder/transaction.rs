@@ -1,31 +1,139 @@
 // src/recorder/sync_recorder/transaction.rs
 
-use super::SyncRecorder;
+use super::io_helpers::response_timeout_for_len;
+use super::protocol_helpers::{command_requests_crc, CheckedCrc};
+use super::{SyncRecorder, TraceEvent, TraceSink};
+use crate::recorder::Transport;
 use crate::common::{
     command::Command,
-    error::Sdi12Error,
+    error::{RetryCause, Sdi12Error},
     hal_traits::{Sdi12Instant, Sdi12Serial, Sdi12Timer},
-    timing, // Needed for retry timing
+    response::{split_binary_packet, BINARY_PACKET_OVERHEAD},
+    FrameFormat,
 };
 use core::fmt::Debug;
-use core::time::Duration; // Needed for retry timing
+use core::time::Duration;
 
 // Define retry constant
 const MAX_TRANSACTION_RETRIES: usize = 3;
 
-impl<IF> SyncRecorder<IF>
+impl<IF, T> SyncRecorder<IF, T>
 where
     IF: Sdi12Serial + Sdi12Timer,
     IF::Error: Debug,
     IF::Instant: Sdi12Instant,
+    T: TraceSink,
 {
+    /// How long `execute_transaction` waits for a response to `command` to start.
+    ///
+    /// Derived from the longest response the command can produce
+    /// (`Command::max_response_len`): a short reply such as an acknowledge or a
+    /// timing response (`Command::expects_timing_response`) is given about
+    /// 100 ms, while `aI!` is allowed well over 300 ms. The transmission part
+    /// scales with `RecorderConfig::baud_rate`; these figures are for 1200 baud.
+    pub fn default_response_timeout(&self, command: &Command) -> Duration {
+        response_timeout_for_len(command.max_response_len(), self.config.baud_rate)
+    }
+
     /// Executes a full command-response transaction with retries.
     /// Returns payload start/end indices on success.
-    pub(super) fn execute_transaction<'buf>(
+    pub(super) fn execute_transaction(
         &mut self,
         command: &Command,
-        read_buffer: &'buf mut [u8], // Still takes buffer for reading into
+        read_buffer: &mut [u8], // Still takes buffer for reading into
     ) -> Result<(usize, usize), Sdi12Error<IF::Error>> { // Return indices
+        let timeout = self.default_response_timeout(command);
+        self.execute_transaction_with_timeout(command, read_buffer, timeout)
+    }
+
+    /// Like `send_command`, but waits at most `response_timeout` for each
+    /// attempt's response to start instead of `default_response_timeout`.
+    ///
+    /// Use this for sensors known to answer late (or to fail fast when scanning
+    /// for absent addresses). The timeout applies per attempt; retries and the
+    /// inter-character timeout are unchanged.
+    pub fn execute_transaction_with_timeout(
+        &mut self,
+        command: &Command,
+        read_buffer: &mut [u8],
+        response_timeout: Duration,
+    ) -> Result<(usize, usize), Sdi12Error<IF::Error>> {
+        self.execute_transaction_expecting(command, read_buffer, response_timeout, command_requests_crc(command))
+    }
+
+    /// The transaction itself, with the CRC expectation chosen by the caller
+    /// (see `check_response_payload`).
+    pub(super) fn execute_transaction_expecting(
+        &mut self,
+        command: &Command,
+        read_buffer: &mut [u8],
+        response_timeout: Duration,
+        crc_expected: bool,
+    ) -> Result<(usize, usize), Sdi12Error<IF::Error>> {
+        self.run_transaction(command, read_buffer, response_timeout, crc_expected, false, None)
+            .map(|(start, end, _)| (start, end))
+    }
+
+    /// `execute_transaction`, also returning the CRC the line was checked
+    /// against, if it had one.
+    pub(super) fn execute_checked_transaction(
+        &mut self,
+        command: &Command,
+        read_buffer: &mut [u8],
+    ) -> Result<(usize, usize, Option<CheckedCrc>), Sdi12Error<IF::Error>> {
+        let timeout = self.default_response_timeout(command);
+        self.run_transaction(command, read_buffer, timeout, command_requests_crc(command), false, None)
+    }
+
+    /// A transaction reading data (`aDn!`, `aRn!`), which also returns the
+    /// CRC the line was checked against. Under `CrcFailurePolicy::WarnAndAccept`
+    /// a bad CRC is returned as invalid instead of failing with `CrcMismatch`.
+    #[cfg(feature = "alloc")]
+    pub(super) fn execute_data_transaction(
+        &mut self,
+        command: &Command,
+        read_buffer: &mut [u8],
+        crc_expected: bool,
+    ) -> Result<(usize, usize, Option<CheckedCrc>), Sdi12Error<IF::Error>> {
+        let timeout = self.default_response_timeout(command);
+        let accept_crc_mismatch = self.config.crc_failure_policy == super::CrcFailurePolicy::WarnAndAccept;
+        self.run_transaction(command, read_buffer, timeout, crc_expected, accept_crc_mismatch, None)
+    }
+
+    /// Like `send_command`, but gives up once `deadline` has passed instead of
+    /// running every retry.
+    ///
+    /// Each attempt waits `default_response_timeout(command)` for the response,
+    /// cut short at `deadline`, and no retry starts after it; the result is then
+    /// `RetriesExhausted` with the attempts actually made and how the last one
+    /// failed. A deadline that has already passed gives `RetriesExhausted` with
+    /// `attempts: 0` without touching the bus; otherwise the worst case is
+    /// `deadline` plus the time to send the command and to finish a response
+    /// that had started.
+    pub fn execute_transaction_with_deadline(
+        &mut self,
+        command: &Command,
+        read_buffer: &mut [u8],
+        deadline: IF::Instant,
+    ) -> Result<(usize, usize), Sdi12Error<IF::Error>> {
+        let timeout = self.default_response_timeout(command);
+        self.run_transaction(command, read_buffer, timeout, command_requests_crc(command), false, Some(deadline))
+            .map(|(start, end, _)| (start, end))
+    }
+
+    fn run_transaction(
+        &mut self,
+        command: &Command,
+        read_buffer: &mut [u8],
+        response_timeout: Duration,
+        crc_expected: bool,
+        accept_crc_mismatch: bool,
+        deadline: Option<IF::Instant>,
+    ) -> Result<(usize, usize, Option<CheckedCrc>), Sdi12Error<IF::Error>> {
+        // 0. No time at all: don't wake the bus for an attempt that can't succeed
+        if deadline.is_some_and(|deadline| self.interface.now() >= deadline) {
+            return Err(Sdi12Error::RetriesExhausted { attempts: 0, last: RetryCause::ResponseTimeout });
+        }
 
         // 1. Ensure break if needed
         self.check_and_send_break()?;
@@ -34,22 +142,26 @@ where
         let command_buffer = command.format_into()
             .map_err(Sdi12Error::CommandFormatFailed)?;
 
-        let mut last_error: Sdi12Error<IF::Error> = Sdi12Error::Timeout; // Default error if all retries fail
+        let mut last_error = RetryCause::ResponseTimeout; // Default error if all retries fail
 
         // 3. Retry Loop
         for attempt in 0..MAX_TRANSACTION_RETRIES {
             // 4. Send Command
-            if let Err(e) = self.send_command_bytes(command_buffer.as_bytes()) {
-                 // Treat send errors as fatal for now
-                 return Err(e);
-            }
+            // Treat send errors as fatal for now
+            self.send_command_bytes(command_buffer.as_bytes())?;
 
-            // 5. Read Response
-            match self.read_response_line(read_buffer) {
+            // 5. Read Response, no longer than the deadline allows
+            let now = self.interface.now();
+            let attempt_timeout = match deadline {
+                Some(deadline) if now >= deadline => Duration::ZERO,
+                Some(deadline) => response_timeout.min(deadline - now),
+                None => response_timeout,
+            };
+            match self.read_response_line_with_timeout(read_buffer, command.max_response_len(), attempt_timeout) {
                 Ok(line_slice) => {
                     // 5a. Process Response Payload
                     // Pass the received slice (which is part of read_buffer)
-                    match self.process_response_payload(line_slice, command) {
+                    match self.check_response_payload(line_slice, command, crc_expected, accept_crc_mismatch) {
                         Ok(indices) => { // Successful processing returns indices
                             // Success! Update time and return indices.
                             self.last_activity_time = Some(self.interface.now());
@@ -58,19 +170,20 @@ where
                         // Treat parsing errors as non-retryable for now
                         Err(e @ Sdi12Error::CrcMismatch { .. }) => return Err(e),
                         Err(e @ Sdi12Error::InvalidFormat) => return Err(e),
+                        Err(e @ Sdi12Error::ParseError(_)) => return Err(e),
                         Err(e @ Sdi12Error::UnexpectedResponse) => return Err(e),
                         Err(e @ Sdi12Error::InvalidAddress( _)) => return Err(e),
                         Err(e) => return Err(e), // Propagate other errors
                     }
                 }
-                // 5b. Handle Read Errors - Timeout/InvalidFormat are retryable
-                Err(Sdi12Error::Timeout) => {
-                    last_error = Sdi12Error::Timeout;
+                // 5b. Handle Read Errors - missing or truncated responses are retryable
+                Err(Sdi12Error::ResponseTimeout) => {
+                    last_error = RetryCause::ResponseTimeout;
                     // Continue to retry logic below
                 }
-                Err(Sdi12Error::InvalidFormat) => { // Treat incomplete read as retryable
-                    last_error = Sdi12Error::InvalidFormat;
-                     // Continue to retry logic below
+                Err(Sdi12Error::InterCharTimeout) => {
+                    last_error = RetryCause::InterCharTimeout;
+                    // Continue to retry logic below
                 }
                  // Any other error (like Io) is fatal
                 Err(e) => return Err(e),
@@ -80,6 +193,12 @@ where
             if attempt + 1 < MAX_TRANSACTION_RETRIES {
                 // Wait slightly more than RETRY_WAIT_MIN (16.67ms)
                 self.interface.delay_ms(20);
+                if deadline.is_some_and(|deadline| self.interface.now() >= deadline) {
+                    trace!("deadline passed after {} attempt(s) of {}", attempt + 1, command_buffer);
+                    return Err(Sdi12Error::RetriesExhausted { attempts: attempt + 1, last: last_error });
+                }
+                trace!("retrying {} ({}/{}) after {:?}", command_buffer, attempt + 2, MAX_TRANSACTION_RETRIES, last_error);
+                self.trace.event(TraceEvent::Retry(attempt + 2));
             } else {
                  // Retries exhausted
                  break;
@@ -87,7 +206,81 @@ where
         } // End retry loop
 
         // 7. Post-Loop: If we finished the loop, all retries failed
-        Err(last_error)
+        Err(Sdi12Error::RetriesExhausted { attempts: MAX_TRANSACTION_RETRIES, last: last_error })
+    }
+}
+
+impl<IF, T> SyncRecorder<IF, T>
+where
+    IF: Sdi12Serial + Sdi12Timer,
+    IF::Error: Debug,
+    IF::Instant: Sdi12Instant,
+    T: TraceSink,
+{
+    /// `run_transaction` for `aDBn!`: the command goes out in 7E1, the packet
+    /// is read in 8N1, and the interface is back in 7E1 afterwards. Retries
+    /// missing or truncated packets; returns the length of the checked packet.
+    pub(super) fn run_binary_transaction(
+        &mut self,
+        command: &Command,
+        read_buffer: &mut [u8],
+    ) -> Result<usize, Sdi12Error<IF::Error>> {
+        // Room for the header and CRC of an empty packet, before anything is sent
+        if read_buffer.len() < BINARY_PACKET_OVERHEAD {
+            return Err(Sdi12Error::BufferOverflow { needed: BINARY_PACKET_OVERHEAD, got: read_buffer.len() });
+        }
+        self.check_and_send_break()?;
+        let command_buffer = command.format_into().map_err(Sdi12Error::CommandFormatFailed)?;
+        let timeout = self.default_response_timeout(command);
+
+        let mut last_error = RetryCause::ResponseTimeout;
+        for attempt in 0..MAX_TRANSACTION_RETRIES {
+            self.send_command_bytes(command_buffer.as_bytes())?;
+            self.ensure_frame_format(FrameFormat::Binary8N1)?;
+            let read = self.read_binary_packet(read_buffer, timeout);
+            self.ensure_frame_format(FrameFormat::Sdi12_7e1)?;
+
+            match read {
+                Ok(len) => {
+                    let (address, ..) = split_binary_packet(&read_buffer[..len]).map_err(Sdi12Error::from_parse_error)?;
+                    if address != command.address() {
+                        return Err(Sdi12Error::UnexpectedResponse);
+                    }
+                    self.last_activity_time = Some(self.interface.now());
+                    return Ok(len);
+                }
+                Err(Sdi12Error::ResponseTimeout) => last_error = RetryCause::ResponseTimeout,
+                Err(Sdi12Error::InterCharTimeout) => last_error = RetryCause::InterCharTimeout,
+                Err(e) => return Err(e),
+            }
+
+            if attempt + 1 < MAX_TRANSACTION_RETRIES {
+                self.interface.delay_ms(20);
+                trace!("retrying {} ({}/{}) after {:?}", command_buffer, attempt + 2, MAX_TRANSACTION_RETRIES, last_error);
+                self.trace.event(TraceEvent::Retry(attempt + 2));
+            }
+        }
+        Err(Sdi12Error::RetriesExhausted { attempts: MAX_TRANSACTION_RETRIES, last: last_error })
+    }
+}
+
+impl<IF, T> Transport for SyncRecorder<IF, T>
+where
+    IF: Sdi12Serial + Sdi12Timer,
+    IF::Error: Debug,
+    IF::Instant: Sdi12Instant,
+    T: TraceSink,
+{
+    type Error = IF::Error;
+
+    /// `send_command`, returning the payload slice instead of its indices.
+    fn transaction<'buf>(
+        &mut self,
+        command: &Command,
+        buffer: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Sdi12Error<IF::Error>> {
+        let (start, end) = self.execute_transaction(command, buffer)?;
+        Ok(&buffer[start..end])
     }
 }
 
@@ -97,9 +290,9 @@ mod tests {
      use super::*;
      use crate::common::{
         address::Sdi12Addr,
-        command::{Command, MeasurementIndex},
+        command::{Command, ContinuousIndex, MeasurementIndex},
         hal_traits::{Sdi12Serial, Sdi12Timer},
-        FrameFormat, Sdi12Error, timing,
+        FrameFormat, Sdi12Error,
         response::PayloadSlice,
     };
     use core::time::Duration;
@@ -129,9 +322,12 @@ mod tests {
         #[cfg(not(feature = "std"))]
         _marker: core::marker::PhantomData<&'static str>,
         fail_read_after: Option<usize>,
+        #[allow(dead_code)]
         fail_write_after: Option<usize>,
         // Field type is fine, Sdi12Error itself doesn't need to be Clone
         read_error_type: Option<Sdi12Error<MockCommError>>,
+        set_config_failures: u8,
+        set_config_calls: u32,
     }
      impl MockInterface { /* ... new(), advance_time(), increment_call_count(), stage_read_data() ... */
          fn new() -> Self {
@@ -144,6 +340,7 @@ mod tests {
                  #[cfg(not(feature = "std"))]
                  _marker: core::marker::PhantomData,
                  fail_read_after: None, fail_write_after: None, read_error_type: None,
+                 set_config_failures: 0, set_config_calls: 0,
             }
           }
           fn advance_time(&mut self, us: u64) { self.current_time_us = self.current_time_us.saturating_add(us); }
@@ -159,8 +356,10 @@ mod tests {
                  self.read_queue[i] = Some(*byte);
              }
            }
+          #[cfg_attr(not(feature = "std"), allow(dead_code))]
           fn set_fail_read_after(&mut self, count: usize) { self.fail_read_after = Some(count); }
           // Accept error by value, store it. MockCommError needs to be Clone if Io variant is used.
+          #[cfg_attr(not(feature = "std"), allow(dead_code))]
           fn set_read_error(&mut self, error: Sdi12Error<MockCommError>) { self.read_error_type = Some(error); }
      }
      impl Sdi12Timer for MockInterface { /* ... */
@@ -197,7 +396,13 @@ mod tests {
          }
         fn flush(&mut self) -> NbResult<(), Self::Error> { self.increment_call_count("flush"); Ok(()) }
         fn send_break(&mut self) -> NbResult<(), Self::Error> { self.increment_call_count("send_break"); self.break_sent = true; Ok(()) }
-        fn set_config(&mut self, config: FrameFormat) -> Result<(), Self::Error> { self.increment_call_count("set_config"); self.config = config; Ok(()) }
+        fn set_config(&mut self, config: FrameFormat) -> Result<(), Self::Error> {
+            self.increment_call_count("set_config");
+            self.set_config_calls += 1;
+            if self.set_config_failures > 0 { self.set_config_failures -= 1; return Err(MockCommError); }
+            self.config = config;
+            Ok(())
+        }
     }
     fn addr(c: char) -> Sdi12Addr { Sdi12Addr::new(c).unwrap() }
 
@@ -219,6 +424,31 @@ mod tests {
          assert!(recorder.last_activity_time.is_some());
     }
 
+    #[test]
+    fn test_frame_format_applied_once_and_retried_after_failure() {
+        let mut mock_if = MockInterface::new();
+        mock_if.set_config_failures = 1;
+        mock_if.stage_read_data(b"0\r\n");
+        let mut recorder = SyncRecorder::new(mock_if);
+
+        // The failed set_config aborts the command before anything is written...
+        assert_eq!(recorder.acknowledge(addr('0')), Err(Sdi12Error::Io(MockCommError)));
+        assert_eq!(recorder.interface.write_pos, 0);
+        // ...and is retried by the next one.
+        assert_eq!(recorder.acknowledge(addr('0')), Ok(()));
+        assert_eq!(recorder.interface.set_config_calls, 2);
+        assert_eq!(recorder.interface.config, FrameFormat::Sdi12_7e1);
+
+        // Once applied, the format isn't set again.
+        recorder.interface.stage_read_data(b"0\r\n");
+        assert_eq!(recorder.acknowledge(addr('0')), Ok(()));
+        assert_eq!(recorder.interface.set_config_calls, 2);
+        // Unless the interface may have been reconfigured.
+        recorder.interface_mut().stage_read_data(b"0\r\n");
+        assert_eq!(recorder.acknowledge(addr('0')), Ok(()));
+        assert_eq!(recorder.interface.set_config_calls, 3);
+    }
+
      #[test]
      #[cfg(feature = "std")]
      //#[ignore] // Keep ignored until timing logic is verified
@@ -236,7 +466,7 @@ mod tests {
          let result = recorder.execute_transaction(&cmd, &mut buffer);
          let end_time = recorder.interface.now();
 
-         assert!(matches!(result, Err(Sdi12Error::Timeout)));
+         assert!(matches!(result, Err(Sdi12Error::RetriesExhausted { attempts: MAX_TRANSACTION_RETRIES, .. })));
 
          let cmd_len = cmd.format_into().unwrap().len();
          assert_eq!(recorder.interface.io_call_counts.get("write_byte").unwrap_or(&0), &(cmd_len * MAX_TRANSACTION_RETRIES) as &u32);
@@ -253,7 +483,8 @@ mod tests {
          mock_if.stage_read_data(crc_error_response);
          // No longer need to clone mock_if
          let mut recorder = SyncRecorder::new(mock_if);
-         let cmd = Command::StartMeasurementCRC { address: addr('0'), index: MeasurementIndex::Base };
+         // A data response with a CRC (aMC! itself answers with a short timing line)
+         let cmd = Command::ReadContinuousCRC { address: addr('0'), index: ContinuousIndex::new(0).unwrap() };
          let mut buffer = [0u8; 32];
          let result = recorder.execute_transaction(&cmd, &mut buffer);
          assert!(matches!(result, Err(Sdi12Error::CrcMismatch{..})));
@@ -264,4 +495,198 @@ mod tests {
             assert_eq!(recorder.interface.io_call_counts.get("write_byte").unwrap_or(&0), &(cmd.format_into().unwrap().len()) as &u32);
          }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_read_continuous_crc_strips_crc() {
+        use crate::common::crc::{calculate_crc16, encode_crc_ascii};
+        use crate::common::response::split_values;
+        use crate::common::Sdi12Value;
+
+        let cmd = Command::ReadContinuousCRC { address: addr('0'), index: ContinuousIndex::new(0).unwrap() };
+        // The short case, and one with the full 75 characters of values plus CRC
+        let long_values = "+1.1+2.2+1234567+1234567+1234567+1234567+1234567+1234567+1234567+1234567+12";
+        assert_eq!(long_values.len(), 75);
+        for values in ["+1.1+2.2", long_values] {
+            let mut line = [0u8; 96];
+            line[0] = b'0';
+            line[1..=values.len()].copy_from_slice(values.as_bytes());
+            let body_len = 1 + values.len();
+            let crc = encode_crc_ascii(calculate_crc16(&line[..body_len]));
+            line[body_len..body_len + 3].copy_from_slice(&crc);
+            line[body_len + 3..body_len + 5].copy_from_slice(b"\r\n");
+
+            let mut mock_if = MockInterface::new();
+            mock_if.stage_read_data(&line[..body_len + 5]);
+            let mut recorder = SyncRecorder::new(mock_if);
+            let mut buffer = [0u8; Command::MAX_RESPONSE_LEN];
+            let (start, end) = recorder.send_command(&cmd, &mut buffer).unwrap();
+
+            let payload = core::str::from_utf8(&buffer[start..end]).unwrap();
+            assert_eq!(payload, values); // CRC and <CR><LF> stripped
+            let mut parsed = split_values(payload).map(|v| Sdi12Value::parse_single(v).unwrap());
+            assert_eq!(parsed.next(), Some(Sdi12Value::new(1.1)));
+            assert_eq!(parsed.next(), Some(Sdi12Value::new(2.2)));
+        }
+    }
+
+    #[test]
+    fn test_default_response_timeout_scales_with_response() {
+        let recorder = SyncRecorder::new(MockInterface::new());
+        let ack = recorder.default_response_timeout(&Command::AcknowledgeActive { address: addr('0') });
+        let ident = recorder.default_response_timeout(&Command::SendIdentification { address: addr('0') });
+        let measure = recorder.default_response_timeout(&Command::StartMeasurement { address: addr('0'), index: MeasurementIndex::Base });
+        assert!(ack <= Duration::from_millis(100));
+        assert!(measure <= Duration::from_millis(150));
+        assert!(ident >= Duration::from_millis(300));
+    }
+
+    #[test]
+    fn test_transaction_with_timeout_override() {
+        // Absent sensor: every attempt waits out the given timeout
+        let mut recorder = SyncRecorder::new(MockInterface::new());
+        let cmd = Command::SendIdentification { address: addr('3') };
+        let mut buffer = [0u8; 48];
+        let short = Duration::from_millis(10);
+        let result = recorder.execute_transaction_with_timeout(&cmd, &mut buffer, short);
+        assert_eq!(result, Err(Sdi12Error::RetriesExhausted { attempts: MAX_TRANSACTION_RETRIES, last: RetryCause::ResponseTimeout }));
+        let elapsed = Duration::from_micros(recorder.interface.current_time_us);
+        assert!(elapsed < recorder.default_response_timeout(&cmd));
+
+        // A responding sensor is unaffected by a generous override
+        let mut mock_if = MockInterface::new();
+        mock_if.stage_read_data(b"0\r\n");
+        let mut recorder = SyncRecorder::new(mock_if);
+        let ack = Command::AcknowledgeActive { address: addr('0') };
+        assert_eq!(recorder.execute_transaction_with_timeout(&ack, &mut buffer, Duration::from_millis(500)).unwrap(), (1, 1));
+    }
+
+    #[test]
+    fn test_transaction_with_deadline_cuts_retries() {
+        // Absent sensor: the deadline falls during the second attempt
+        let mut recorder = SyncRecorder::new(MockInterface::new());
+        let cmd = Command::SendIdentification { address: addr('3') };
+        let mut buffer = [0u8; 48];
+        let per_attempt = recorder.default_response_timeout(&cmd);
+        let deadline = MockInstant(0) + per_attempt + per_attempt / 2;
+        let result = recorder.execute_transaction_with_deadline(&cmd, &mut buffer, deadline);
+        assert_eq!(result, Err(Sdi12Error::RetriesExhausted { attempts: 2, last: RetryCause::ResponseTimeout }));
+        let overshoot = recorder.interface.now() - deadline;
+        assert!(overshoot <= Duration::from_millis(25), "{overshoot:?}");
+
+        // A deadline that has already passed leaves the bus alone
+        let mut mock_if = MockInterface::new();
+        mock_if.stage_read_data(b"0\r\n");
+        let mut recorder = SyncRecorder::new(mock_if);
+        let ack = Command::AcknowledgeActive { address: addr('0') };
+        assert_eq!(
+            recorder.execute_transaction_with_deadline(&ack, &mut buffer, MockInstant(0)),
+            Err(Sdi12Error::RetriesExhausted { attempts: 0, last: RetryCause::ResponseTimeout })
+        );
+        assert!(!recorder.interface.break_sent);
+        assert_eq!(recorder.interface.write_log[0], None);
+        assert_eq!(recorder.interface.now(), MockInstant(0));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_crc_failure_policy() {
+        use crate::recorder::CrcFailurePolicy;
+        // D0 checks, D1 doesn't
+        let lines = b"0+3.14OqZ\r\n0+1OqZ\r\n";
+
+        let mut mock_if = MockInterface::new();
+        mock_if.stage_read_data(lines);
+        let mut recorder = SyncRecorder::new(mock_if);
+        assert!(matches!(recorder.collect_data(addr('0'), 2, true), Err(Sdi12Error::CrcMismatch { .. })));
+
+        let mut mock_if = MockInterface::new();
+        mock_if.stage_read_data(lines);
+        let mut recorder = SyncRecorder::new(mock_if);
+        recorder.config_mut().crc_failure_policy = CrcFailurePolicy::WarnAndAccept;
+        let data = recorder.collect_data(addr('0'), 2, true).unwrap();
+        assert_eq!(data.values.len(), 2);
+        assert_eq!(data.values[1], crate::common::Sdi12Value::new(1.0));
+        assert_eq!(data.crc_valid, Some(false));
+
+        // Good CRCs still read as valid under WarnAndAccept
+        let mut mock_if = MockInterface::new();
+        mock_if.stage_read_data(b"0+3.14OqZ\r\n");
+        let mut recorder = SyncRecorder::new(mock_if);
+        recorder.config_mut().crc_failure_policy = CrcFailurePolicy::WarnAndAccept;
+        assert_eq!(recorder.collect_data(addr('0'), 1, true).unwrap().crc_valid, Some(true));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_collect_all_data_across_registers() {
+        let mut mock_if = MockInterface::new();
+        mock_if.stage_read_data(b"0+1.5-2\r\n0+3\r\n");
+        let mut recorder = SyncRecorder::new(mock_if);
+        let data = recorder.collect_all_data(addr('0'), 3).unwrap();
+        assert_eq!(data.values.len(), 3);
+        assert_eq!(data.values[2], crate::common::Sdi12Value::new(3.0));
+        // Stopped after D1; D2 was never sent
+        assert_eq!(&recorder.interface.write_log[..6], &[Some(b'0'), Some(b'D'), Some(b'0'), Some(b'!'), Some(b'0'), Some(b'D')]);
+        assert_eq!(recorder.interface.write_log[8], None);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_collect_all_data_rejects_extra_values() {
+        // Three values announced, five delivered in D0
+        let mut mock_if = MockInterface::new();
+        mock_if.stage_read_data(b"0+1+2+3+4+5\r\n");
+        let mut recorder = SyncRecorder::new(mock_if);
+        assert_eq!(recorder.collect_all_data(addr('0'), 3), Err(Sdi12Error::UnexpectedResponse));
+
+        // Tolerated when configured
+        let mut mock_if = MockInterface::new();
+        mock_if.stage_read_data(b"0+1+2+3+4+5\r\n");
+        let mut recorder = SyncRecorder::new(mock_if);
+        recorder.config_mut().extra_values_allowed = 2;
+        assert_eq!(recorder.collect_all_data(addr('0'), 3).unwrap().values.len(), 5);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_collect_all_data_stops_on_empty_register() {
+        let mut mock_if = MockInterface::new();
+        mock_if.stage_read_data(b"0+1\r\n0\r\n");
+        let mut recorder = SyncRecorder::new(mock_if);
+        let data = recorder.collect_all_data(addr('0'), 4).unwrap();
+        assert_eq!(data.values.len(), 1);
+        assert_eq!(recorder.interface.write_log[8], None);
+
+        // A huge count doesn't reserve room for that many values up front
+        let mut mock_if = MockInterface::new();
+        mock_if.stage_read_data(b"0+1\r\n0\r\n");
+        let mut recorder = SyncRecorder::new(mock_if);
+        assert_eq!(recorder.collect_all_data(addr('0'), usize::MAX).unwrap().values.len(), 1);
+    }
+
+    #[test]
+    fn test_transport_returns_payload() {
+        let mut mock_if = MockInterface::new();
+        mock_if.stage_read_data(b"0+1.5-2\r\n");
+        let mut recorder = SyncRecorder::new(mock_if);
+        let mut buffer = [0u8; 32];
+        let cmd = Command::SendData { address: addr('0'), index: crate::common::command::DataIndex::new(0).unwrap() };
+        assert_eq!(Transport::transaction(&mut recorder, &cmd, &mut buffer).unwrap(), b"+1.5-2");
+    }
+
+    #[test]
+    fn test_send_raw_passes_bytes_through() {
+        let mut mock_if = MockInterface::new();
+        mock_if.stage_read_data(b"0XOK;ABC\r\n");
+        let mut recorder = SyncRecorder::new(mock_if);
+        let mut buffer = [0u8; 32];
+        let line = recorder.send_raw(b"0XRESET!", &mut buffer).unwrap();
+        assert_eq!(line, b"0XOK;ABC\r\n"); // No CRC or payload interpretation
+        assert!(recorder.interface.break_sent);
+        assert_eq!(recorder.interface.write_log[..8], b"0XRESET!".map(Some));
+        assert!(recorder.last_activity_time.is_some());
+
+        let mut recorder = SyncRecorder::new(MockInterface::new());
+        assert!(matches!(recorder.send_raw(b"0XRESET!", &mut buffer), Err(Sdi12Error::ResponseTimeout)));
+    }
+}
@@ -0,0 +1,121 @@
+// src/recorder/transport.rs
+
+use crate::common::{command::Command, error::Sdi12Error};
+#[cfg(feature = "alloc")]
+use crate::common::{address::Sdi12Addr, command::DataIndex, response::PayloadSlice, types::Sdi12Value};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+/// One SDI-12 command/response exchange, independent of how it is carried out.
+///
+/// `SyncRecorder` implements this with its full transaction (break, retries,
+/// address and CRC checks). Workflow code written against `Transport` instead
+/// of the recorder, like the data collection behind
+/// `SyncRecorder::collect_all_data` and `Measurement::finish`, can be
+/// unit-tested with a fake that returns canned payloads, and users can stub
+/// the bus the same way.
+pub trait Transport {
+    /// Error type of the underlying interface.
+    type Error: Debug;
+
+    /// Sends `command` and returns the validated payload of the response: the
+    /// line without address, CRC and `<CR><LF>`, borrowed from `buffer`.
+    fn transaction<'buf>(
+        &mut self,
+        command: &Command,
+        buffer: &'buf mut [u8],
+    ) -> Result<&'buf [u8], Sdi12Error<Self::Error>>;
+}
+
+/// Reads `aD0!`, `aD1!`, ... from `address` until `expected_count` values have
+/// arrived or a register comes back empty, and returns the values.
+///
+/// More than `expected_count + extra_allowed` values is a protocol violation
+/// and fails with `Sdi12Error::UnexpectedResponse`.
+#[cfg(feature = "alloc")]
+pub(crate) fn collect_data_registers<T: Transport>(
+    transport: &mut T,
+    address: Sdi12Addr,
+    expected_count: usize,
+    extra_allowed: usize,
+) -> Result<Vec<Sdi12Value>, Sdi12Error<T::Error>> {
+    // No measurement announces more than 999 values (`aHA!`'s `nnn`), whatever the caller passed
+    let mut values = Vec::with_capacity(expected_count.min(999));
+    let mut buffer = [0u8; Command::MAX_RESPONSE_LEN];
+
+    for index in (0..=999).map_while(|i| DataIndex::new(i).ok()) {
+        if values.len() >= expected_count {
+            break;
+        }
+        let payload = transport.transaction(&Command::SendData { address, index }, &mut buffer)?;
+        if payload.is_empty() {
+            break; // Empty register: no more data
+        }
+        values.extend(PayloadSlice(payload).parse_values().map_err(Sdi12Error::from_parse_error)?);
+    }
+
+    if values.len() > expected_count.saturating_add(extra_allowed) {
+        trace!("{} values where {} were announced", values.len(), expected_count);
+        return Err(Sdi12Error::UnexpectedResponse);
+    }
+    Ok(values)
+}
+
+// --- Unit Tests ---
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    /// Answers every command with the same payload and counts the calls.
+    struct CannedTransport {
+        payload: &'static [u8],
+        calls: usize,
+    }
+
+    impl Transport for CannedTransport {
+        type Error = ();
+        fn transaction<'buf>(&mut self, _command: &Command, buffer: &'buf mut [u8]) -> Result<&'buf [u8], Sdi12Error<()>> {
+            self.calls += 1;
+            let got = buffer.len();
+            let payload = buffer.get_mut(..self.payload.len()).ok_or(Sdi12Error::BufferOverflow {
+                needed: self.payload.len(),
+                got,
+            })?;
+            payload.copy_from_slice(self.payload);
+            Ok(payload)
+        }
+    }
+
+    #[test]
+    fn test_collect_data_registers_against_canned_transport() {
+        let address = Sdi12Addr::new('0').unwrap();
+
+        let mut empty = CannedTransport { payload: b"", calls: 0 };
+        assert_eq!(collect_data_registers(&mut empty, address, 2, 0), Ok(Vec::new()));
+        assert_eq!(empty.calls, 1);
+
+        // Two values per register: four values take two registers
+        let mut full = CannedTransport { payload: b"+1.5-2", calls: 0 };
+        let values = collect_data_registers(&mut full, address, 4, 0).unwrap();
+        assert_eq!(values.len(), 4);
+        assert_eq!(values[2], Sdi12Value::new(1.5));
+        assert_eq!(full.calls, 2);
+
+        // One more value than announced is only accepted when allowed
+        let mut full = CannedTransport { payload: b"+1.5-2", calls: 0 };
+        assert_eq!(collect_data_registers(&mut full, address, 3, 0), Err(Sdi12Error::UnexpectedResponse));
+        let mut full = CannedTransport { payload: b"+1.5-2", calls: 0 };
+        assert_eq!(collect_data_registers(&mut full, address, 3, 1).map(|values| values.len()), Ok(4));
+
+        let mut garbled = CannedTransport { payload: b"+1.5x", calls: 0 };
+        assert!(matches!(collect_data_registers(&mut garbled, address, 1, 0), Err(Sdi12Error::ParseError(_))));
+    }
+
+    #[test]
+    fn test_canned_transport_reports_short_buffer() {
+        let command = Command::AcknowledgeActive { address: Sdi12Addr::new('0').unwrap() };
+        let mut full = CannedTransport { payload: b"+1.5-2", calls: 0 };
+        assert_eq!(full.transaction(&command, &mut [0u8; 2]), Err(Sdi12Error::BufferOverflow { needed: 6, got: 2 }));
+    }
+}
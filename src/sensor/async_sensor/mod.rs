@@ -0,0 +1,241 @@
+// src/sensor/async_sensor/mod.rs
+
+use super::handler::AsyncSensorHandler;
+use super::parser::parse_command;
+use super::response::SensorResponse;
+use super::session::{route, Route, Session};
+use super::MAX_RESPONSE_LEN;
+use crate::common::{
+    command::Command,
+    error::Sdi12Error,
+    hal_traits::Sdi12SerialAsync,
+    timing,
+};
+use core::convert::Infallible;
+use core::time::Duration;
+use embedded_hal_async::delay::DelayNs;
+
+/// Async sensor runner: the `SyncSensor` counterpart for async runtimes such as Embassy.
+///
+/// Reads commands with `Sdi12SerialAsync`, dispatches them to an `AsyncSensorHandler`
+/// and writes the formatted responses. Parsing, formatting and the spec-level checks
+/// (address matching, silently ignoring malformed commands, address-only reply for
+/// `aDn!` beyond `max_data_index`) are the same as in the sync runner.
+#[derive(Debug)]
+pub struct AsyncSensor<IF, H> {
+    interface: IF,
+    handler: H,
+    session: Session,
+    response_delay: Duration,
+}
+
+impl<IF, H> AsyncSensor<IF, H>
+where
+    IF: Sdi12SerialAsync + DelayNs,
+    H: AsyncSensorHandler,
+{
+    pub fn new(interface: IF, handler: H) -> Self {
+        AsyncSensor {
+            interface,
+            handler,
+            session: Session::new(),
+            response_delay: timing::SENSOR_PRE_RESPONSE_MARKING,
+        }
+    }
+
+    /// Marking time held before each response (spec: 8.33 ms, the default).
+    pub fn response_delay(&self) -> Duration {
+        self.response_delay
+    }
+
+    /// Overrides the pre-response marking time, e.g. zero for test harnesses.
+    pub fn set_response_delay(&mut self, delay: Duration) {
+        self.response_delay = delay;
+    }
+
+    pub fn handler(&self) -> &H {
+        &self.handler
+    }
+
+    pub fn handler_mut(&mut self) -> &mut H {
+        &mut self.handler
+    }
+
+    /// Releases the interface and handler.
+    pub fn release(self) -> (IF, H) {
+        (self.interface, self.handler)
+    }
+
+    /// Serves commands forever. Only returns if the interface reports an error.
+    pub async fn run(&mut self) -> Result<Infallible, Sdi12Error<IF::Error>> {
+        loop {
+            self.process_next().await?;
+        }
+    }
+
+    /// Reads bytes until a command's `!` arrives, then handles that command.
+    pub async fn process_next(&mut self) -> Result<(), Sdi12Error<IF::Error>> {
+        loop {
+            let byte = self.interface.read_byte().await.map_err(Sdi12Error::Io)?;
+            match self.session.push_byte(byte) {
+                Some(Ok(command)) => return self.respond(&command).await,
+                Some(Err(_)) => return Ok(()), // Malformed: ignored, as in `process_command`
+                None => {}
+            }
+        }
+    }
+
+    /// Parses a complete command (`a...!`) and answers it if it is addressed to this sensor.
+    /// Malformed commands are silently ignored, as the spec requires.
+    pub async fn process_command(&mut self, bytes: &[u8]) -> Result<(), Sdi12Error<IF::Error>> {
+        match parse_command(bytes) {
+            Ok(command) => self.respond(&command).await,
+            Err(_) => Ok(()),
+        }
+    }
+
+    /// Dispatches a parsed command to the handler and writes the reply.
+    async fn respond(&mut self, command: &Command) -> Result<(), Sdi12Error<IF::Error>> {
+        let mut address = self.handler.address();
+        let response = match route(command, address, self.handler.max_data_index()) {
+            Route::Ignore => return Ok(()),
+            Route::Acknowledge => SensorResponse::Acknowledge,
+            Route::ChangeAddress(new_address) => {
+                if self.handler.change_address(new_address).await {
+                    address = new_address;
+                }
+                SensorResponse::Acknowledge
+            }
+            Route::Identification => self.handler.identification().await,
+            Route::StartMeasurement => self.handler.start_measurement(command).await,
+            Route::SendData(index) => self.handler.send_data(index).await,
+            Route::Other => self.handler.handle_other(command).await,
+        };
+
+        let mut out = [0u8; MAX_RESPONSE_LEN];
+        let Some(len) = self.session.format_reply(address, &response, command, &mut out)? else {
+            return Ok(());
+        };
+        if !self.response_delay.is_zero() {
+            self.interface.delay_us(self.response_delay.as_micros() as u32).await;
+        }
+        for byte in &out[..len] {
+            self.interface.write_byte(*byte).await.map_err(Sdi12Error::Io)?;
+        }
+        self.interface.flush().await.map_err(Sdi12Error::Io)?;
+        Ok(())
+    }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{
+        address::Sdi12Addr,
+        command::DataIndex,
+        types::Sdi12Value,
+        FrameFormat,
+    };
+    use core::future::Future;
+    use core::pin::pin;
+    use core::task::{Context, Poll, Waker};
+
+    // The mock never returns Pending, so polling once always completes.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("mock interface never blocks"),
+        }
+    }
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    enum MockCommError { EndOfInput }
+
+    struct MockInterface {
+        read_queue: [u8; 32],
+        read_len: usize,
+        read_pos: usize,
+        written: [u8; 96],
+        write_len: usize,
+        delayed_ns: u64,
+    }
+    impl MockInterface {
+        fn new(input: &[u8]) -> Self {
+            let mut read_queue = [0u8; 32];
+            read_queue[..input.len()].copy_from_slice(input);
+            MockInterface { read_queue, read_len: input.len(), read_pos: 0, written: [0; 96], write_len: 0, delayed_ns: 0 }
+        }
+        fn written(&self) -> &[u8] { &self.written[..self.write_len] }
+    }
+    impl DelayNs for MockInterface {
+        async fn delay_ns(&mut self, ns: u32) { self.delayed_ns += ns as u64; }
+    }
+    impl Sdi12SerialAsync for MockInterface {
+        type Error = MockCommError;
+        async fn read_byte(&mut self) -> Result<u8, Self::Error> {
+            if self.read_pos < self.read_len { self.read_pos += 1; Ok(self.read_queue[self.read_pos - 1]) } else { Err(MockCommError::EndOfInput) }
+        }
+        async fn write_byte(&mut self, byte: u8) -> Result<(), Self::Error> { self.written[self.write_len] = byte; self.write_len += 1; Ok(()) }
+        async fn flush(&mut self) -> Result<(), Self::Error> { Ok(()) }
+        async fn send_break(&mut self) -> Result<(), Self::Error> { Ok(()) }
+        async fn set_config(&mut self, _config: FrameFormat) -> Result<(), Self::Error> { Ok(()) }
+    }
+
+    struct TestHandler {
+        address: Sdi12Addr,
+        values: [Sdi12Value; 2],
+        send_data_calls: usize,
+    }
+    impl AsyncSensorHandler for TestHandler {
+        fn address(&self) -> Sdi12Addr { self.address }
+        async fn identification(&mut self) -> SensorResponse<'_> { SensorResponse::Text("14TESTCORPMODEL1001") }
+        async fn start_measurement(&mut self, _command: &Command) -> SensorResponse<'_> {
+            SensorResponse::Timing { time_seconds: 1, values_count: 2 }
+        }
+        async fn send_data(&mut self, _index: DataIndex) -> SensorResponse<'_> {
+            self.send_data_calls += 1;
+            SensorResponse::Values(&self.values)
+        }
+        fn max_data_index(&self) -> DataIndex { DataIndex::new(0).unwrap() }
+    }
+
+    fn sensor(input: &[u8]) -> AsyncSensor<MockInterface, TestHandler> {
+        let handler = TestHandler {
+            address: Sdi12Addr::new('0').unwrap(),
+            values: [Sdi12Value::new(1.5), Sdi12Value::new(-2.0)],
+            send_data_calls: 0,
+        };
+        AsyncSensor::new(MockInterface::new(input), handler)
+    }
+
+    #[test]
+    fn test_process_next_measurement_and_data() {
+        let mut s = sensor(b"\x00\x000M!0D0!0D5!");
+        assert!(block_on(s.process_next()).is_ok());
+        assert_eq!(s.interface.written(), b"00012\r\n");
+        assert_eq!(s.interface.delayed_ns as u128, timing::SENSOR_PRE_RESPONSE_MARKING.as_nanos());
+
+        s.interface.write_len = 0;
+        assert!(block_on(s.process_next()).is_ok());
+        assert_eq!(s.interface.written(), b"0+1.5-2\r\n");
+
+        // Beyond max_data_index: address-only reply, handler not called
+        s.interface.write_len = 0;
+        assert!(block_on(s.process_next()).is_ok());
+        assert_eq!(s.interface.written(), b"0\r\n");
+        assert_eq!(s.handler().send_data_calls, 1);
+    }
+
+    #[test]
+    fn test_run_serves_until_interface_error() {
+        let mut s = sensor(b"1M!0I!");
+        s.set_response_delay(Duration::ZERO);
+        assert!(matches!(block_on(s.run()), Err(Sdi12Error::Io(MockCommError::EndOfInput))));
+        // The command for address 1 was ignored; only the identification went out
+        assert_eq!(s.interface.written(), b"014TESTCORPMODEL1001\r\n");
+        assert_eq!(s.interface.delayed_ns, 0);
+    }
+}
@@ -0,0 +1,143 @@
+// src/sensor/formatter.rs
+
+use super::response::SensorResponse;
+use crate::common::{
+    address::Sdi12Addr,
+    command::Command,
+    crc::append_crc_and_terminator,
+    error::Sdi12Error,
+};
+use core::fmt::{Debug, Write};
+
+/// Small `fmt::Write` adapter over a byte slice, so formatting needs no allocation.
+struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl SliceWriter<'_> {
+    fn push<E: Debug>(&mut self, bytes: &[u8]) -> Result<(), Sdi12Error<E>> {
+        let end = self.len + bytes.len();
+        if end > self.buf.len() {
+            return Err(Sdi12Error::BufferOverflow { needed: end, got: self.buf.len() });
+        }
+        self.buf[self.len..end].copy_from_slice(bytes);
+        self.len = end;
+        Ok(())
+    }
+}
+
+impl Write for SliceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.push::<()>(s.as_bytes()).map_err(|_| core::fmt::Error)
+    }
+}
+
+/// Formats a complete response line (`a<payload>[CRC]<CR><LF>`) into `buf`.
+///
+/// A CRC is appended when `crc` is set; which replies carry one is up to the
+/// runner (see `Session::format_reply`). Returns the number of bytes written,
+/// or `None` for `SensorResponse::NoResponse`.
+pub(crate) fn format_response<E: Debug>(
+    address: Sdi12Addr,
+    response: &SensorResponse<'_>,
+    command: &Command,
+    crc: bool,
+    buf: &mut [u8],
+) -> Result<Option<usize>, Sdi12Error<E>> {
+    let mut w = SliceWriter { buf, len: 0 };
+    let overflow = |w: &SliceWriter<'_>| Sdi12Error::BufferOverflow { needed: w.len + 1, got: w.buf.len() };
+
+    let mut addr_bytes = [0u8; 4];
+    w.push(address.as_char().encode_utf8(&mut addr_bytes).as_bytes())?;
+
+    match response {
+        SensorResponse::NoResponse => return Ok(None),
+        SensorResponse::Acknowledge => {}
+        SensorResponse::Timing { time_seconds, values_count } => {
+            let width = command.timing_count_digits().unwrap_or(1);
+            if *time_seconds > 999 || u32::from(*values_count) >= 10u32.pow(width as u32) {
+                return Err(Sdi12Error::InvalidFormat);
+            }
+            write!(w, "{:03}{:0width$}", time_seconds, values_count, width = width).map_err(|_| overflow(&w))?;
+        }
+        SensorResponse::Values(values) => {
+            for value in values.iter() {
+                let text = value.format_sdi12().map_err(|_| Sdi12Error::InvalidFormat)?;
+                w.push(text.as_bytes())?;
+            }
+        }
+        SensorResponse::Text(text) => w.push(text.as_bytes())?,
+        SensorResponse::Identification(payload) => w.push(payload.as_bytes())?,
+    }
+
+    if crc {
+        let len = append_crc_and_terminator(w.buf, w.len)
+            .ok_or(Sdi12Error::BufferOverflow { needed: w.len + 5, got: w.buf.len() })?;
+        return Ok(Some(len));
+    }
+    w.push(b"\r\n")?;
+    Ok(Some(w.len))
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::command::{DataIndex, MeasurementIndex};
+    use crate::common::types::Sdi12Value;
+
+    fn addr(c: char) -> Sdi12Addr { Sdi12Addr::new(c).unwrap() }
+
+    fn format<'b>(response: &SensorResponse<'_>, command: &Command, crc: bool, buf: &'b mut [u8]) -> &'b [u8] {
+        let len = format_response::<()>(addr('0'), response, command, crc, buf).unwrap().unwrap();
+        &buf[..len]
+    }
+
+    #[test]
+    fn test_format_timing_widths() {
+        let mut buf = [0u8; 32];
+        let timing = SensorResponse::Timing { time_seconds: 5, values_count: 3 };
+        let m = Command::StartMeasurement { address: addr('0'), index: MeasurementIndex::Base };
+        let c = Command::StartConcurrentMeasurement { address: addr('0'), index: MeasurementIndex::Base };
+        let ha = Command::StartHighVolumeASCII { address: addr('0') };
+        assert_eq!(format(&timing, &m, false, &mut buf), b"00053\r\n");
+        assert_eq!(format(&timing, &c, false, &mut buf), b"000503\r\n");
+        assert_eq!(format(&timing, &ha, false, &mut buf), b"0005003\r\n");
+
+        let too_many = SensorResponse::Timing { time_seconds: 5, values_count: 10 };
+        assert_eq!(format_response::<()>(addr('0'), &too_many, &m, false, &mut buf), Err(Sdi12Error::InvalidFormat));
+    }
+
+    #[test]
+    fn test_format_values_with_crc() {
+        let mut buf = [0u8; 32];
+        let values = [Sdi12Value::new(3.5)];
+        let d0 = Command::SendData { address: addr('0'), index: DataIndex::new(0).unwrap() };
+        assert_eq!(format(&SensorResponse::Values(&values), &d0, false, &mut buf), b"0+3.5\r\n");
+
+        let line = format(&SensorResponse::Values(&values), &d0, true, &mut buf);
+        assert_eq!(line.len(), b"0+3.5CRC\r\n".len());
+        assert!(crate::common::crc::verify_response_crc_ascii::<()>(&line[..line.len() - 2]).is_ok());
+    }
+
+    #[test]
+    fn test_format_no_response_and_overflow() {
+        let mut buf = [0u8; 4];
+        let ack = Command::AcknowledgeActive { address: addr('0') };
+        assert_eq!(format_response::<()>(addr('0'), &SensorResponse::NoResponse, &ack, false, &mut buf), Ok(None));
+        assert!(matches!(
+            format_response::<()>(addr('0'), &SensorResponse::Text("TOO LONG"), &ack, false, &mut buf),
+            Err(Sdi12Error::BufferOverflow { .. })
+        ));
+
+        // Room for the payload but not its CRC
+        let mut buf = [0u8; 8];
+        let values = [Sdi12Value::new(3.5)];
+        let d0 = Command::SendData { address: addr('0'), index: DataIndex::new(0).unwrap() };
+        assert_eq!(
+            format_response::<()>(addr('0'), &SensorResponse::Values(&values), &d0, true, &mut buf),
+            Err(Sdi12Error::BufferOverflow { needed: 10, got: 8 })
+        );
+    }
+}
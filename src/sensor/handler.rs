@@ -0,0 +1,79 @@
+// src/sensor/handler.rs
+
+use super::response::SensorResponse;
+use crate::common::{
+    address::Sdi12Addr,
+    command::{Command, DataIndex},
+};
+
+/// Application logic of a sensor. The runner (`SyncSensor`) takes care of reading
+/// and parsing commands, address matching, spec-level checks and formatting.
+pub trait SensorHandler {
+    /// The sensor's current address.
+    fn address(&self) -> Sdi12Addr;
+
+    /// Handles `aAb!`. Return `true` once the new address is stored; the runner then
+    /// replies from the new address. The default refuses, so the sensor keeps
+    /// (and replies with) its current address.
+    fn change_address(&mut self, _new_address: Sdi12Addr) -> bool {
+        false
+    }
+
+    /// Handles `aI!`. Return the payload (`llccccccccmmmmmmvvvxxx...`), preferably
+    /// built with `SensorResponse::identification`, which checks the field widths.
+    fn identification(&mut self) -> SensorResponse<'_>;
+
+    /// Handles M, MC, C, CC, V, HA and HB. Return `SensorResponse::Timing`.
+    fn start_measurement(&mut self, command: &Command) -> SensorResponse<'_>;
+
+    /// Handles `aDn!`. Only called for `index <= max_data_index()`.
+    fn send_data(&mut self, index: DataIndex) -> SensorResponse<'_>;
+
+    /// Highest `Dn` index this sensor serves. Requests beyond it are answered
+    /// with an address-only reply without calling `send_data`.
+    /// Defaults to 9 (`D0`-`D9`).
+    fn max_data_index(&self) -> DataIndex {
+        DataIndex::new(9).unwrap()
+    }
+
+    /// Handles every other command (R, DB, identify, extended).
+    /// Defaults to no reply, which the recorder sees as an unsupported command.
+    fn handle_other(&mut self, _command: &Command) -> SensorResponse<'_> {
+        SensorResponse::NoResponse
+    }
+}
+
+/// Async counterpart of `SensorHandler`, driven by `AsyncSensor`.
+///
+/// Same contract as `SensorHandler`; methods may await (e.g. an ADC conversion)
+/// before returning their response.
+#[cfg(feature = "async")]
+#[allow(async_fn_in_trait)] // Single-threaded embedded executors don't need `Send` futures
+pub trait AsyncSensorHandler {
+    /// The sensor's current address.
+    fn address(&self) -> Sdi12Addr;
+
+    /// Handles `aAb!`. See `SensorHandler::change_address`.
+    async fn change_address(&mut self, _new_address: Sdi12Addr) -> bool {
+        false
+    }
+
+    /// Handles `aI!`. See `SensorHandler::identification`.
+    async fn identification(&mut self) -> SensorResponse<'_>;
+
+    /// Handles M, MC, C, CC, V, HA and HB. Return `SensorResponse::Timing`.
+    async fn start_measurement(&mut self, command: &Command) -> SensorResponse<'_>;
+
+    /// Handles `aDn!`. Only called for `index <= max_data_index()`.
+    async fn send_data(&mut self, index: DataIndex) -> SensorResponse<'_>;
+
+    /// Highest `Dn` index this sensor serves. Defaults to 9 (`D0`-`D9`).
+    fn max_data_index(&self) -> DataIndex {
+        DataIndex::new(9).unwrap()
+    }
+
+    /// Handles every other command. Defaults to no reply.
+    async fn handle_other(&mut self, _command: &Command) -> SensorResponse<'_> {
+        SensorResponse::NoResponse
+    }
+}
@@ -5,9 +5,15 @@
 
 // Shared logic (used by both sync and async sensor runners)
 pub mod handler;      // Defines the SensorHandler trait (user implements this)
-mod response;     // Defines the internal SensorResponse enum and related structs
+mod response;     // Defines the SensorResponse enum returned by handlers
 mod formatter;    // Logic to format SensorResponse -> byte stream
 mod parser;       // Logic to parse byte stream -> Command
+mod session;      // Command buffering and routing, the I/O-free part of a runner
+
+/// Longest command accepted, including address and `!` (extended commands included).
+pub const MAX_COMMAND_LEN: usize = 32;
+/// Longest response emitted: address + 75 value chars + CRC + `<CR><LF>`, rounded up.
+const MAX_RESPONSE_LEN: usize = 96;
 
 // Specific runner implementations
 pub mod sync_sensor; // Synchronous sensor runner
@@ -20,16 +26,20 @@ pub mod async_sensor;
 // Re-export the essential types that users of the library will interact with
 // when implementing a sensor.
 
+// Command parsing (byte stream -> Command), useful on its own for sensor firmware.
+pub use parser::{parse_command, parse_command_with_max_len};
+
 // The core trait the user needs to implement.
-// pub use handler::SensorHandler;
+pub use handler::SensorHandler;
+#[cfg(feature = "async")]
+pub use handler::AsyncSensorHandler;
+
+// What handlers return; the runner formats it onto the wire.
+pub use response::{IdentificationError, IdentificationField, SensorResponse, MAX_IDENTIFICATION_LEN};
 
 // The synchronous runner struct the user will instantiate and run.
-// pub use sync_sensor::SyncSensor;
+pub use sync_sensor::SyncSensor;
 
 // Conditionally re-export the asynchronous runner struct.
 #[cfg(feature = "async")]
 pub use async_sensor::AsyncSensor;
-
-// Potential re-exports for response types if they are directly used
-// in the SensorHandler trait signatures (might need adjustment later).
-// pub use response::{ SensorResponse, IdentificationInfo, /* ... */ };
\ No newline at end of file
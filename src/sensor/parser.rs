@@ -1,9 +1,10 @@
 // src/sensor/parser.rs
 
+use super::MAX_COMMAND_LEN;
 use crate::common::{
     address::Sdi12Addr,
     command::{
-        Command, /* CommandIndexError (keep if used by helpers), */ // Remove if not directly used here
+        Command, CommandIndexError,
         ContinuousIndex, DataIndex, IdentifyMeasurementCommand,
         IdentifyMeasurementParameterCommand, IdentifyParameterIndex, MeasurementIndex,
     },
@@ -12,8 +13,6 @@ use crate::common::{
 // use core::convert::TryFrom; // Not directly used here anymore
 use core::str;
 
-#[cfg(feature = "alloc")]
-use alloc::string::String;
 #[cfg(feature = "alloc")]
 use alloc::string::ToString; // Needed for to_string()
 
@@ -31,20 +30,36 @@ use alloc::string::ToString; // Needed for to_string()
 ///
 /// * `Ok(Command)`: If the byte sequence represents a valid SDI-12 command.
 /// * `Err(Sdi12Error<()>)`: If parsing fails due to invalid format, address, index, etc.
+///   Commands longer than `MAX_COMMAND_LEN` bytes are rejected with `InvalidFormat`.
+///
+/// The minimal inputs: `a!` is `AcknowledgeActive` and `?!` is `AddressQuery`;
+/// `?` followed by anything else is `InvalidFormat`. An empty input or one not
+/// ending in `!` is `InvalidFormat`, while a lone `!` is `InvalidAddress('!')`:
+/// its first byte is where the address belongs.
 pub fn parse_command(bytes: &[u8]) -> Result<Command, Sdi12Error<()>> {
+    parse_command_with_max_len(bytes, MAX_COMMAND_LEN)
+}
+
+/// Like `parse_command`, but rejects commands longer than `max_len` bytes
+/// (address and `!` included) instead of `MAX_COMMAND_LEN`.
+///
+/// Useful to bound the extended commands a sensor accepts.
+pub fn parse_command_with_max_len(bytes: &[u8], max_len: usize) -> Result<Command, Sdi12Error<()>> {
     // --- Basic Validation ---
-    if bytes.len() < 2 {
-        return Err(Sdi12Error::InvalidFormat); // Need at least 'a!' or '?!'
-    }
-    if bytes[bytes.len() - 1] != b'!' {
-        return Err(Sdi12Error::InvalidFormat); // Must end with '!'
+    if bytes.len() > max_len {
+        return Err(Sdi12Error::InvalidFormat); // At most max_len
     }
+    let Some((&b'!', without_terminator)) = bytes.split_last() else {
+        return Err(Sdi12Error::InvalidFormat); // Empty, or not ending with '!'
+    };
 
+    // A lone "!" fails here with InvalidAddress('!'): the terminator sits where the address belongs
     let address_char = bytes[0] as char;
     let address = Sdi12Addr::new(address_char)?; // Returns InvalidAddress error if needed
 
-    // Command body excludes address and '!'
-    let body = &bytes[1..bytes.len() - 1];
+    // Command body excludes address and '!' (`without_terminator` holds the
+    // address at least: a valid address isn't '!')
+    let body = &without_terminator[1..];
 
     // Handle Address Query (?!) separately
     if address.is_query() {
@@ -57,15 +72,15 @@ pub fn parse_command(bytes: &[u8]) -> Result<Command, Sdi12Error<()>> {
 
     // --- Check Body Bytes for Printable ASCII ---
     // SDI-12 Spec Sec 4.2 requires command body chars to be printable ASCII (0x20-0x7E)
-    if !body.iter().all(|&b| b >= 0x20 && b <= 0x7E) {
+    if !body.iter().all(|&b| (0x20..=0x7E).contains(&b)) {
         // If any byte is outside the range, it's an invalid command format per SDI-12.
         return Err(Sdi12Error::InvalidFormat);
     }
 
     // --- Parse Command Body (now known to be printable ASCII) ---
-    // Since we've verified bytes are in the ASCII range 0x20-0x7E,
-    // this conversion is safe and will produce a valid ASCII string.
-    let body_str = unsafe { str::from_utf8_unchecked(body) };
+    // ASCII is valid UTF-8, so this can't fail, and every char below is one
+    // byte: byte offsets into `body_str` always fall on char boundaries.
+    let body_str = str::from_utf8(body).map_err(|_| Sdi12Error::InvalidFormat)?;
 
     match body_str {
         // --- Basic Commands ---
@@ -77,9 +92,11 @@ pub fn parse_command(bytes: &[u8]) -> Result<Command, Sdi12Error<()>> {
 
         // Change Address: aAb!
         body if body.starts_with('A') && body.len() == 2 => {
-            let new_addr_char = body.chars().nth(1).unwrap(); // Safe due to len check
-            let new_address = Sdi12Addr::new(new_addr_char)?;
-            Ok(Command::ChangeAddress { address, new_address })
+            let &[_, new_addr_byte] = body.as_bytes() else {
+                return Err(Sdi12Error::InvalidFormat);
+            };
+            let new_address = Sdi12Addr::new(char::from(new_addr_byte))?;
+            Command::change_address(address, new_address)
         }
 
         // Measurement: aM[n]! / aMC[n]! / aC[n]! / aCC[n]!
@@ -118,37 +135,25 @@ fn parse_measurement_command(
     address: Sdi12Addr,
     body: &str,
 ) -> Result<Command, Sdi12Error<()>> {
-    let (cmd_code, index_str) = match body.len() {
-        1 => (body, None), // M, C
-        2 => {
-             // MC, CC, M1-9, C1-9
-            let code_part = &body[..body.len() - 1]; // M, C, MC, CC
-            let index_part = &body[body.len() - 1..];
-            if index_part.chars().all(|c| c.is_ascii_digit()) {
-                (code_part, Some(index_part))
-            } else {
-                 // Must be MC or CC
-                 (body, None)
-            }
-        }
-        3 => {
-            // MC1-9, CC1-9
-             let code_part = &body[..body.len() - 1]; // MC, CC
-             let index_part = &body[body.len() - 1..];
-            if index_part.chars().all(|c| c.is_ascii_digit()) {
-                (code_part, Some(index_part))
-            } else {
-                 return Err(Sdi12Error::InvalidFormat); // e.g., MCX
-            }
+    // Longest code first, so "MC1" isn't read as "M" + "C1"
+    let cmd_code = ["MC", "CC", "M", "C"]
+        .into_iter()
+        .find(|code| body.starts_with(code))
+        .ok_or(Sdi12Error::InvalidFormat)?;
+    let index_str = match &body[cmd_code.len()..] {
+        "" => None,
+        digit if digit.len() == 1 && digit.as_bytes()[0].is_ascii_digit() => Some(digit),
+        // Only one index digit: M10 or M12 names a group past M9
+        digits if digits.bytes().all(|b| b.is_ascii_digit()) => {
+            return Err(Sdi12Error::InvalidCommandIndex(CommandIndexError::MeasurementOutOfRange))
         }
-        _ => return Err(Sdi12Error::InvalidFormat),
+        _ => return Err(Sdi12Error::InvalidFormat), // e.g. MCX, M1C
     };
 
-    let index_val = index_str
-        .map(|s| s.parse::<u8>().map_err(|_| Sdi12Error::InvalidFormat)) // Invalid number format
-        .transpose()?; // Convert Option<Result<u8, _>> to Result<Option<u8>, _>
-
-    let index = MeasurementIndex::new(index_val)?; // Returns InvalidCommandIndex error
+    let index = match index_str {
+        Some(s) => MeasurementIndex::from_ascii_digit(s.as_bytes()[0])?, // Returns InvalidCommandIndex error
+        None => MeasurementIndex::Base,
+    };
 
     match cmd_code {
         "M" => Ok(Command::StartMeasurement { address, index }),
@@ -161,10 +166,10 @@ fn parse_measurement_command(
 
 // --- Helper: Parse D/DB commands ---
 fn parse_data_command(address: Sdi12Addr, body: &str) -> Result<Command, Sdi12Error<()>> {
-    let (is_binary, index_str) = if body.starts_with("DB") {
-        (true, &body[2..])
-    } else if body.starts_with('D') {
-        (false, &body[1..])
+    let (is_binary, index_str) = if let Some(rest) = body.strip_prefix("DB") {
+        (true, rest)
+    } else if let Some(rest) = body.strip_prefix('D') {
+        (false, rest)
     } else {
         return Err(Sdi12Error::InvalidFormat); // Should not happen if called correctly
     };
@@ -188,10 +193,10 @@ fn parse_continuous_command(
     address: Sdi12Addr,
     body: &str,
 ) -> Result<Command, Sdi12Error<()>> {
-    let (is_crc, index_str) = if body.starts_with("RC") {
-        (true, &body[2..])
-    } else if body.starts_with('R') {
-        (false, &body[1..])
+    let (is_crc, index_str) = if let Some(rest) = body.strip_prefix("RC") {
+        (true, rest)
+    } else if let Some(rest) = body.strip_prefix('R') {
+        (false, rest)
     } else {
         return Err(Sdi12Error::InvalidFormat);
     };
@@ -200,8 +205,7 @@ fn parse_continuous_command(
         return Err(Sdi12Error::InvalidFormat); // Needs exactly one index digit
     }
 
-    let index_val = index_str.parse::<u8>().map_err(|_| Sdi12Error::InvalidFormat)?;
-    let index = ContinuousIndex::new(index_val)?; // Returns InvalidCommandIndex
+    let index = ContinuousIndex::from_ascii_digit(index_str.as_bytes()[0])?; // Returns InvalidCommandIndex
 
     if is_crc {
         Ok(Command::ReadContinuousCRC { address, index })
@@ -284,10 +288,10 @@ fn parse_identify_command(
              match base_code {
                  "IM" | "IMC" | "IC" | "ICC" => {
                     // Measurement/Concurrent Parameter
-                    let m_index_val = index_opt_str
-                        .map(|s| s.parse::<u8>().map_err(|_| Sdi12Error::InvalidFormat))
-                        .transpose()?;
-                    let m_index = MeasurementIndex::new(m_index_val)?; // map CommandIndexError -> Sdi12Error
+                    let m_index = match index_opt_str {
+                        Some(s) => MeasurementIndex::from_ascii_digit(s.as_bytes()[0])?, // map CommandIndexError -> Sdi12Error
+                        None => MeasurementIndex::Base,
+                    };
                     match base_code {
                         "IM" => Ok(Command::IdentifyMeasurementParameter(IdentifyMeasurementParameterCommand::Measurement { address, m_index, param_index })),
                         "IMC" => Ok(Command::IdentifyMeasurementParameter(IdentifyMeasurementParameterCommand::MeasurementCRC { address, m_index, param_index })),
@@ -302,10 +306,8 @@ fn parse_identify_command(
                 }
                 "IR" | "IRC" => {
                     // Continuous Parameter
-                    let r_index_val = index_opt_str
-                         .ok_or(Sdi12Error::InvalidFormat)? // IR/IRC needs R index
-                         .parse::<u8>().map_err(|_| Sdi12Error::InvalidFormat)?;
-                    let r_index = ContinuousIndex::new(r_index_val)?; // map CommandIndexError -> Sdi12Error
+                    let r_index_str = index_opt_str.ok_or(Sdi12Error::InvalidFormat)?; // IR/IRC needs R index
+                    let r_index = ContinuousIndex::from_ascii_digit(r_index_str.as_bytes()[0])?; // map CommandIndexError -> Sdi12Error
                     match base_code {
                         "IR" => Ok(Command::IdentifyMeasurementParameter(IdentifyMeasurementParameterCommand::ReadContinuous { address, r_index, param_index })),
                         "IRC" => Ok(Command::IdentifyMeasurementParameter(IdentifyMeasurementParameterCommand::ReadContinuousCRC { address, r_index, param_index })),
@@ -330,10 +332,10 @@ fn parse_identify_command(
              match base_code {
                 "IM" | "IMC" | "IC" | "ICC" => {
                      // Measurement/Concurrent Identify
-                    let index_val = index_opt_str
-                        .map(|s| s.parse::<u8>().map_err(|_| Sdi12Error::InvalidFormat))
-                        .transpose()?;
-                    let index = MeasurementIndex::new(index_val)?; // map CommandIndexError -> Sdi12Error
+                    let index = match index_opt_str {
+                        Some(s) => MeasurementIndex::from_ascii_digit(s.as_bytes()[0])?, // map CommandIndexError -> Sdi12Error
+                        None => MeasurementIndex::Base,
+                    };
                      match base_code {
                         "IM" => Ok(Command::IdentifyMeasurement(IdentifyMeasurementCommand::Measurement { address, index })),
                         "IMC" => Ok(Command::IdentifyMeasurement(IdentifyMeasurementCommand::MeasurementCRC { address, index })),
@@ -381,6 +383,7 @@ mod tests {
         assert_eq!(parse_command(b"1I!").unwrap(), Command::SendIdentification { address: addr('1') });
         assert_eq!(parse_command(b"?!").unwrap(), Command::AddressQuery);
         assert_eq!(parse_command(b"2A3!").unwrap(), Command::ChangeAddress { address: addr('2'), new_address: addr('3') });
+        assert_eq!(parse_command(b"2A?!"), Err(Sdi12Error::InvalidAddress('?')));
         assert_eq!(parse_command(b"4V!").unwrap(), Command::StartVerification { address: addr('4') });
         assert_eq!(parse_command(b"5HA!").unwrap(), Command::StartHighVolumeASCII { address: addr('5') });
         assert_eq!(parse_command(b"6HB!").unwrap(), Command::StartHighVolumeBinary { address: addr('6') });
@@ -471,12 +474,29 @@ mod tests {
         assert_eq!(parse_command(b"1SOME_CMD_123!").unwrap(), Command::ExtendedCommand { address: addr('1'), command_body: "SOME_CMD_123".to_string() });
     }
 
+    #[test]
+    fn test_parse_over_long_commands() {
+        let mut long = [b'X'; MAX_COMMAND_LEN + 1];
+        long[0] = b'0';
+        long[MAX_COMMAND_LEN] = b'!';
+        assert!(matches!(parse_command(&long), Err(Sdi12Error::InvalidFormat)));
+        #[cfg(feature = "alloc")]
+        {
+            long[MAX_COMMAND_LEN - 1] = b'!';
+            assert!(matches!(parse_command(&long[..MAX_COMMAND_LEN]), Ok(Command::ExtendedCommand { .. }))); // At the limit
+        }
+
+        assert!(matches!(parse_command_with_max_len(b"0XABCDEF!", 8), Err(Sdi12Error::InvalidFormat)));
+        assert!(matches!(parse_command_with_max_len(b"0D123!", 5), Err(Sdi12Error::InvalidFormat)));
+        assert_eq!(parse_command_with_max_len(b"0D12!", 5), Ok(Command::SendData { address: addr('0'), index: DataIndex::new(12).unwrap() }));
+    }
+
     #[test]
     fn test_parse_invalid_formats() {
         // Basic structure
         assert!(matches!(parse_command(b""), Err(Sdi12Error::InvalidFormat)));
         assert!(matches!(parse_command(b"0"), Err(Sdi12Error::InvalidFormat))); // Missing !
-        assert!(matches!(parse_command(b"!"), Err(Sdi12Error::InvalidFormat))); // Invalid address
+        assert!(matches!(parse_command(b"!"), Err(Sdi12Error::InvalidAddress('!')))); // No address
         assert!(matches!(parse_command(b"0M"), Err(Sdi12Error::InvalidFormat))); // Missing !
         assert!(matches!(parse_command(b"?A!"), Err(Sdi12Error::InvalidFormat))); // Query cannot have body
 
@@ -507,6 +527,9 @@ mod tests {
 
         assert!(matches!(parse_command(b"1MA!"), Err(Sdi12Error::InvalidFormat))); // Invalid char after M
         assert!(matches!(parse_command(b"2MCC!"), Err(Sdi12Error::InvalidFormat))); // Double C
+        assert!(matches!(parse_command(b"0MC1X!"), Err(Sdi12Error::InvalidFormat)));
+        assert!(matches!(parse_command(b"0M1C!"), Err(Sdi12Error::InvalidFormat)));
+        assert!(matches!(parse_command(b"0CM!"), Err(Sdi12Error::InvalidFormat)));
         assert!(matches!(parse_command(b"3DA!"), Err(Sdi12Error::InvalidFormat))); // D needs digits
         assert!(matches!(parse_command(b"4D!"), Err(Sdi12Error::InvalidFormat))); // D needs digits
         assert!(matches!(parse_command(b"5R!"), Err(Sdi12Error::InvalidFormat))); // R needs digit
@@ -516,6 +539,10 @@ mod tests {
         // Indices
         assert!(matches!(parse_command(b"0M0!"), Err(Sdi12Error::InvalidCommandIndex(CommandIndexError::MeasurementOutOfRange))));
         assert!(matches!(parse_command(b"1M10!"), Err(Sdi12Error::InvalidCommandIndex(CommandIndexError::MeasurementOutOfRange))));
+        // Two or more index digits
+        for command in [&b"0M12!"[..], b"0MC12!", b"0C12!", b"0CC123!"] {
+            assert!(matches!(parse_command(command), Err(Sdi12Error::InvalidCommandIndex(CommandIndexError::MeasurementOutOfRange))));
+        }
         assert!(matches!(parse_command(b"2D1000!"), Err(Sdi12Error::InvalidCommandIndex(CommandIndexError::DataOutOfRange))));
         assert!(matches!(parse_command(b"3R10!"), Err(Sdi12Error::InvalidFormat))); // R has only 1 digit index
         assert!(matches!(parse_command(b"4RC10!"), Err(Sdi12Error::InvalidFormat))); // RC has only 1 digit index
@@ -534,6 +561,41 @@ mod tests {
     }
 
     // Test that CommandIndexError maps correctly (via From trait in error.rs)
+    #[test]
+    fn test_parse_minimal_commands() {
+        assert_eq!(parse_command(b"0!"), Ok(Command::AcknowledgeActive { address: addr('0') }));
+        assert_eq!(parse_command(b"?!"), Ok(Command::AddressQuery));
+        // Nothing, or no terminator
+        assert_eq!(parse_command(b""), Err(Sdi12Error::InvalidFormat));
+        assert_eq!(parse_command(b"0"), Err(Sdi12Error::InvalidFormat));
+        assert_eq!(parse_command(b"?"), Err(Sdi12Error::InvalidFormat));
+        // A terminator without an address, even repeated
+        assert_eq!(parse_command(b"!"), Err(Sdi12Error::InvalidAddress('!')));
+        assert_eq!(parse_command(b"!!"), Err(Sdi12Error::InvalidAddress('!')));
+        // `?` takes no body, not even another `?` or `!`
+        for command in [&b"?A!"[..], b"??!", b"?!!", b"?0!", b"? !"] {
+            assert_eq!(parse_command(command), Err(Sdi12Error::InvalidFormat), "{:?}", core::str::from_utf8(command));
+        }
+        // The length limit applies before anything else
+        assert_eq!(parse_command_with_max_len(b"0!", 1), Err(Sdi12Error::InvalidFormat));
+        assert_eq!(parse_command_with_max_len(b"!", 0), Err(Sdi12Error::InvalidFormat));
+    }
+
+    #[test]
+    fn test_parse_rejects_non_ascii_and_malformed_identify() {
+        // Multi-byte UTF-8 ("é" is 0xC3 0xA9) as the address, the new address or in the body
+        assert_eq!(parse_command("é!".as_bytes()), Err(Sdi12Error::InvalidAddress('\u{C3}')));
+        assert_eq!(parse_command("0Aé!".as_bytes()), Err(Sdi12Error::InvalidFormat));
+        assert_eq!(parse_command("0A\u{C3}!".as_bytes()), Err(Sdi12Error::InvalidFormat));
+        assert_eq!(parse_command("0M€!".as_bytes()), Err(Sdi12Error::InvalidFormat));
+        assert_eq!(parse_command(&[b'0', b'A', 0xFF, b'!']), Err(Sdi12Error::InvalidFormat));
+        // IR/IRC with a missing, malformed or extra index
+        for command in [&b"0IR_001!"[..], b"0IRC_001!", b"0IRX_001!", b"0IR10_001!", b"0IR0_!", b"0IR0_1!", b"0IR0__001!", b"0IR0_001_!", b"0IR0!"] {
+            assert_eq!(parse_command(command), Err(Sdi12Error::InvalidFormat), "{:?}", core::str::from_utf8(command));
+        }
+        assert_eq!(parse_command(b"0IR0_000!"), Err(Sdi12Error::InvalidCommandIndex(CommandIndexError::IdentifyParamOutOfRange)));
+    }
+
     #[test]
     fn test_index_error_mapping() {
          // Simulate a failure during index parsing/validation
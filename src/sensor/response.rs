@@ -0,0 +1,137 @@
+// src/sensor/response.rs
+
+use crate::common::types::Sdi12Value;
+use arrayvec::ArrayString;
+use core::fmt;
+
+/// Longest `aI!` payload: `ll` + 8 vendor + 6 model + 3 version + 13 optional.
+pub const MAX_IDENTIFICATION_LEN: usize = 32;
+
+/// What a `SensorHandler` wants to send back for a command.
+///
+/// The runner adds the address, the CRC (on the data of CRC measurements,
+/// `aRCn!` and CRC metadata replies) and `<CR><LF>`; the handler only
+/// describes the payload.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SensorResponse<'a> {
+    /// Address only (`a<CR><LF>`). Also the spec's "no data" reply to `aDn!`.
+    Acknowledge,
+    /// `atttn`, `atttnn` or `atttnnn`. The number of count digits follows the
+    /// command (1 for M/V, 2 for C, 3 for HA/HB).
+    Timing { time_seconds: u16, values_count: u16 },
+    /// `<values>` for D/R commands.
+    Values(&'a [Sdi12Value]),
+    /// Payload sent verbatim after the address (identification, metadata, extended commands).
+    Text(&'a str),
+    /// A checked `aI!` payload, built with `SensorResponse::identification`.
+    Identification(ArrayString<MAX_IDENTIFICATION_LEN>),
+    /// Send nothing.
+    NoResponse,
+}
+
+/// A field of the `aI!` response, as reported by `IdentificationError`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IdentificationField {
+    Sdi12Version,
+    Vendor,
+    Model,
+    SensorVersion,
+    Optional,
+}
+
+/// Why `SensorResponse::identification` rejected its input.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IdentificationError {
+    /// The SDI-12 version isn't two digits (`"14"` for v1.4).
+    InvalidSdi12Version,
+    /// The field is longer than the spec allows.
+    FieldTooLong { field: IdentificationField, max: usize },
+    /// The field holds a character outside printable ASCII.
+    InvalidCharacter { field: IdentificationField },
+}
+
+impl fmt::Display for IdentificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdentificationError::InvalidSdi12Version => write!(f, "SDI-12 version must be two digits"),
+            IdentificationError::FieldTooLong { field, max } => {
+                write!(f, "Identification field {:?} longer than {} characters", field, max)
+            }
+            IdentificationError::InvalidCharacter { field } => {
+                write!(f, "Identification field {:?} contains a non-printable character", field)
+            }
+        }
+    }
+}
+
+impl SensorResponse<'static> {
+    /// Builds the `aI!` payload `llccccccccmmmmmmvvv[xxx...]`.
+    ///
+    /// `sdi12_version` must be two digits (`"14"`). `vendor`, `model` and
+    /// `sensor_version` are padded with spaces to 8, 6 and 3 characters;
+    /// `optional` (serial number etc.) is sent as given, at most 13 characters.
+    /// Longer fields and characters outside printable ASCII are errors rather
+    /// than being truncated, so a sensor can't emit a malformed identification.
+    pub fn identification(
+        sdi12_version: &str,
+        vendor: &str,
+        model: &str,
+        sensor_version: &str,
+        optional: &str,
+    ) -> Result<Self, IdentificationError> {
+        if sdi12_version.len() != 2 || !sdi12_version.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(IdentificationError::InvalidSdi12Version);
+        }
+        let mut payload = ArrayString::new();
+        payload.push_str(sdi12_version);
+        for (field, text, width, pad) in [
+            (IdentificationField::Vendor, vendor, 8, true),
+            (IdentificationField::Model, model, 6, true),
+            (IdentificationField::SensorVersion, sensor_version, 3, true),
+            (IdentificationField::Optional, optional, 13, false),
+        ] {
+            if !text.bytes().all(|b| (0x20..=0x7E).contains(&b)) {
+                return Err(IdentificationError::InvalidCharacter { field });
+            }
+            if text.len() > width {
+                return Err(IdentificationError::FieldTooLong { field, max: width });
+            }
+            payload.push_str(text);
+            if pad {
+                (text.len()..width).for_each(|_| payload.push(' '));
+            }
+        }
+        Ok(SensorResponse::Identification(payload))
+    }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identification_builder() {
+        let Ok(SensorResponse::Identification(id)) = SensorResponse::identification("14", "ACME", "TH1", "1.0", "SN42") else {
+            panic!("expected identification");
+        };
+        assert_eq!(id.as_str(), "14ACME    TH1   1.0SN42");
+
+        let full = SensorResponse::identification("13", "VENDORXY", "MODEL1", "100", "1234567890123");
+        assert!(matches!(full, Ok(SensorResponse::Identification(id)) if id.len() == MAX_IDENTIFICATION_LEN));
+
+        assert_eq!(SensorResponse::identification("1.4", "A", "B", "C", ""), Err(IdentificationError::InvalidSdi12Version));
+        assert_eq!(
+            SensorResponse::identification("14", "VENDORXYZ", "B", "C", ""),
+            Err(IdentificationError::FieldTooLong { field: IdentificationField::Vendor, max: 8 })
+        );
+        assert_eq!(
+            SensorResponse::identification("14", "A", "B", "C", "12345678901234"),
+            Err(IdentificationError::FieldTooLong { field: IdentificationField::Optional, max: 13 })
+        );
+        assert_eq!(
+            SensorResponse::identification("14", "A", "B\r", "C", ""),
+            Err(IdentificationError::InvalidCharacter { field: IdentificationField::Model })
+        );
+    }
+}
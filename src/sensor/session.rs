@@ -0,0 +1,158 @@
+// src/sensor/session.rs
+
+use super::formatter::format_response;
+use super::parser::parse_command;
+use super::response::SensorResponse;
+use super::MAX_COMMAND_LEN;
+use crate::common::{
+    address::Sdi12Addr,
+    command::{Command, DataIndex},
+    error::Sdi12Error,
+};
+use core::fmt::Debug;
+
+/// Which handler call answers a command, as decided by `route`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(super) enum Route {
+    /// Not for this sensor: send nothing.
+    Ignore,
+    /// Address-only reply, no handler call.
+    Acknowledge,
+    /// `change_address`, then an address-only reply.
+    ChangeAddress(Sdi12Addr),
+    Identification,
+    StartMeasurement,
+    SendData(DataIndex),
+    Other,
+}
+
+/// Applies the spec-level checks both runners share: commands for other
+/// addresses are ignored, and `aDn!` beyond `max_data_index` gets an
+/// address-only reply without reaching the handler.
+pub(super) fn route(command: &Command, address: Sdi12Addr, max_data_index: DataIndex) -> Route {
+    if !matches!(command, Command::AddressQuery) && command.address() != address {
+        return Route::Ignore;
+    }
+    match command {
+        Command::AcknowledgeActive { .. } | Command::AddressQuery => Route::Acknowledge,
+        Command::ChangeAddress { new_address, .. } => Route::ChangeAddress(*new_address),
+        Command::SendIdentification { .. } => Route::Identification,
+        Command::StartMeasurement { .. }
+        | Command::StartMeasurementCRC { .. }
+        | Command::StartConcurrentMeasurement { .. }
+        | Command::StartConcurrentMeasurementCRC { .. }
+        | Command::StartVerification { .. }
+        | Command::StartHighVolumeASCII { .. }
+        | Command::StartHighVolumeBinary { .. } => Route::StartMeasurement,
+        Command::SendData { index, .. } if index.value() > max_data_index.value() => Route::Acknowledge,
+        Command::SendData { index, .. } => Route::SendData(*index),
+        _ => Route::Other,
+    }
+}
+
+/// The I/O-independent state of a sensor runner: the command being received
+/// and whether the data being served carries a CRC.
+#[derive(Debug)]
+pub(super) struct Session {
+    command_buf: [u8; MAX_COMMAND_LEN],
+    command_len: usize,
+    /// Whether the last measurement started (M, C, V, HA or HB) puts a CRC
+    /// on its `aDn!` data.
+    crc_on_data: bool,
+}
+
+impl Session {
+    pub(super) fn new() -> Self {
+        Session { command_buf: [0; MAX_COMMAND_LEN], command_len: 0, crc_on_data: false }
+    }
+
+    /// Adds a received byte to the command being assembled. Once its `!`
+    /// arrives, returns the parsed command, or the parse error of a malformed
+    /// one (which the runner ignores, as the spec requires).
+    pub(super) fn push_byte(&mut self, byte: u8) -> Option<Result<Command, Sdi12Error<()>>> {
+        // A break shows up as NUL bytes. It resets the command state, as the spec
+        // requires, so noise received before it can't spoil the next command.
+        if byte == 0 {
+            self.command_len = 0;
+            return None;
+        }
+        if self.command_len == MAX_COMMAND_LEN {
+            // Over-long garbage: drop it and resynchronise on the next command.
+            self.command_len = 0;
+        }
+        self.command_buf[self.command_len] = byte;
+        self.command_len += 1;
+
+        if byte != b'!' {
+            return None;
+        }
+        let len = core::mem::take(&mut self.command_len);
+        Some(parse_command(&self.command_buf[..len]))
+    }
+
+    /// Formats the handler's reply to `command` into `buf`; see `format_response`.
+    ///
+    /// Timing replies (`atttn`) never carry a CRC. The `aDn!` data of a CRC
+    /// measurement (`aMC!`, `aCC!`) or a high-volume ASCII one does, as do
+    /// `aRCn!` and the `I?C_nnn` metadata replies; starting a measurement
+    /// sets what the data commands that follow send.
+    pub(super) fn format_reply<E: Debug>(
+        &mut self,
+        address: Sdi12Addr,
+        response: &SensorResponse<'_>,
+        command: &Command,
+        buf: &mut [u8],
+    ) -> Result<Option<usize>, Sdi12Error<E>> {
+        let crc = match command {
+            Command::IdentifyMeasurement(_) => false,
+            Command::StartHighVolumeASCII { .. } | Command::StartHighVolumeBinary { .. } => {
+                self.crc_on_data = true;
+                false
+            }
+            _ if command.expects_timing_response() => {
+                self.crc_on_data = command.is_crc_variant();
+                false
+            }
+            Command::SendData { .. } => self.crc_on_data,
+            _ => command.is_crc_variant(),
+        };
+        format_response(address, response, command, crc, buf)
+    }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::command::MeasurementIndex;
+
+    fn addr(c: char) -> Sdi12Addr { Sdi12Addr::new(c).unwrap() }
+
+    #[test]
+    fn test_route() {
+        let max = DataIndex::new(1).unwrap();
+        let route_of = |bytes: &[u8]| route(&parse_command(bytes).unwrap(), addr('0'), max);
+        assert_eq!(route_of(b"1M!"), Route::Ignore);
+        assert_eq!(route_of(b"?!"), Route::Acknowledge);
+        assert_eq!(route_of(b"0A5!"), Route::ChangeAddress(addr('5')));
+        assert_eq!(route_of(b"0MC!"), Route::StartMeasurement);
+        assert_eq!(route_of(b"0D1!"), Route::SendData(max));
+        assert_eq!(route_of(b"0D2!"), Route::Acknowledge);
+        assert_eq!(route_of(b"0R0!"), Route::Other);
+    }
+
+    #[test]
+    fn test_push_byte_assembles_commands() {
+        let mut session = Session::new();
+        let mut feed = |bytes: &[u8]| bytes.iter().filter_map(|&b| session.push_byte(b)).last();
+        assert_eq!(feed(b"\x00\x000M"), None);
+        assert_eq!(feed(b"!"), Some(Ok(Command::StartMeasurement { address: addr('0'), index: MeasurementIndex::Base })));
+        assert!(matches!(feed(b"?M!"), Some(Err(_))));
+        // Garbage longer than any command is dropped, the next command still parses
+        assert_eq!(feed(&[b'#'; MAX_COMMAND_LEN + 3]), None);
+        assert!(matches!(feed(b"0!"), Some(Err(_))));
+        assert_eq!(feed(b"0!"), Some(Ok(Command::AcknowledgeActive { address: addr('0') })));
+        // A break after partial noise discards the noise
+        assert_eq!(feed(b"#\x00\x000M!"), Some(Ok(Command::StartMeasurement { address: addr('0'), index: MeasurementIndex::Base })));
+    }
+}
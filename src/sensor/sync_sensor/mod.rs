@@ -0,0 +1,285 @@
+// src/sensor/sync_sensor/mod.rs
+
+use super::handler::SensorHandler;
+use super::parser::parse_command;
+use super::response::SensorResponse;
+use super::session::{route, Route, Session};
+use super::MAX_RESPONSE_LEN;
+use crate::common::{
+    command::Command,
+    error::Sdi12Error,
+    hal_traits::{Sdi12Serial, Sdi12Timer},
+    timing,
+};
+use core::fmt::Debug;
+use core::time::Duration;
+
+/// Blocking sensor runner: reads commands from the bus, dispatches them to a
+/// `SensorHandler` and writes the formatted responses.
+///
+/// Spec-level behaviour lives here rather than in each handler: commands for
+/// other addresses and malformed commands are ignored, and `aDn!` beyond
+/// `SensorHandler::max_data_index` gets an address-only reply.
+#[derive(Debug)]
+pub struct SyncSensor<IF, H> {
+    interface: IF,
+    handler: H,
+    session: Session,
+    response_delay: Duration,
+}
+
+impl<IF, H> SyncSensor<IF, H>
+where
+    IF: Sdi12Serial + Sdi12Timer,
+    IF::Error: Debug,
+    H: SensorHandler,
+{
+    pub fn new(interface: IF, handler: H) -> Self {
+        SyncSensor {
+            interface,
+            handler,
+            session: Session::new(),
+            response_delay: timing::SENSOR_PRE_RESPONSE_MARKING,
+        }
+    }
+
+    /// Marking time held before each response (spec: 8.33 ms, the default).
+    pub fn response_delay(&self) -> Duration {
+        self.response_delay
+    }
+
+    /// Overrides the pre-response marking time, e.g. zero for test harnesses.
+    pub fn set_response_delay(&mut self, delay: Duration) {
+        self.response_delay = delay;
+    }
+
+    pub fn handler(&self) -> &H {
+        &self.handler
+    }
+
+    pub fn handler_mut(&mut self) -> &mut H {
+        &mut self.handler
+    }
+
+    /// Releases the interface and handler.
+    pub fn release(self) -> (IF, H) {
+        (self.interface, self.handler)
+    }
+
+    /// Reads whatever bytes are available and handles a command once its `!` arrives.
+    ///
+    /// Returns `WouldBlock` while waiting for more input, so it can be driven from a
+    /// main loop or with `nb::block!`.
+    pub fn poll(&mut self) -> nb::Result<(), Sdi12Error<IF::Error>> {
+        loop {
+            let byte = self.interface.read_byte().map_err(|e| e.map(Sdi12Error::Io))?;
+            match self.session.push_byte(byte) {
+                Some(Ok(command)) => return self.respond(&command).map_err(nb::Error::Other),
+                Some(Err(_)) => return Ok(()), // Malformed: ignored, as in `process_command`
+                None => {}
+            }
+        }
+    }
+
+    /// Parses a complete command (`a...!`) and answers it if it is addressed to this sensor.
+    /// Malformed commands are silently ignored, as the spec requires.
+    pub fn process_command(&mut self, bytes: &[u8]) -> Result<(), Sdi12Error<IF::Error>> {
+        match parse_command(bytes) {
+            Ok(command) => self.respond(&command),
+            Err(_) => Ok(()),
+        }
+    }
+
+    /// Dispatches a parsed command to the handler and writes the reply.
+    fn respond(&mut self, command: &Command) -> Result<(), Sdi12Error<IF::Error>> {
+        let mut address = self.handler.address();
+        let response = match route(command, address, self.handler.max_data_index()) {
+            Route::Ignore => return Ok(()),
+            Route::Acknowledge => SensorResponse::Acknowledge,
+            Route::ChangeAddress(new_address) => {
+                if self.handler.change_address(new_address) {
+                    address = new_address;
+                }
+                SensorResponse::Acknowledge
+            }
+            Route::Identification => self.handler.identification(),
+            Route::StartMeasurement => self.handler.start_measurement(command),
+            Route::SendData(index) => self.handler.send_data(index),
+            Route::Other => self.handler.handle_other(command),
+        };
+
+        let mut out = [0u8; MAX_RESPONSE_LEN];
+        let Some(len) = self.session.format_reply(address, &response, command, &mut out)? else {
+            return Ok(());
+        };
+        if !self.response_delay.is_zero() {
+            self.interface.delay_us(self.response_delay.as_micros() as u32);
+        }
+        for byte in &out[..len] {
+            nb::block!(self.interface.write_byte(*byte)).map_err(Sdi12Error::Io)?;
+        }
+        nb::block!(self.interface.flush()).map_err(Sdi12Error::Io)?;
+        Ok(())
+    }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{
+        address::Sdi12Addr,
+        command::DataIndex,
+        types::Sdi12Value,
+        FrameFormat,
+    };
+    use core::time::Duration;
+    use nb::Result as NbResult;
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+    struct MockInstant(u64);
+    impl core::ops::Add<Duration> for MockInstant { type Output = Self; fn add(self, rhs: Duration) -> Self { MockInstant(self.0.saturating_add(rhs.as_micros() as u64)) } }
+    impl core::ops::Sub<MockInstant> for MockInstant { type Output = Duration; fn sub(self, rhs: MockInstant) -> Duration { Duration::from_micros(self.0.saturating_sub(rhs.0)) } }
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    struct MockCommError;
+
+    struct MockInterface {
+        read_queue: [u8; 32],
+        read_len: usize,
+        read_pos: usize,
+        written: [u8; 96],
+        write_len: usize,
+        delayed_us: u64,
+        writes_before_delay: Option<usize>,
+    }
+    impl MockInterface {
+        fn new(input: &[u8]) -> Self {
+            let mut read_queue = [0u8; 32];
+            read_queue[..input.len()].copy_from_slice(input);
+            MockInterface { read_queue, read_len: input.len(), read_pos: 0, written: [0; 96], write_len: 0, delayed_us: 0, writes_before_delay: None }
+        }
+        fn written(&self) -> &[u8] { &self.written[..self.write_len] }
+    }
+    impl Sdi12Timer for MockInterface {
+        type Instant = MockInstant;
+        fn delay_us(&mut self, us: u32) {
+            self.writes_before_delay.get_or_insert(self.write_len);
+            self.delayed_us += us as u64;
+        }
+        fn delay_ms(&mut self, ms: u32) { self.delay_us(ms * 1000); }
+        fn now(&self) -> Self::Instant { MockInstant(self.delayed_us) }
+    }
+    impl Sdi12Serial for MockInterface {
+        type Error = MockCommError;
+        fn read_byte(&mut self) -> NbResult<u8, Self::Error> {
+            if self.read_pos < self.read_len { self.read_pos += 1; Ok(self.read_queue[self.read_pos - 1]) } else { Err(nb::Error::WouldBlock) }
+        }
+        fn write_byte(&mut self, byte: u8) -> NbResult<(), Self::Error> { self.written[self.write_len] = byte; self.write_len += 1; Ok(()) }
+        fn flush(&mut self) -> NbResult<(), Self::Error> { Ok(()) }
+        fn send_break(&mut self) -> NbResult<(), Self::Error> { Ok(()) }
+        fn set_config(&mut self, _config: FrameFormat) -> Result<(), Self::Error> { Ok(()) }
+    }
+
+    struct TestHandler {
+        address: Sdi12Addr,
+        values: [Sdi12Value; 2],
+        send_data_calls: usize,
+    }
+    impl SensorHandler for TestHandler {
+        fn address(&self) -> Sdi12Addr { self.address }
+        fn identification(&mut self) -> SensorResponse<'_> { SensorResponse::identification("14", "TESTCORP", "MODEL1", "001", "").unwrap() }
+        fn start_measurement(&mut self, _command: &Command) -> SensorResponse<'_> {
+            SensorResponse::Timing { time_seconds: 1, values_count: 2 }
+        }
+        fn send_data(&mut self, _index: DataIndex) -> SensorResponse<'_> {
+            self.send_data_calls += 1;
+            SensorResponse::Values(&self.values)
+        }
+        fn max_data_index(&self) -> DataIndex { DataIndex::new(0).unwrap() }
+    }
+
+    fn sensor(input: &[u8]) -> SyncSensor<MockInterface, TestHandler> {
+        let handler = TestHandler {
+            address: Sdi12Addr::new('0').unwrap(),
+            values: [Sdi12Value::new(1.5), Sdi12Value::new(-2.0)],
+            send_data_calls: 0,
+        };
+        SyncSensor::new(MockInterface::new(input), handler)
+    }
+
+    #[test]
+    fn test_poll_measurement_and_data() {
+        let mut s = sensor(b"0M!");
+        assert!(s.poll().is_ok());
+        assert_eq!(s.interface.written(), b"00012\r\n");
+
+        let mut s = sensor(b"0D0!");
+        assert!(s.poll().is_ok());
+        assert_eq!(s.interface.written(), b"0+1.5-2\r\n");
+        assert!(matches!(s.poll(), Err(nb::Error::WouldBlock)));
+    }
+
+    #[test]
+    fn test_crc_on_data_of_crc_measurements_only() {
+        let mut s = sensor(b"0MC!0D0!0M!0D0!");
+        for _ in 0..4 {
+            assert!(s.poll().is_ok());
+        }
+        let (timing_crc, rest) = s.interface.written().split_at(7);
+        let (data_crc, rest) = rest.split_at(12);
+        assert_eq!(timing_crc, b"00012\r\n"); // Never a CRC on the timing reply
+        assert!(crate::common::crc::verify_response_crc_ascii::<()>(&data_crc[..10]).is_ok());
+        assert!(data_crc.starts_with(b"0+1.5-2") && data_crc.ends_with(b"\r\n"));
+        assert_eq!(rest, b"00012\r\n0+1.5-2\r\n");
+    }
+
+    #[test]
+    fn test_pre_response_marking_delay() {
+        let mut s = sensor(b"0!");
+        assert_eq!(s.response_delay(), timing::SENSOR_PRE_RESPONSE_MARKING);
+        assert!(s.poll().is_ok());
+        assert_eq!(s.interface.writes_before_delay, Some(0)); // Delay came before the first byte
+        assert_eq!(s.interface.delayed_us as u128, timing::SENSOR_PRE_RESPONSE_MARKING.as_micros());
+
+        let mut s = sensor(b"0!");
+        s.set_response_delay(Duration::ZERO);
+        assert!(s.poll().is_ok());
+        assert_eq!(s.interface.written(), b"0\r\n");
+        assert_eq!(s.interface.writes_before_delay, None);
+    }
+
+    #[test]
+    fn test_data_index_beyond_max_is_not_dispatched() {
+        let mut s = sensor(b"0D99!");
+        assert!(s.poll().is_ok());
+        assert_eq!(s.interface.written(), b"0\r\n");
+        assert_eq!(s.handler().send_data_calls, 0);
+    }
+
+    #[test]
+    fn test_ignores_other_addresses_and_garbage() {
+        let mut s = sensor(b"1M!");
+        assert!(s.poll().is_ok());
+        assert!(s.interface.written().is_empty());
+
+        let mut s = sensor(b"0Z9Q!");
+        assert!(s.poll().is_ok());
+        assert!(s.interface.written().is_empty());
+
+        // Unhandled commands default to silence
+        let mut s = sensor(b"0R0!");
+        assert!(s.poll().is_ok());
+        assert!(s.interface.written().is_empty());
+    }
+
+    #[test]
+    fn test_address_query_and_refused_change() {
+        let mut s = sensor(b"?!");
+        assert!(s.poll().is_ok());
+        assert_eq!(s.interface.written(), b"0\r\n");
+
+        let mut s = sensor(b"0A5!");
+        assert!(s.poll().is_ok());
+        assert_eq!(s.interface.written(), b"0\r\n"); // Default handler keeps its address
+    }
+}
@@ -0,0 +1,270 @@
+// src/testing.rs
+
+//! A scriptable fake interface for testing code that drives a `SyncRecorder`.
+//!
+//! `FakeInterface` implements `Sdi12Serial` and `Sdi12Timer` on a simulated
+//! clock: delays advance it instantly, so tests of second-long measurements
+//! run in microseconds. Each command the recorder sends is answered with the
+//! next `StagedReply`, whose bytes become readable after configurable delays,
+//! which is how slow or stalling sensors are simulated. Everything sent is
+//! logged for assertions.
+//!
+//! ```ignore
+//! let mut bus = FakeInterface::new();
+//! bus.stage(StagedReply::new(b"0\r\n").start_delay(Duration::from_millis(200)));
+//! bus.stage(StagedReply::new(b"0\r\n"));
+//! let mut recorder = SyncRecorder::new(bus);
+//! recorder.acknowledge(address)?; // The first attempt times out, the retry succeeds
+//! assert_eq!(recorder.interface().sent().len(), 2);
+//! ```
+
+use crate::common::{BaudRate, FrameFormat, Sdi12Serial, Sdi12Timer};
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::time::Duration;
+
+/// A point on `FakeInterface`'s simulated clock, in microseconds since the
+/// interface was created.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct FakeInstant(pub u64);
+
+impl core::ops::Add<Duration> for FakeInstant {
+    type Output = Self;
+    fn add(self, rhs: Duration) -> Self {
+        FakeInstant(self.0.saturating_add(rhs.as_micros() as u64))
+    }
+}
+
+impl core::ops::Sub<FakeInstant> for FakeInstant {
+    type Output = Duration;
+    fn sub(self, rhs: FakeInstant) -> Duration {
+        Duration::from_micros(self.0.saturating_sub(rhs.0))
+    }
+}
+
+/// The answer to one command, with its timing.
+///
+/// Bytes become readable all at once by default; `start_delay` and
+/// `byte_delay` spread them out like a slow sensor would.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StagedReply {
+    bytes: Vec<u8>,
+    start_delay: Duration,
+    byte_delay: Duration,
+}
+
+impl StagedReply {
+    /// A reply of `bytes`, `<CR><LF>` included, readable as soon as the
+    /// command's `!` was written.
+    pub fn new(bytes: &[u8]) -> Self {
+        StagedReply { bytes: bytes.to_vec(), start_delay: Duration::ZERO, byte_delay: Duration::ZERO }
+    }
+
+    /// No reply at all: the sensor didn't hear the command.
+    pub fn silent() -> Self {
+        Self::new(&[])
+    }
+
+    /// Delays the first byte by `delay` after the command.
+    pub fn start_delay(mut self, delay: Duration) -> Self {
+        self.start_delay = delay;
+        self
+    }
+
+    /// Delays every byte after the first by `delay` after the one before it.
+    pub fn byte_delay(mut self, delay: Duration) -> Self {
+        self.byte_delay = delay;
+        self
+    }
+}
+
+/// A command received by `FakeInterface`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SentCommand {
+    /// The bytes written, up to and including the `!`.
+    pub bytes: Vec<u8>,
+    /// Whether a break was sent since the previous command.
+    pub after_break: bool,
+    /// When the `!` was written.
+    pub at: FakeInstant,
+}
+
+/// A fake SDI-12 interface answering from staged replies on a simulated clock.
+///
+/// A command is complete when its `!` is written; it is then answered with the
+/// next staged reply, in staging order, or not at all once they run out. Reply
+/// bytes the recorder hasn't read when the next command arrives are dropped.
+/// The fake never returns an I/O error.
+#[derive(Debug, Clone, Default)]
+pub struct FakeInterface {
+    clock_us: u64,
+    replies: VecDeque<StagedReply>,
+    /// Bytes on their way to the recorder, with the time each becomes readable.
+    incoming: VecDeque<(u64, u8)>,
+    command: Vec<u8>,
+    sent: Vec<SentCommand>,
+    break_pending: bool,
+    frame_format: Option<FrameFormat>,
+    baud_rate: Option<BaudRate>,
+}
+
+impl FakeInterface {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues the answer to the next command that doesn't have one yet.
+    pub fn stage(&mut self, reply: StagedReply) -> &mut Self {
+        self.replies.push_back(reply);
+        self
+    }
+
+    /// Makes `bytes` readable `delay` from now without a command, e.g. a
+    /// service request (`b"0\r\n"`) arriving during a measurement.
+    pub fn inject(&mut self, bytes: &[u8], delay: Duration) -> &mut Self {
+        let ready = self.clock_us + delay.as_micros() as u64;
+        self.incoming.extend(bytes.iter().map(|&b| (ready, b)));
+        self
+    }
+
+    /// Moves the simulated clock forward, like time spent outside the recorder.
+    pub fn advance(&mut self, duration: Duration) {
+        self.clock_us += duration.as_micros() as u64;
+    }
+
+    /// Every command received so far.
+    pub fn sent(&self) -> &[SentCommand] {
+        &self.sent
+    }
+
+    /// Staged replies not used yet.
+    pub fn pending_replies(&self) -> usize {
+        self.replies.len()
+    }
+
+    /// The frame format last set by the recorder, if any.
+    pub fn frame_format(&self) -> Option<FrameFormat> {
+        self.frame_format
+    }
+
+    /// The baud rate last set by the recorder, if any. Only recorded: reply
+    /// timing comes from the staged delays alone.
+    pub fn baud_rate(&self) -> Option<BaudRate> {
+        self.baud_rate
+    }
+
+    fn answer(&mut self) {
+        self.incoming.clear();
+        let Some(reply) = self.replies.pop_front() else {
+            return;
+        };
+        let mut ready = self.clock_us + reply.start_delay.as_micros() as u64;
+        for (i, &byte) in reply.bytes.iter().enumerate() {
+            if i > 0 {
+                ready += reply.byte_delay.as_micros() as u64;
+            }
+            self.incoming.push_back((ready, byte));
+        }
+    }
+}
+
+impl Sdi12Timer for FakeInterface {
+    type Instant = FakeInstant;
+
+    fn delay_us(&mut self, us: u32) {
+        self.clock_us += u64::from(us);
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        self.clock_us += u64::from(ms) * 1000;
+    }
+
+    fn now(&self) -> Self::Instant {
+        FakeInstant(self.clock_us)
+    }
+}
+
+impl Sdi12Serial for FakeInterface {
+    type Error = Infallible;
+
+    fn read_byte(&mut self) -> nb::Result<u8, Self::Error> {
+        match self.incoming.front() {
+            Some(&(ready, byte)) if ready <= self.clock_us => {
+                self.incoming.pop_front();
+                Ok(byte)
+            }
+            _ => Err(nb::Error::WouldBlock),
+        }
+    }
+
+    fn write_byte(&mut self, byte: u8) -> nb::Result<(), Self::Error> {
+        self.command.push(byte);
+        if byte == b'!' {
+            let bytes = core::mem::take(&mut self.command);
+            let after_break = core::mem::take(&mut self.break_pending);
+            self.sent.push(SentCommand { bytes, after_break, at: self.now() });
+            self.answer();
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn send_break(&mut self) -> nb::Result<(), Self::Error> {
+        self.break_pending = true;
+        self.command.clear();
+        Ok(())
+    }
+
+    fn set_config(&mut self, config: FrameFormat) -> Result<(), Self::Error> {
+        self.frame_format = Some(config);
+        Ok(())
+    }
+
+    fn set_baud_rate(&mut self, baud: BaudRate) -> Result<(), Self::Error> {
+        self.baud_rate = Some(baud);
+        Ok(())
+    }
+
+    /// Any rate: it is only recorded.
+    fn supports_baud_rate(&self, _baud: BaudRate) -> bool {
+        true
+    }
+}
+
+// --- Unit Tests ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(bus: &mut FakeInterface, command: &[u8]) {
+        for &b in command {
+            bus.write_byte(b).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_reply_bytes_follow_their_delays() {
+        let mut bus = FakeInterface::new();
+        bus.stage(StagedReply::new(b"0\r\n").start_delay(Duration::from_millis(10)).byte_delay(Duration::from_millis(2)));
+        write(&mut bus, b"0!");
+
+        assert_eq!(bus.read_byte(), Err(nb::Error::WouldBlock));
+        bus.delay_ms(10);
+        assert_eq!(bus.read_byte(), Ok(b'0'));
+        assert_eq!(bus.read_byte(), Err(nb::Error::WouldBlock));
+        bus.delay_ms(4);
+        assert_eq!(bus.read_byte(), Ok(b'\r'));
+        assert_eq!(bus.read_byte(), Ok(b'\n'));
+
+        // Out of replies: silence
+        write(&mut bus, b"1!");
+        bus.delay_ms(1000);
+        assert_eq!(bus.read_byte(), Err(nb::Error::WouldBlock));
+        assert_eq!(bus.sent().len(), 2);
+        assert_eq!(bus.sent()[1], SentCommand { bytes: b"1!".to_vec(), after_break: false, at: FakeInstant(10_000 + 4_000) });
+    }
+}
@@ -0,0 +1,92 @@
+// tests/address_validation.rs
+//
+// One table of address bytes, checked through every entry point that reads an
+// address off the wire, so their notions of a valid address can't diverge.
+
+use sdi12::common::command::Command;
+use sdi12::common::{parse_response, Response};
+use sdi12::sensor::parse_command;
+use sdi12::{Sdi12Addr, Sdi12Error};
+
+/// How an address byte must be treated.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Kind {
+    /// `0-9`, `a-z`, `A-Z`: a sensor address.
+    Sensor,
+    /// `?`: only valid in the address query `?!`.
+    Query,
+    Invalid,
+}
+
+use Kind::*;
+
+/// The boundaries of each class plus the punctuation most likely to slip through.
+const TABLE: &[(u8, Kind)] = &[
+    (b'0', Sensor), (b'9', Sensor), (b'a', Sensor), (b'z', Sensor), (b'A', Sensor), (b'Z', Sensor),
+    (b'?', Query),
+    (b'*', Invalid), (b' ', Invalid), (b'!', Invalid), (b'$', Invalid), (b'+', Invalid), (b'-', Invalid),
+    (b'/', Invalid), (b':', Invalid), (b'@', Invalid), (b'[', Invalid), (b'`', Invalid), (b'{', Invalid),
+    (b'~', Invalid), (b'\r', Invalid), (b'\n', Invalid), (0x00, Invalid), (0x7F, Invalid), (0x80, Invalid),
+    (0xFF, Invalid),
+];
+
+/// The rule the table spells out, extended to every byte.
+fn kind_of(byte: u8) -> Kind {
+    match byte {
+        b'0'..=b'9' | b'a'..=b'z' | b'A'..=b'Z' => Sensor,
+        b'?' => Query,
+        _ => Invalid,
+    }
+}
+
+fn check_entry_points(byte: u8, kind: Kind) {
+    let c = byte as char;
+
+    // Sdi12Addr::new
+    match kind {
+        Sensor | Query => assert_eq!(Sdi12Addr::new(c).map(|a| a.as_char()), Ok(c), "new({byte:#04x})"),
+        Invalid => assert_eq!(Sdi12Addr::new(c), Err(Sdi12Error::InvalidAddress(c)), "new({byte:#04x})"),
+    }
+
+    // parse_command, as the command address...
+    let expected = match kind {
+        Sensor => Ok(Command::AcknowledgeActive { address: Sdi12Addr::new(c).unwrap() }),
+        Query => Ok(Command::AddressQuery),
+        Invalid => Err(Sdi12Error::InvalidAddress(c)),
+    };
+    assert_eq!(parse_command(&[byte, b'!']), expected, "parse_command({byte:#04x})");
+
+    // ...and as the new address of aAb!
+    let change = parse_command(&[b'0', b'A', byte, b'!']);
+    assert_eq!(change.is_ok(), kind == Sensor, "change address to {byte:#04x}: {change:?}");
+
+    // parse_response: no sensor answers from `?`
+    let line = [byte, b'\r', b'\n'];
+    let response = parse_response(&line);
+    match kind {
+        Sensor => assert_eq!(response, Ok(Response::Acknowledge { address: Sdi12Addr::new(c).unwrap() })),
+        Query | Invalid => assert!(response.is_err(), "parse_response({byte:#04x}): {response:?}"),
+    }
+}
+
+#[test]
+fn table_follows_the_address_rule() {
+    for &(byte, kind) in TABLE {
+        assert_eq!(kind_of(byte), kind, "{byte:#04x}");
+    }
+}
+
+#[test]
+fn entry_points_agree_on_table() {
+    for &(byte, kind) in TABLE {
+        check_entry_points(byte, kind);
+    }
+}
+
+#[test]
+fn entry_points_agree_on_every_byte() {
+    for byte in 0..=u8::MAX {
+        check_entry_points(byte, kind_of(byte));
+        assert_eq!(Sdi12Addr::is_valid_address_char(byte as char), kind_of(byte) == Sensor, "{byte:#04x}");
+    }
+}
@@ -0,0 +1,442 @@
+// tests/alloc_measure.rs
+//
+// Drives the recorder through a whole measurement using only `alloc`:
+// run with `cargo test --no-default-features --features alloc`.
+#![cfg(feature = "alloc")]
+
+use core::time::Duration;
+use sdi12::common::command::{ContinuousIndex, IdentifyMeasurementCommand, IdentifyParameterIndex, MeasurementBase, MeasurementIndex};
+use sdi12::common::crc::{calculate_crc16, encode_crc_ascii};
+use sdi12::common::{FrameFormat, Sdi12Serial, Sdi12Timer, Sdi12Value};
+use sdi12::recorder::{CrcMode, RecorderConfig, SyncRecorder};
+use sdi12::{Sdi12Addr, Sdi12Error};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct MockInstant(u64);
+impl core::ops::Add<Duration> for MockInstant { type Output = Self; fn add(self, rhs: Duration) -> Self { MockInstant(self.0 + rhs.as_micros() as u64) } }
+impl core::ops::Sub<MockInstant> for MockInstant { type Output = Duration; fn sub(self, rhs: MockInstant) -> Duration { Duration::from_micros(self.0.saturating_sub(rhs.0)) } }
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct MockCommError;
+
+/// A sensor at address `0` that answers from a fixed script, plus a second
+/// one at `2` for concurrent measurements and a third at `3` sending garbled
+/// data; address `1` is silent.
+#[derive(Default)]
+struct ScriptedSensor {
+    clock_us: u64,
+    command: Vec<u8>,
+    pending: Vec<u8>,
+    /// Every command received, with whether a break preceded it and when it arrived.
+    log: Vec<(String, bool, u64)>,
+    break_seen: bool,
+    /// Bytes still pending when the next command arrived, i.e. never read.
+    unread: Vec<u8>,
+    /// Set by `aMC!` and `aHA!`: data responses then carry a CRC.
+    crc: bool,
+    /// If set, `aM!`/`aMC!` are followed by a service request this many µs later.
+    service_request_after: Option<u64>,
+    /// When the pending service request goes out.
+    service_request_due: Option<u64>,
+}
+
+impl ScriptedSensor {
+    fn reply(&mut self, command: &str) -> Vec<u8> {
+        if command.starts_with('1') {
+            return Vec::new();
+        }
+        let mut line = match command {
+            "0!" => b"0".to_vec(),
+            "0M!" | "0MC!" => b"00012".to_vec(),
+            "0M1!" => b"00000".to_vec(),
+            // Timing, then a (non-compliant) service request the recorder must not read
+            "0C!" => b"000102\r\n0".to_vec(),
+            "0HA!" => b"0000003".to_vec(),
+            "0V!" => b"00013".to_vec(),
+            "0D0!" => b"0+1.5-2".to_vec(),
+            "0D1!" => b"0+3".to_vec(),
+            "0R0!" | "0RC0!" => b"0+3.14".to_vec(),
+            "0IM!" => b"00012".to_vec(),
+            "0IM_001!" | "0IMC_001!" => b"0,TA,C,air temp;".to_vec(),
+            "2C!" => b"200302".to_vec(),
+            "2D0!" => b"2+7+8".to_vec(),
+            "3C!" => b"300101".to_vec(),
+            "3D0!" => b"3+1.5x".to_vec(),
+            _ => panic!("unexpected command {command}"),
+        };
+        if command.starts_with("0M") || command == "0HA!" {
+            self.crc = command != "0M!";
+            self.service_request_due = self.service_request_after.map(|after| self.clock_us + after);
+        }
+        if (command.starts_with("0D") && self.crc) || command.starts_with("0RC") || command.starts_with("0IMC") {
+            line.extend_from_slice(&encode_crc_ascii(calculate_crc16(&line)));
+        }
+        line.extend_from_slice(b"\r\n");
+        line
+    }
+}
+
+impl Sdi12Timer for ScriptedSensor {
+    type Instant = MockInstant;
+    fn delay_us(&mut self, us: u32) { self.clock_us += us as u64; }
+    fn delay_ms(&mut self, ms: u32) { self.clock_us += ms as u64 * 1000; }
+    fn now(&self) -> Self::Instant { MockInstant(self.clock_us) }
+}
+
+impl Sdi12Serial for ScriptedSensor {
+    type Error = MockCommError;
+    fn read_byte(&mut self) -> nb::Result<u8, Self::Error> {
+        if self.pending.is_empty() && self.service_request_due.is_some_and(|due| self.clock_us >= due) {
+            self.service_request_due = None;
+            self.pending = b"0\r\n".to_vec();
+        }
+        if self.pending.is_empty() { Err(nb::Error::WouldBlock) } else { Ok(self.pending.remove(0)) }
+    }
+    fn write_byte(&mut self, byte: u8) -> nb::Result<(), Self::Error> {
+        self.command.push(byte);
+        if byte == b'!' {
+            let command = String::from_utf8(core::mem::take(&mut self.command)).unwrap();
+            self.unread.append(&mut self.pending);
+            self.pending = self.reply(&command);
+            self.log.push((command, core::mem::take(&mut self.break_seen), self.clock_us));
+        }
+        Ok(())
+    }
+    fn flush(&mut self) -> nb::Result<(), Self::Error> { Ok(()) }
+    fn send_break(&mut self) -> nb::Result<(), Self::Error> { self.break_seen = true; Ok(()) }
+    fn set_config(&mut self, config: FrameFormat) -> Result<(), Self::Error> {
+        assert_eq!(config, FrameFormat::Sdi12_7e1);
+        Ok(())
+    }
+}
+
+#[test]
+fn measure_crc_runs_start_wait_and_collect() {
+    let mut recorder = SyncRecorder::new(ScriptedSensor::default());
+    let address = Sdi12Addr::new('0').unwrap();
+
+    let data = recorder.measure_crc(address, MeasurementIndex::Base).unwrap();
+
+    assert_eq!(data.address, address);
+    assert_eq!(data.values, vec![Sdi12Value::new(1.5), Sdi12Value::new(-2.0)]);
+    assert_eq!(data.crc, Some(calculate_crc16(b"0+1.5-2")));
+
+    let log = &recorder.interface().log;
+    let commands: Vec<&str> = log.iter().map(|(c, _, _)| c.as_str()).collect();
+    assert_eq!(commands, ["0MC!", "0D0!"]);
+    // A break precedes both commands, the second because the wait outlasted the marking window.
+    assert!(log.iter().all(|(_, after_break, _)| *after_break));
+    // No service request came, so data was only requested after the sensor's one-second estimate.
+    assert!(log[1].2 - log[0].2 >= 1_000_000);
+}
+
+#[test]
+fn verify_and_collect_reads_diagnostics() {
+    let mut recorder = SyncRecorder::new(ScriptedSensor::default());
+    let address = Sdi12Addr::new('0').unwrap();
+
+    let data = recorder.verify_and_collect(address).unwrap();
+
+    assert_eq!(data.values, vec![Sdi12Value::new(1.5), Sdi12Value::new(-2.0), Sdi12Value::new(3.0)]);
+    assert_eq!(data.crc, None);
+    let log = &recorder.interface().log;
+    let commands: Vec<&str> = log.iter().map(|(c, _, _)| c.as_str()).collect();
+    assert_eq!(commands, ["0V!", "0D0!", "0D1!"]);
+    assert!(log[1].2 - log[0].2 >= 1_000_000);
+}
+
+#[test]
+fn measure_proceeds_on_early_service_request() {
+    let sensor = ScriptedSensor { service_request_after: Some(300_000), ..ScriptedSensor::default() };
+    let mut recorder = SyncRecorder::new(sensor);
+    let address = Sdi12Addr::new('0').unwrap();
+
+    let data = recorder.measure(address, MeasurementIndex::Base).unwrap();
+
+    assert_eq!(data.values, vec![Sdi12Value::new(1.5), Sdi12Value::new(-2.0)]);
+    let log = &recorder.interface().log;
+    let commands: Vec<&str> = log.iter().map(|(c, _, _)| c.as_str()).collect();
+    assert_eq!(commands, ["0M!", "0D0!"]);
+    // Data was requested right after the service request, well before the one-second estimate,
+    // and the line was still active, so without a break.
+    let elapsed = log[1].2 - log[0].2;
+    assert!((300_000..400_000).contains(&elapsed), "{elapsed}");
+    assert!(!log[1].1);
+    assert!(recorder.interface().unread.is_empty());
+}
+
+#[test]
+fn begin_measure_exposes_progress_before_finish() {
+    let mut recorder = SyncRecorder::new(ScriptedSensor::default());
+    let address = Sdi12Addr::new('0').unwrap();
+
+    let mut measurement = recorder.begin_measure(address, MeasurementIndex::Base).unwrap();
+    assert_eq!(measurement.address(), address);
+    assert_eq!(measurement.values_expected(), 2);
+    assert_eq!(measurement.seconds_remaining(recorder.interface().now()), 1);
+
+    // Time spent elsewhere counts towards the wait.
+    recorder.interface_mut().delay_ms(600);
+    assert_eq!(measurement.remaining(recorder.interface().now()), Duration::from_millis(400));
+    recorder.interface_mut().delay_ms(600);
+    assert_eq!(measurement.seconds_remaining(recorder.interface().now()), 0);
+
+    let before = recorder.interface().now();
+    let data = measurement.finish(&mut recorder).unwrap();
+    assert_eq!(data.values, vec![Sdi12Value::new(1.5), Sdi12Value::new(-2.0)]);
+    // Already past the deadline, so data was requested without further waiting.
+    let log = &recorder.interface().log;
+    assert_eq!(log[1].0, "0D0!");
+    assert!(log[1].2 - before.0 < 200_000);
+}
+
+/// A 16-bit microsecond clock, wrapping every ~65 ms: a one-second `ttt`
+/// spans many wraps.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct WrapInstant(u16);
+impl core::ops::Add<Duration> for WrapInstant { type Output = Self; fn add(self, rhs: Duration) -> Self { WrapInstant(self.0.wrapping_add(rhs.as_micros() as u16)) } }
+impl core::ops::Sub<WrapInstant> for WrapInstant { type Output = Duration; fn sub(self, rhs: WrapInstant) -> Duration { Duration::from_micros(self.0.wrapping_sub(rhs.0).into()) } }
+
+/// `ScriptedSensor` behind a wrapping clock.
+struct WrappingClock(ScriptedSensor);
+
+impl Sdi12Timer for WrappingClock {
+    type Instant = WrapInstant;
+    fn delay_us(&mut self, us: u32) { self.0.delay_us(us) }
+    fn delay_ms(&mut self, ms: u32) { self.0.delay_ms(ms) }
+    fn now(&self) -> Self::Instant { WrapInstant(self.0.clock_us as u16) }
+}
+
+impl Sdi12Serial for WrappingClock {
+    type Error = MockCommError;
+    fn read_byte(&mut self) -> nb::Result<u8, Self::Error> { self.0.read_byte() }
+    fn write_byte(&mut self, byte: u8) -> nb::Result<(), Self::Error> { self.0.write_byte(byte) }
+    fn flush(&mut self) -> nb::Result<(), Self::Error> { self.0.flush() }
+    fn send_break(&mut self) -> nb::Result<(), Self::Error> { self.0.send_break() }
+    fn set_config(&mut self, config: FrameFormat) -> Result<(), Self::Error> { self.0.set_config(config) }
+}
+
+#[test]
+fn finish_waits_out_ttt_on_a_wrapping_clock() {
+    // The sensor never sends a service request, so only the full second may end the wait.
+    let mut recorder = SyncRecorder::new(WrappingClock(ScriptedSensor::default()));
+    let address = Sdi12Addr::new('0').unwrap();
+
+    let measurement = recorder.begin_measure(address, MeasurementIndex::Base).unwrap();
+    assert_eq!(measurement.ready_after(), Duration::from_secs(1));
+    let data = measurement.finish(&mut recorder).unwrap();
+
+    assert_eq!(data.values, vec![Sdi12Value::new(1.5), Sdi12Value::new(-2.0)]);
+    let log = &recorder.interface().0.log;
+    assert_eq!(log[1].0, "0D0!");
+    let elapsed = log[1].2 - log[0].2;
+    assert!((1_000_000..1_100_000).contains(&elapsed), "{elapsed}");
+}
+
+#[test]
+fn service_request_ends_the_wait_on_a_wrapping_clock() {
+    let sensor = ScriptedSensor { service_request_after: Some(300_000), ..ScriptedSensor::default() };
+    let mut recorder = SyncRecorder::new(WrappingClock(sensor));
+    let address = Sdi12Addr::new('0').unwrap();
+
+    let data = recorder.begin_measure(address, MeasurementIndex::Base).unwrap().finish(&mut recorder).unwrap();
+
+    assert_eq!(data.values, vec![Sdi12Value::new(1.5), Sdi12Value::new(-2.0)]);
+    let log = &recorder.interface().0.log;
+    let elapsed = log[1].2 - log[0].2;
+    assert!((300_000..400_000).contains(&elapsed), "{elapsed}");
+}
+
+#[test]
+fn abort_measurement_breaks_and_reacknowledges() {
+    let mut recorder = SyncRecorder::new(ScriptedSensor::default());
+    let address = Sdi12Addr::new('0').unwrap();
+
+    let measurement = recorder.begin_measure(address, MeasurementIndex::Base).unwrap();
+    recorder.interface_mut().delay_ms(100);
+    measurement.abort(&mut recorder).unwrap();
+
+    let log = &recorder.interface().log;
+    let commands: Vec<&str> = log.iter().map(|(c, _, _)| c.as_str()).collect();
+    assert_eq!(commands, ["0M!", "0!"]);
+    // Only the abort's break precedes `0!`, long before the measurement was due.
+    assert!(log[1].1);
+    assert!(log[1].2 - log[0].2 < 1_000_000);
+}
+
+#[test]
+fn poll_service_request_reads_only_pending_requests() {
+    let sensor = ScriptedSensor { service_request_after: Some(300_000), ..ScriptedSensor::default() };
+    let mut recorder = SyncRecorder::new(sensor);
+    let address = Sdi12Addr::new('0').unwrap();
+
+    let measurement = recorder.begin_measure(address, MeasurementIndex::Base).unwrap();
+    let before = recorder.interface().now();
+    assert_eq!(recorder.poll_service_request(), None);
+    // Nothing pending, so the poll didn't wait.
+    assert_eq!(recorder.interface().now(), before);
+
+    recorder.interface_mut().delay_ms(300);
+    assert_eq!(recorder.poll_service_request(), Some(address));
+    assert_eq!(recorder.poll_service_request(), None);
+
+    // The consumed request doesn't end the wait early a second time.
+    let data = measurement.finish(&mut recorder).unwrap();
+    assert_eq!(data.values, vec![Sdi12Value::new(1.5), Sdi12Value::new(-2.0)]);
+    let log = &recorder.interface().log;
+    assert!(log[1].2 - log[0].2 >= 1_000_000);
+}
+
+#[test]
+fn high_volume_ascii_reads_crc_checked_registers() {
+    let mut recorder = SyncRecorder::new(ScriptedSensor::default());
+    let address = Sdi12Addr::new('0').unwrap();
+
+    let timing = recorder.start_high_volume_ascii(address).unwrap();
+    assert_eq!((timing.time_seconds, timing.values_count), (0, 3));
+
+    let data = recorder.collect_high_volume_ascii(address, timing.values_count.into()).unwrap();
+    assert_eq!(data.values, vec![Sdi12Value::new(1.5), Sdi12Value::new(-2.0), Sdi12Value::new(3.0)]);
+    let commands: Vec<&str> = recorder.interface().log.iter().map(|(c, _, _)| c.as_str()).collect();
+    assert_eq!(commands, ["0HA!", "0D0!", "0D1!"]);
+}
+
+#[test]
+fn measure_without_values_sends_no_data_command() {
+    let mut recorder = SyncRecorder::new(ScriptedSensor::default());
+    let address = Sdi12Addr::new('0').unwrap();
+    let index = MeasurementIndex::Indexed(1);
+
+    let data = recorder.measure(address, index).unwrap();
+
+    assert_eq!(data.address, address);
+    assert!(data.values.is_empty());
+    assert_eq!(data.crc, None);
+    let log = &recorder.interface().log;
+    assert_eq!(log.len(), 1);
+    assert_eq!(log[0].0, "0M1!");
+}
+
+#[test]
+fn measure_all_stops_at_first_empty_group() {
+    let mut recorder = SyncRecorder::new(ScriptedSensor::default());
+    let address = Sdi12Addr::new('0').unwrap();
+
+    let groups = recorder.measure_all(address).unwrap();
+
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].values, vec![Sdi12Value::new(1.5), Sdi12Value::new(-2.0)]);
+    let commands: Vec<&str> = recorder.interface().log.iter().map(|(c, _, _)| c.as_str()).collect();
+    assert_eq!(commands, ["0M!", "0D0!", "0M1!"]);
+}
+
+#[test]
+#[allow(clippy::approx_constant)] // Spec CRC example uses +3.14
+fn crc_mode_selects_command_variants() {
+    let address = Sdi12Addr::new('0').unwrap();
+    let always = RecorderConfig { crc_mode: CrcMode::Always, ..RecorderConfig::default() };
+    let mut recorder = SyncRecorder::with_config(ScriptedSensor::default(), always);
+
+    let data = recorder.measure(address, MeasurementIndex::Base).unwrap();
+    assert_eq!(data.values, vec![Sdi12Value::new(1.5), Sdi12Value::new(-2.0)]);
+    let data = recorder.read_continuous(address, ContinuousIndex::new(0).unwrap()).unwrap();
+    assert_eq!(data.values, vec![Sdi12Value::new(3.14)]);
+    assert_eq!(data.crc, Some(0xFC5A)); // "0+3.14OqZ"
+    let commands: Vec<&str> = recorder.interface().log.iter().map(|(c, _, _)| c.as_str()).collect();
+    assert_eq!(commands, ["0MC!", "0D0!", "0RC0!"]);
+
+    let never = RecorderConfig { crc_mode: CrcMode::Never, ..RecorderConfig::default() };
+    let mut recorder = SyncRecorder::with_config(ScriptedSensor::default(), never);
+    recorder.measure_crc(address, MeasurementIndex::Base).unwrap();
+    let data = recorder.read_continuous(address, ContinuousIndex::new(0).unwrap()).unwrap();
+    assert_eq!(data.crc, None);
+    let commands: Vec<&str> = recorder.interface().log.iter().map(|(c, _, _)| c.as_str()).collect();
+    assert_eq!(commands, ["0M!", "0D0!", "0R0!"]);
+}
+
+#[test]
+fn concurrent_measure_never_reads_a_service_request() {
+    let mut recorder = SyncRecorder::new(ScriptedSensor::default());
+    let address = Sdi12Addr::new('0').unwrap();
+
+    let measurement = recorder.begin_concurrent_measure(address, MeasurementIndex::Base).unwrap();
+    assert!(!measurement.allows_service_request());
+    assert_eq!(measurement.values_expected(), 2);
+    let data = measurement.finish(&mut recorder).unwrap();
+    assert_eq!(data.values, vec![Sdi12Value::new(1.5), Sdi12Value::new(-2.0)]);
+
+    let log = &recorder.interface().log;
+    let commands: Vec<&str> = log.iter().map(|(c, _, _)| c.as_str()).collect();
+    assert_eq!(commands, ["0C!", "0D0!"]);
+    // The full `ttt` was waited out and the stray `0<CR><LF>` left on the line.
+    assert!(log[1].2 - log[0].2 >= 1_000_000);
+    assert_eq!(recorder.interface().unread, b"0\r\n");
+    assert!(recorder.begin_measure(address, MeasurementIndex::Base).unwrap().allows_service_request());
+}
+
+#[test]
+fn measure_concurrent_all_waits_once_and_reports_each_sensor() {
+    let mut recorder = SyncRecorder::new(ScriptedSensor::default());
+    let addresses: Vec<Sdi12Addr> = "012".chars().map(|c| Sdi12Addr::new(c).unwrap()).collect();
+
+    let results = recorder.measure_concurrent_all(&addresses).unwrap();
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0], (addresses[0], Ok(sdi12::common::DataInfo { address: addresses[0], values: vec![Sdi12Value::new(1.5), Sdi12Value::new(-2.0)], crc: None, crc_valid: None })));
+    assert_eq!(results[1].0, addresses[1]);
+    assert!(matches!(results[1].1, Err(Sdi12Error::RetriesExhausted { .. })), "{:?}", results[1].1);
+    assert_eq!(results[2].0, addresses[2]);
+    assert_eq!(results[2].1.as_ref().unwrap().values, vec![Sdi12Value::new(7.0), Sdi12Value::new(8.0)]);
+
+    let log = &recorder.interface().log;
+    let commands: Vec<&str> = log.iter().map(|(c, _, _)| c.as_str()).collect();
+    assert_eq!(commands, ["0C!", "1C!", "1C!", "1C!", "2C!", "0D0!", "2D0!"]);
+    // Each sensor was read once its own ttt had passed, and the waits overlapped.
+    assert!(log[5].2 - log[0].2 >= 1_000_000);
+    assert!(log[6].2 - log[4].2 >= 3_000_000);
+    assert!(log[6].2 - log[0].2 < 4_000_000);
+}
+
+#[test]
+fn measure_concurrent_all_reports_garbled_data_as_an_error() {
+    let mut recorder = SyncRecorder::new(ScriptedSensor::default());
+    let address = Sdi12Addr::new('3').unwrap();
+
+    let results = recorder.measure_concurrent_all(&[address]).unwrap();
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].0, address);
+    // Unlike a silent sensor, this one answered: the error says what was wrong with the data.
+    assert!(matches!(results[0].1, Err(Sdi12Error::ParseError(_))), "{:?}", results[0].1);
+}
+
+#[test]
+fn identify_measurement_reads_timing_only() {
+    let mut recorder = SyncRecorder::new(ScriptedSensor::default());
+    let address = Sdi12Addr::new('0').unwrap();
+
+    let command = IdentifyMeasurementCommand::Measurement { address, index: MeasurementIndex::Base };
+    let timing = recorder.identify_measurement(command).unwrap();
+
+    assert_eq!((timing.address, timing.time_seconds, timing.values_count), (address, 1, 2));
+    // Neither waited for nor asked for data
+    let log = &recorder.interface().log;
+    assert_eq!(log.len(), 1);
+    assert_eq!(log[0].0, "0IM!");
+    assert!(recorder.interface().now().0 - log[0].2 < 200_000);
+}
+
+#[test]
+fn identify_parameter_checks_crc_of_crc_variants() {
+    let mut recorder = SyncRecorder::new(ScriptedSensor::default());
+    let address = Sdi12Addr::new('0').unwrap();
+    let param = IdentifyParameterIndex::new(1).unwrap();
+
+    let plain = recorder.identify_parameter(address, MeasurementBase::Measurement(MeasurementIndex::Base), param).unwrap();
+    assert_eq!(plain.fields, ["TA", "C", "air temp"]);
+    assert_eq!(plain.crc, None);
+
+    let checked = recorder.identify_parameter(address, MeasurementBase::MeasurementCRC(MeasurementIndex::Base), param).unwrap();
+    assert_eq!(checked.fields, plain.fields);
+    assert_eq!(checked.crc, Some(calculate_crc16(b"0,TA,C,air temp;")));
+}
@@ -0,0 +1,165 @@
+// tests/fake_interface.rs
+//
+// The recorder's timing against slow sensors, simulated with the public fake:
+// run with `cargo test --features test-util`.
+#![cfg(feature = "test-util")]
+
+use core::time::Duration;
+use sdi12::common::command::DataIndex;
+use sdi12::common::crc::{calculate_crc16, encode_crc_binary};
+use sdi12::common::command::Command;
+use sdi12::common::{
+    parse_binary_packet, BaudRate, FrameFormat, RetryCause, BINARY_PACKET_MAX_PAYLOAD, BINARY_PACKET_OVERHEAD,
+};
+use sdi12::recorder::{NoTrace, RecorderConfig, SyncRecorder, TraceEvent};
+use sdi12::testing::{FakeInterface, StagedReply};
+use sdi12::{Sdi12Addr, Sdi12Error};
+
+fn address() -> Sdi12Addr {
+    Sdi12Addr::new('0').unwrap()
+}
+
+#[test]
+fn late_response_is_retried() {
+    let mut bus = FakeInterface::new();
+    bus.stage(StagedReply::new(b"0\r\n").start_delay(Duration::from_millis(200)));
+    bus.stage(StagedReply::new(b"0\r\n"));
+    let mut recorder = SyncRecorder::new(bus);
+
+    assert_eq!(recorder.acknowledge(address()), Ok(()));
+
+    let bus = recorder.interface();
+    assert_eq!(bus.sent().len(), 2);
+    assert!(bus.sent()[0].after_break);
+    assert_eq!(bus.frame_format(), Some(FrameFormat::Sdi12_7e1));
+    // The retry went out once the first attempt's timeout had passed.
+    let gap = bus.sent()[1].at - bus.sent()[0].at;
+    assert!(gap < Duration::from_millis(200), "{gap:?}");
+}
+
+#[test]
+fn trace_sink_sees_every_protocol_step() {
+    let mut bus = FakeInterface::new();
+    bus.stage(StagedReply::new(b"0\r\n").start_delay(Duration::from_millis(200)));
+    bus.stage(StagedReply::new(b"0\r\n"));
+    let mut log = Vec::new();
+    let mut recorder = SyncRecorder::new(bus).with_trace(|event: TraceEvent<'_>| {
+        log.push(match event {
+            TraceEvent::Tx(bytes) => format!("tx {}", bytes.escape_ascii()),
+            TraceEvent::Rx(bytes) => format!("rx {}", bytes.escape_ascii()),
+            other => format!("{other:?}"),
+        });
+    });
+    assert_eq!(recorder.acknowledge(address()), Ok(()));
+
+    // Swapping the sink out ends the closure's borrow of `log`.
+    let mut recorder = recorder.with_trace(NoTrace);
+    assert_eq!(log, ["BreakSent", "tx 0!", "Timeout", "Retry(2)", "tx 0!", "rx 0\\r\\n"]);
+    recorder.interface_mut().stage(StagedReply::new(b"0\r\n"));
+    assert_eq!(recorder.acknowledge(address()), Ok(()));
+    assert_eq!(log.len(), 6);
+}
+
+#[test]
+fn slow_characters_need_a_longer_inter_char_timeout() {
+    let slow = || StagedReply::new(b"0\r\n").byte_delay(Duration::from_millis(12));
+
+    let mut bus = FakeInterface::new();
+    bus.stage(slow()).stage(slow()).stage(slow());
+    let mut recorder = SyncRecorder::new(bus);
+    assert_eq!(
+        recorder.acknowledge(address()),
+        Err(Sdi12Error::RetriesExhausted { attempts: 3, last: RetryCause::InterCharTimeout })
+    );
+
+    let mut bus = FakeInterface::new();
+    bus.stage(slow());
+    let config = RecorderConfig { inter_char_timeout: Duration::from_millis(15), ..RecorderConfig::default() };
+    let mut recorder = SyncRecorder::with_config(bus, config);
+    assert_eq!(recorder.acknowledge(address()), Ok(()));
+    assert_eq!(recorder.interface().pending_replies(), 0);
+}
+
+#[test]
+fn injected_service_request_is_polled() {
+    let mut bus = FakeInterface::new();
+    bus.inject(b"0\r\n", Duration::from_millis(50));
+    let mut recorder = SyncRecorder::new(bus);
+
+    assert_eq!(recorder.poll_service_request(), None);
+    recorder.interface_mut().advance(Duration::from_millis(50));
+    assert_eq!(recorder.poll_service_request(), Some(address()));
+}
+
+/// A `SignedI16` packet from sensor `0` carrying `payload`, CRC included.
+fn binary_packet(payload: &[u8]) -> Vec<u8> {
+    let mut packet = vec![b'0'];
+    packet.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+    packet.push(3); // SignedI16
+    packet.extend_from_slice(payload);
+    let crc = calculate_crc16(&packet);
+    packet.extend_from_slice(&encode_crc_binary(crc));
+    packet
+}
+
+#[test]
+fn maximal_binary_packet_is_read() {
+    let payload: Vec<u8> = (0..BINARY_PACKET_MAX_PAYLOAD).map(|i| i as u8).collect();
+    let packet = binary_packet(&payload);
+    let d0 = DataIndex::new(0).unwrap();
+
+    let mut bus = FakeInterface::new();
+    bus.stage(StagedReply::new(&packet));
+    let mut recorder = SyncRecorder::new(bus);
+    let mut buffer = [0u8; BINARY_PACKET_MAX_PAYLOAD + BINARY_PACKET_OVERHEAD];
+    let read = recorder.send_binary_data(address(), d0, &mut buffer).unwrap();
+    assert_eq!(read, &packet[..]);
+    let info = parse_binary_packet(read).unwrap();
+    assert_eq!(info.as_i16_slice().unwrap().len(), 500);
+    assert_eq!(recorder.interface().sent()[0].bytes, b"0DB0!");
+    assert_eq!(recorder.interface().frame_format(), Some(FrameFormat::Sdi12_7e1));
+
+    // One byte short: the packet is read out, then refused
+    let mut bus = FakeInterface::new();
+    bus.stage(StagedReply::new(&packet)).stage(StagedReply::new(b"0\r\n"));
+    let mut recorder = SyncRecorder::new(bus);
+    let mut small = [0u8; BINARY_PACKET_MAX_PAYLOAD + BINARY_PACKET_OVERHEAD - 1];
+    assert_eq!(
+        recorder.send_binary_data(address(), d0, &mut small),
+        Err(Sdi12Error::BufferOverflow { needed: 1006, got: 1005 })
+    );
+    assert_eq!(recorder.acknowledge(address()), Ok(()));
+    assert_eq!(recorder.interface().sent().len(), 2);
+}
+
+#[test]
+fn corrupted_binary_packet_is_rejected() {
+    let mut packet = binary_packet(&[1, 0, 2, 0]);
+    packet[5] ^= 0xFF;
+    let mut bus = FakeInterface::new();
+    bus.stage(StagedReply::new(&packet));
+    let mut recorder = SyncRecorder::new(bus);
+    let mut buffer = [0u8; 16];
+    let result = recorder.send_binary_data(address(), DataIndex::new(0).unwrap(), &mut buffer);
+    assert!(matches!(result, Err(Sdi12Error::CrcMismatch { .. })), "{result:?}");
+    assert_eq!(recorder.interface().frame_format(), Some(FrameFormat::Sdi12_7e1));
+}
+
+#[test]
+fn baud_rate_reaches_the_interface_and_scales_timeouts() {
+    let identify = Command::SendIdentification { address: address() };
+    let mut bus = FakeInterface::new();
+    bus.stage(StagedReply::new(b"0\r\n")).stage(StagedReply::new(b"0\r\n"));
+    let mut recorder = SyncRecorder::new(bus);
+    let standard_timeout = recorder.default_response_timeout(&identify);
+
+    assert_eq!(recorder.acknowledge(address()), Ok(()));
+    assert_eq!(recorder.interface().baud_rate(), Some(BaudRate::STANDARD));
+
+    // Changed between commands: applied before the next one
+    let fast = BaudRate::new(9600).unwrap();
+    recorder.config_mut().baud_rate = fast;
+    assert_eq!(recorder.acknowledge(address()), Ok(()));
+    assert_eq!(recorder.interface().baud_rate(), Some(fast));
+    assert!(recorder.default_response_timeout(&identify) < standard_timeout);
+}
@@ -0,0 +1,185 @@
+// tests/parse_robustness.rs
+//
+// Feeds pseudo-random and mutated response lines to the parsers, and command
+// strings to the sensor-side `parse_command`, and checks they return `Ok` or
+// `Err` without panicking. Deterministic (fixed seed), so a failure
+// reproduces; run with `--features alloc` to cover the data, metadata,
+// binary and extended command paths too.
+
+use sdi12::common::command::{Command, DataIndex, MeasurementIndex};
+use sdi12::common::{parse_response, parse_response_expecting, parse_response_for, parse_response_with_options, ParseOptions};
+use sdi12::sensor::parse_command;
+use sdi12::Sdi12Addr;
+
+/// xorshift64*: enough randomness for input generation, no dependencies.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    /// A byte biased towards the characters the parsers branch on.
+    fn byte(&mut self) -> u8 {
+        const INTERESTING: &[u8] = b"0123456789+-.,;:\r\n aZz@~\x7f\x00\xff";
+        if self.below(4) == 0 { self.next() as u8 } else { INTERESTING[self.below(INTERESTING.len())] }
+    }
+
+    /// A byte biased towards the characters `parse_command` branches on,
+    /// including the lead and continuation bytes of multi-byte UTF-8.
+    fn command_byte(&mut self) -> u8 {
+        const INTERESTING: &[u8] = b"0123456789!?_ABCDHIMRVXaz\x7f\x00\xc3\xa9\xe2\x82\xac";
+        if self.below(4) == 0 { self.next() as u8 } else { INTERESTING[self.below(INTERESTING.len())] }
+    }
+}
+
+/// Well-formed lines of every shape, the starting points for mutation.
+const SEEDS: &[&[u8]] = &[
+    b"0\r\n",
+    b"00012\r\n",
+    b"000102\r\n",
+    b"0000003\r\n",
+    b"013VENDORXYMODEL1100SN4KEY\r\n",
+    b"014TESTCORPMODEL1001\r\n",
+    b"0+3.14OqZ\r\n",
+    b"1+1.5-2+30.25\r\n",
+    b"0,RP,mm/h,rainfall rate;\r\n",
+    b"0,TA,C,air temp,SN123;Ipz\r\n",
+    b"0XOK=12\r\n",
+];
+
+/// Valid commands of every shape, the starting points for `parse_command`.
+const COMMAND_SEEDS: &[&[u8]] = &[
+    b"0!", b"?!", b"0I!", b"0A1!", b"0M!", b"0MC9!", b"0C1!", b"0CC!", b"0D0!", b"0DB999!", b"0R0!",
+    b"0RC9!", b"0V!", b"0HA!", b"0HB!", b"0IM!", b"0IMC1!", b"0IV!", b"0ICC9!", b"0IHB!", b"0IM_001!",
+    b"0IMC1_999!", b"0IR0_001!", b"0IRC9_100!", b"0IHA_050!", b"0XOK!",
+];
+
+fn mutate(rng: &mut Rng, line: &mut Vec<u8>) {
+    mutate_with(rng, line, Rng::byte)
+}
+
+fn mutate_with(rng: &mut Rng, line: &mut Vec<u8>, byte: fn(&mut Rng) -> u8) {
+    for _ in 0..=rng.below(4) {
+        match rng.below(4) {
+            0 if !line.is_empty() => {
+                let i = rng.below(line.len());
+                line[i] = byte(rng);
+            }
+            1 => {
+                let i = rng.below(line.len() + 1);
+                line.insert(i, byte(rng));
+            }
+            2 if !line.is_empty() => {
+                line.remove(rng.below(line.len()));
+            }
+            _ => line.truncate(rng.below(line.len() + 1)),
+        }
+    }
+}
+
+fn exercise(line: &[u8], commands: &[Command]) {
+    let _ = parse_response(line);
+    let _ = parse_response_expecting(line, true);
+    let _ = parse_response_expecting(line, false);
+    let lenient = ParseOptions { accept_bare_lf: true, accept_extended_responses: true, ..ParseOptions::default() };
+    let _ = parse_response_with_options(line, lenient);
+    for command in commands {
+        let _ = parse_response_for(command, line);
+    }
+    #[cfg(feature = "alloc")]
+    let _ = sdi12::common::parse_binary_packet(line);
+    #[cfg(feature = "use_heapless")]
+    let _ = sdi12::common::parse_response_heapless::<4>(line);
+}
+
+#[test]
+fn parsers_never_panic_on_mutated_lines() {
+    let address = Sdi12Addr::new('0').unwrap();
+    let commands = [
+        Command::AcknowledgeActive { address },
+        Command::SendIdentification { address },
+        Command::StartMeasurement { address, index: MeasurementIndex::Base },
+        Command::StartConcurrentMeasurement { address, index: MeasurementIndex::Base },
+        Command::StartHighVolumeASCII { address },
+        Command::SendData { address, index: DataIndex::new(0).unwrap() },
+    ];
+    let mut rng = Rng(0x5D12);
+    for _ in 0..20_000 {
+        let mut line = SEEDS[rng.below(SEEDS.len())].to_vec();
+        mutate(&mut rng, &mut line);
+        exercise(&line, &commands);
+    }
+}
+
+#[test]
+fn parsers_never_panic_on_random_bytes() {
+    let mut rng = Rng(0xC0FFEE);
+    for _ in 0..20_000 {
+        let len = rng.below(48);
+        let line: Vec<u8> = (0..len).map(|_| rng.byte()).collect();
+        exercise(&line, &[]);
+        // Binary packets: a plausible header in front of random bytes
+        let mut packet = vec![b'0', len as u8, 0, rng.below(12) as u8];
+        packet.extend_from_slice(&line);
+        exercise(&packet, &[]);
+    }
+}
+
+/// Parses `bytes` as a sensor would; a command that parses must format back
+/// into one that parses the same (leading zeros in `aD007!` aside, the
+/// formatted form is the canonical one).
+fn exercise_command(bytes: &[u8]) {
+    let Ok(command) = parse_command(bytes) else {
+        return;
+    };
+    if let Ok(formatted) = command.format_into() {
+        assert_eq!(parse_command(formatted.as_bytes()).as_ref(), Ok(&command), "{:?}", bytes.escape_ascii().to_string());
+        assert_eq!(command.formatted_len(), formatted.len());
+    }
+}
+
+#[test]
+fn parse_command_never_panics_on_mutated_commands() {
+    let mut rng = Rng(0x5E45);
+    for _ in 0..50_000 {
+        let mut bytes = COMMAND_SEEDS[rng.below(COMMAND_SEEDS.len())].to_vec();
+        mutate_with(&mut rng, &mut bytes, Rng::command_byte);
+        exercise_command(&bytes);
+    }
+}
+
+#[test]
+fn parse_command_never_panics_on_random_bytes() {
+    let mut rng = Rng(0xBADC0DE);
+    for _ in 0..50_000 {
+        let len = rng.below(24);
+        let mut bytes: Vec<u8> = (0..len).map(|_| rng.command_byte()).collect();
+        // Most commands must end in `!` to get past the first check
+        if rng.below(4) != 0 {
+            bytes.push(b'!');
+        }
+        exercise_command(&bytes);
+    }
+}
+
+#[test]
+fn parse_command_never_panics_on_short_commands() {
+    // Every address byte followed by every one- and two-byte body
+    for address in 0..=u8::MAX {
+        exercise_command(&[address, b'!']);
+        for b1 in 0..=u8::MAX {
+            exercise_command(&[address, b1, b'!']);
+            for b2 in (b'0'..=b'9').chain(*b"ABCDHIMRV_\xc3\xa9") {
+                exercise_command(&[address, b1, b2, b'!']);
+            }
+        }
+    }
+}